@@ -0,0 +1,460 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::app::breach::BreachCorpus;
+use crate::app::error::{AppError, Result};
+use crate::app::history::format_timestamp;
+use crate::app::index::list_entries;
+use crate::app::keyring::{get_entry_record, get_from_keyring};
+use crate::app::otp::get_otp_expiry;
+use crate::app::password::entry_entropy;
+use crate::app::profile::active_profile;
+use crate::app::store::store;
+use crate::app::strength::{estimate_strength, Pattern, Strength, StrengthEstimate};
+
+/// Passwords last changed longer ago than this, in days, are reported when no other age is given.
+pub const DEFAULT_MAX_AGE_DAYS: u64 = 365;
+
+/// The kinds of problems `apppass audit` reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingKind {
+    /// A password weaker than the required strength.
+    Weak,
+    /// A custom password found in the breach corpus (`password.breach_file`).
+    Breached,
+    /// The same password used by several entries.
+    Reused,
+    /// A password not changed for longer than the maximum age.
+    Old,
+    /// An entry with neither a username nor a URL, so it is unclear what it logs into.
+    MissingMetadata,
+    /// A one-time password past its expiry that was not cleaned up.
+    ExpiredOtp,
+}
+
+impl FindingKind {
+    /// Every kind, in report order.
+    pub const ALL: [FindingKind; 6] = [
+        FindingKind::Weak,
+        FindingKind::Breached,
+        FindingKind::Reused,
+        FindingKind::Old,
+        FindingKind::MissingMetadata,
+        FindingKind::ExpiredOtp,
+    ];
+
+    /// Names of the kinds as accepted on the command line, in the order of `ALL`.
+    pub const NAMES: [&'static str; 6] = ["weak", "breached", "reused", "old", "missing-metadata", "expired-otp"];
+
+    /// Heading of the kind in text and Markdown reports.
+    pub fn title(self) -> &'static str {
+        match self {
+            FindingKind::Weak => "Weak passwords",
+            FindingKind::Breached => "Breached passwords",
+            FindingKind::Reused => "Reused passwords",
+            FindingKind::Old => "Old passwords",
+            FindingKind::MissingMetadata => "Entries missing metadata",
+            FindingKind::ExpiredOtp => "Expired OTP leftovers",
+        }
+    }
+}
+
+impl fmt::Display for FindingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(FindingKind::NAMES[*self as usize])
+    }
+}
+
+impl FromStr for FindingKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace('_', "-");
+        FindingKind::NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|i| FindingKind::ALL[i])
+            .ok_or_else(|| format!("unknown finding '{}' (expected one of: {})", s.trim(), FindingKind::NAMES.join(", ")))
+    }
+}
+
+/// One problem found by the audit. Never holds a password.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    /// What kind of problem this is.
+    pub kind: FindingKind,
+    /// The entries concerned: one, or every entry sharing the password for `Reused`.
+    pub entries: Vec<String>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+/// How many entries of each type the audit looked at.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EntryBreakdown {
+    /// Every entry.
+    pub total: usize,
+    /// Passwords chosen by the user.
+    pub custom: usize,
+    /// Generated passwords and passphrases.
+    pub generated: usize,
+    /// Generated PINs.
+    pub pin: usize,
+    /// One-time passwords, expired or not.
+    pub otp: usize,
+}
+
+/// What the audit checks against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditOptions {
+    /// Passwords weaker than this are reported. PINs are never rated.
+    pub min_strength: Strength,
+    /// Passwords last changed more than this many days ago are reported.
+    pub max_age_days: u64,
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            min_strength: Strength::Fair,
+            max_age_days: DEFAULT_MAX_AGE_DAYS,
+        }
+    }
+}
+
+/// The result of auditing a store, printed by `apppass audit` as text, JSON or Markdown.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditReport {
+    /// Profile that was audited.
+    pub profile: String,
+    /// Backend that was audited.
+    pub backend: String,
+    /// Unix timestamp of the audit.
+    pub timestamp: u64,
+    /// Whether custom passwords were looked up in a breach corpus.
+    pub breach_checked: bool,
+    /// Entries audited, by type.
+    pub entries: EntryBreakdown,
+    /// Problems found, grouped by kind in the order of `FindingKind::ALL`, then by entry.
+    pub findings: Vec<Finding>,
+}
+
+impl AuditReport {
+    /// Returns the number of findings of `kind`.
+    pub fn count(&self, kind: FindingKind) -> usize {
+        self.findings.iter().filter(|f| f.kind == kind).count()
+    }
+
+    /// Returns true if there is a finding of one of `kinds`, which should fail a CI check.
+    pub fn has_findings(&self, kinds: &[FindingKind]) -> bool {
+        self.findings.iter().any(|f| kinds.contains(&f.kind))
+    }
+
+    /// Formats the report as plain text for a terminal.
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "Audit of profile '{}' ({} backend), {}\n{}\n",
+            self.profile,
+            self.backend,
+            format_timestamp(self.timestamp),
+            self.breakdown()
+        );
+        for kind in FindingKind::ALL {
+            let findings: Vec<&Finding> = self.findings.iter().filter(|f| f.kind == kind).collect();
+            if !findings.is_empty() {
+                out.push_str(&format!("\n{} ({})\n", kind.title(), findings.len()));
+                for finding in findings {
+                    out.push_str(&format!("  - {}: {}\n", finding.entries.join(", "), finding.message));
+                }
+            }
+        }
+        out.push_str(&format!("\n{}\n", self.summary()));
+        out
+    }
+
+    /// Formats the report as a Markdown document.
+    pub fn to_markdown(&self) -> String {
+        let e = &self.entries;
+        let mut out = format!(
+            "# apppass security audit\n\nProfile `{}` ({} backend), {}.\n\n\
+             | Entries | Custom | Generated | PIN | OTP |\n|---:|---:|---:|---:|---:|\n| {} | {} | {} | {} | {} |\n",
+            self.profile,
+            self.backend,
+            format_timestamp(self.timestamp),
+            e.total,
+            e.custom,
+            e.generated,
+            e.pin,
+            e.otp
+        );
+        for kind in FindingKind::ALL {
+            let findings: Vec<&Finding> = self.findings.iter().filter(|f| f.kind == kind).collect();
+            if !findings.is_empty() {
+                out.push_str(&format!("\n## {} ({})\n\n", kind.title(), findings.len()));
+                for finding in findings {
+                    let names: Vec<String> = finding.entries.iter().map(|name| format!("**{}**", escape_markdown(name))).collect();
+                    out.push_str(&format!("- {}: {}\n", names.join(", "), escape_markdown(&finding.message)));
+                }
+            }
+        }
+        out.push_str(&format!("\n{}\n", self.summary()));
+        out
+    }
+
+    /// The entry counts as one line.
+    fn breakdown(&self) -> String {
+        let e = &self.entries;
+        format!(
+            "{} entries: {} custom, {} generated, {} PIN, {} OTP",
+            e.total, e.custom, e.generated, e.pin, e.otp
+        )
+    }
+
+    /// The closing line: the number of findings, and whether breaches were checked.
+    fn summary(&self) -> String {
+        let mut summary = match self.findings.len() {
+            0 => "No findings.".to_string(),
+            1 => "1 finding.".to_string(),
+            n => format!("{} findings.", n),
+        };
+        if !self.breach_checked {
+            summary.push_str(" Breached passwords were not checked (password.breach_file is not set).");
+        }
+        summary
+    }
+}
+
+/// Escapes the characters Markdown would interpret.
+fn escape_markdown(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Audits every entry of the active store: weak, breached, reused and old passwords, entries
+/// missing a username and URL, and expired OTPs left behind. Nothing is changed.
+///
+/// # Arguments
+///
+/// * `options` - The required strength and the maximum age of passwords.
+///
+/// # Returns
+///
+/// * `Result<AuditReport>` - The report, or an `AppError` if an entry or the breach corpus
+///   cannot be read. Entries listed in the index without a password are skipped (see `apppass doctor`).
+pub fn audit(options: &AuditOptions) -> Result<AuditReport> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    audit_at(options, now)
+}
+
+/// Audits the store like `audit`, as if the current time were `now` (a Unix timestamp).
+pub fn audit_at(options: &AuditOptions, now: u64) -> Result<AuditReport> {
+    let mut corpus = BreachCorpus::configured()?;
+    let mut report = AuditReport {
+        profile: active_profile(),
+        backend: store().name().to_string(),
+        timestamp: now,
+        breach_checked: corpus.is_some(),
+        entries: EntryBreakdown::default(),
+        findings: Vec::new(),
+    };
+    let finding = |kind, name: &str, message: String| Finding {
+        kind,
+        entries: vec![name.to_string()],
+        message,
+    };
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut by_password: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for entry in list_entries()? {
+        let name = entry.name.as_str();
+        let password = match get_from_keyring(name) {
+            Ok(password) => password,
+            Err(AppError::NotFound(_)) => continue,
+            Err(e) => return Err(e),
+        };
+        report.entries.total += 1;
+
+        if let Some(expiry) = get_otp_expiry(name) {
+            report.entries.otp += 1;
+            if now >= expiry {
+                let message = format!("expired on {} but is still stored", format_timestamp(expiry));
+                findings.push(finding(FindingKind::ExpiredOtp, name, message));
+            }
+            // One-time passwords are short-lived, the other checks do not apply
+            continue;
+        }
+
+        let record = get_entry_record(name)?;
+        let estimate = match entry.password_type.as_str() {
+            "pin" => {
+                report.entries.pin += 1;
+                None
+            }
+            "custom" => {
+                report.entries.custom += 1;
+                Some(estimate_strength(&password))
+            }
+            _ => {
+                report.entries.generated += 1;
+                // Older generated entries do not know their policy, so rate the password itself
+                match record.policy {
+                    Some(_) => Some(StrengthEstimate::random(entry_entropy(name)?)),
+                    None => Some(estimate_strength(&password)),
+                }
+            }
+        };
+
+        if let Some(estimate) = estimate.filter(|e| e.strength < options.min_strength) {
+            let mut message = estimate.to_string();
+            if !estimate.patterns.is_empty() {
+                let patterns: Vec<String> = estimate.patterns.iter().map(Pattern::to_string).collect();
+                message.push_str(&format!(": {}", patterns.join("; ")));
+            }
+            findings.push(finding(FindingKind::Weak, name, message));
+        }
+        if let (Some(corpus), "custom") = (corpus.as_mut(), entry.password_type.as_str()) {
+            let count = corpus.count(&password)?;
+            if count > 0 {
+                findings.push(finding(FindingKind::Breached, name, format!("found {} times in known breaches", count)));
+            }
+        }
+        if entry.updated > 0 && now.saturating_sub(entry.updated) > options.max_age_days * 86_400 {
+            let message = format!(
+                "last changed on {}, {} days ago",
+                format_timestamp(entry.updated),
+                (now - entry.updated) / 86_400
+            );
+            findings.push(finding(FindingKind::Old, name, message));
+        }
+        if entry.password_type != "pin" && record.username.is_none() && record.urls.is_empty() {
+            findings.push(finding(FindingKind::MissingMetadata, name, "has no username or URL".to_string()));
+        }
+        by_password.entry(password).or_default().push(entry.name.clone());
+    }
+
+    for names in by_password.into_values().filter(|names| names.len() > 1) {
+        let message = format!("{} entries share the same password", names.len());
+        findings.push(Finding {
+            kind: FindingKind::Reused,
+            entries: names,
+            message,
+        });
+    }
+    findings.sort_by(|a, b| (a.kind, &a.entries).cmp(&(b.kind, &b.entries)));
+    report.findings = findings;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::keyring::{save_to_keyring, set_entry_record, set_password_type};
+    use crate::app::otp::{delete_otp, generate_otp};
+    use crate::app::password::{delete_password, generate_save_safety_password};
+    use crate::app::entry::EntryRecord;
+
+    /// The findings about entries whose names start with `prefix`.
+    fn findings_for<'a>(report: &'a AuditReport, prefix: &str) -> Vec<(FindingKind, Vec<&'a str>)> {
+        report
+            .findings
+            .iter()
+            .filter(|f| f.entries.iter().all(|e| e.starts_with(prefix)))
+            .map(|f| (f.kind, f.entries.iter().map(String::as_str).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_findings() {
+        let prefix = "test_audit_";
+        let names = ["test_audit_weak", "test_audit_copy", "test_audit_strong", "test_audit_generated"];
+        for name in names {
+            let _ = delete_password(name);
+        }
+        let _ = delete_otp("test_audit_otp");
+
+        save_to_keyring("test_audit_weak", "Summer2024").unwrap();
+        set_password_type("test_audit_weak", "custom").unwrap();
+        save_to_keyring("test_audit_copy", "Summer2024").unwrap();
+        set_password_type("test_audit_copy", "custom").unwrap();
+        save_to_keyring("test_audit_strong", "q8#Lr2vX!m9Tz&Wc").unwrap();
+        set_password_type("test_audit_strong", "custom").unwrap();
+        let record = EntryRecord { username: Some("alice".to_string()), ..EntryRecord::default() };
+        set_entry_record("test_audit_strong", &record).unwrap();
+        generate_save_safety_password("test_audit_generated", Some(24), None, None).unwrap();
+        let record = EntryRecord {
+            urls: vec!["https://example.com".to_string()],
+            ..get_entry_record("test_audit_generated").unwrap()
+        };
+        set_entry_record("test_audit_generated", &record).unwrap();
+        generate_otp("test_audit_otp", 300, 12, None).unwrap();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let report = audit_at(&AuditOptions::default(), now).unwrap();
+        assert_eq!(
+            findings_for(&report, prefix),
+            vec![
+                (FindingKind::Weak, vec!["test_audit_copy"]),
+                (FindingKind::Weak, vec!["test_audit_weak"]),
+                (FindingKind::Reused, vec!["test_audit_copy", "test_audit_weak"]),
+                (FindingKind::MissingMetadata, vec!["test_audit_copy"]),
+                (FindingKind::MissingMetadata, vec!["test_audit_weak"]),
+            ]
+        );
+        assert!(report.entries.custom >= 3 && report.entries.generated >= 1 && report.entries.otp >= 1);
+
+        // A year later every password is old and the OTP has expired
+        let later = audit_at(&AuditOptions::default(), now + 400 * 86_400).unwrap();
+        let kinds: Vec<FindingKind> = findings_for(&later, prefix).into_iter().map(|(kind, _)| kind).collect();
+        assert_eq!(kinds.iter().filter(|k| **k == FindingKind::Old).count(), 4);
+        assert!(kinds.contains(&FindingKind::ExpiredOtp), "{:?}", kinds);
+        assert!(later.has_findings(&[FindingKind::Old]));
+
+        // Reports never contain a password
+        for text in [report.to_text(), report.to_markdown(), serde_json::to_string(&report).unwrap()] {
+            assert!(!text.contains("Summer2024") && !text.contains("q8#Lr2vX"), "{}", text);
+        }
+
+        for name in names {
+            let _ = delete_password(name);
+        }
+        let _ = delete_otp("test_audit_otp");
+    }
+
+    #[test]
+    fn test_report_formats() {
+        let report = AuditReport {
+            profile: "default".to_string(),
+            backend: "memory".to_string(),
+            timestamp: 0,
+            breach_checked: false,
+            entries: EntryBreakdown { total: 2, custom: 2, ..EntryBreakdown::default() },
+            findings: vec![Finding {
+                kind: FindingKind::Reused,
+                entries: vec!["mail_work".to_string(), "mail|home".to_string()],
+                message: "2 entries share the same password".to_string(),
+            }],
+        };
+        let text = report.to_text();
+        assert!(text.contains("2 entries: 2 custom, 0 generated, 0 PIN, 0 OTP"), "{}", text);
+        assert!(text.contains("Reused passwords (1)\n  - mail_work, mail|home: 2 entries share"), "{}", text);
+        assert!(text.contains("1 finding. Breached passwords were not checked"), "{}", text);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("# apppass security audit"), "{}", markdown);
+        assert!(markdown.contains("| 2 | 2 | 0 | 0 | 0 |"), "{}", markdown);
+        assert!(markdown.contains("## Reused passwords (1)\n\n- **mail\\_work**, **mail\\|home**: 2 entries"), "{}", markdown);
+
+        assert_eq!("missing_metadata".parse::<FindingKind>(), Ok(FindingKind::MissingMetadata));
+        assert!("stale".parse::<FindingKind>().is_err());
+        for (kind, name) in FindingKind::ALL.iter().zip(FindingKind::NAMES) {
+            assert_eq!(kind.to_string(), name);
+        }
+    }
+}
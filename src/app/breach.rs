@@ -0,0 +1,223 @@
+use sha1::{Digest, Sha1};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use crate::app::config::config;
+use crate::app::error::{AppError, Result};
+use crate::app::index::list_entries;
+use crate::app::keyring::get_from_keyring;
+
+/// A local copy of a breached-password corpus: one `SHA1HASH:COUNT` line per password,
+/// sorted by hash, like the "ordered by hash" downloads of Have I Been Pwned. Lines without
+/// a count count once.
+///
+/// Lookups binary-search the file, so it is never loaded into memory: a corpus of a billion
+/// lines takes about 30 reads.
+pub struct BreachCorpus {
+    path: PathBuf,
+    reader: BufReader<File>,
+    len: u64,
+}
+
+impl BreachCorpus {
+    /// Opens a corpus file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<BreachCorpus>` - The corpus, or `AppError::Io` if the file cannot be opened.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| AppError::io(path, e))?;
+        let len = file.metadata().map_err(|e| AppError::io(path, e))?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            reader: BufReader::new(file),
+            len,
+        })
+    }
+
+    /// Opens the corpus set in `password.breach_file`, if any.
+    pub fn configured() -> Result<Option<Self>> {
+        config().password.breach_file.map(|path| Self::open(&path)).transpose()
+    }
+
+    /// Returns how many times `password` appears in the corpus, 0 if it does not.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64>` - The count, `AppError::Io` if the file cannot be read, or
+    ///   `AppError::Parse` if a line read is not a SHA-1 hash with an optional count.
+    pub fn count(&mut self, password: &str) -> Result<u64> {
+        let hash = format!("{:X}", Sha1::digest(password.as_bytes()));
+
+        // The line holding the hash, if any, starts in lo..hi; lo is always a line start
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let (start, line) = self.line_from(mid)?;
+            let line = match line {
+                Some(line) => line,
+                None => {
+                    hi = mid;
+                    continue;
+                }
+            };
+            let (line_hash, count) = self.parse_line(&line)?;
+            match line_hash.cmp(&hash) {
+                Ordering::Equal => return Ok(count),
+                Ordering::Less => lo = start + line.len() as u64,
+                Ordering::Greater => hi = mid,
+            }
+        }
+        Ok(0)
+    }
+
+    /// Reads the first line starting at or after `offset`, with its start. `None` at the end
+    /// of the file.
+    fn line_from(&mut self, offset: u64) -> Result<(u64, Option<String>)> {
+        let io_error = |e| AppError::io(&self.path, e);
+        let mut start = offset;
+        let mut line = Vec::new();
+        if offset > 0 {
+            // Skip the rest of the line `offset` falls in; a line starting at `offset` is kept
+            // since the byte before it is its predecessor's newline
+            self.reader.seek(SeekFrom::Start(offset - 1)).map_err(io_error)?;
+            start = offset - 1 + self.reader.read_until(b'\n', &mut line).map_err(io_error)? as u64;
+            line.clear();
+        } else {
+            self.reader.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        }
+        match self.reader.read_until(b'\n', &mut line).map_err(io_error)? {
+            0 => Ok((start, None)),
+            _ => Ok((start, Some(String::from_utf8_lossy(&line).into_owned()))),
+        }
+    }
+
+    /// Splits a line into its uppercase hash and count.
+    fn parse_line(&self, line: &str) -> Result<(String, u64)> {
+        let invalid = || {
+            AppError::parse(
+                self.path.display().to_string(),
+                format!("'{}' is not a SHA-1 hash with an optional count", line.trim()),
+            )
+        };
+        let (hash, count) = match line.trim().split_once(':') {
+            Some((hash, count)) => (hash, count.trim().parse().map_err(|_| invalid())?),
+            None => (line.trim(), 1),
+        };
+        if hash.len() != 40 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        Ok((hash.to_ascii_uppercase(), count))
+    }
+}
+
+/// Returns how many times `password` appears in the corpus set in `password.breach_file`.
+///
+/// # Returns
+///
+/// * `Result<Option<u64>>` - The count (0 if it does not appear), `None` if no corpus is
+///   configured, or an error from `BreachCorpus::count`.
+pub fn breach_count(password: &str) -> Result<Option<u64>> {
+    match BreachCorpus::configured()? {
+        Some(mut corpus) => corpus.count(password).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Looks up the passwords of entries in a corpus.
+///
+/// # Arguments
+///
+/// * `corpus` - The corpus to search.
+/// * `names` - The entries to check; every custom password if empty.
+///
+/// # Returns
+///
+/// * `Result<Vec<(String, u64)>>` - Every entry checked with its count (0 if it does not
+///   appear), `AppError::NotFound` if a named entry has no password, or an error from `count`.
+pub fn check_entries(corpus: &mut BreachCorpus, names: &[String]) -> Result<Vec<(String, u64)>> {
+    let names: Vec<String> = if names.is_empty() {
+        list_entries()?
+            .into_iter()
+            .filter(|entry| entry.password_type == "custom")
+            .map(|entry| entry.name)
+            .collect()
+    } else {
+        names.to_vec()
+    };
+    names
+        .into_iter()
+        .map(|name| {
+            let count = corpus.count(&get_from_keyring(&name)?)?;
+            Ok((name, count))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::keyring::{delete_from_keyring, save_to_keyring};
+
+    /// Writes a sorted corpus with `passwords` and filler hashes around them.
+    fn write_corpus(name: &str, passwords: &[(&str, u64)], line_ending: &str) -> PathBuf {
+        let mut lines: Vec<String> = (0..500u32)
+            .map(|i| format!("{:X}", Sha1::digest(format!("filler {}", i).as_bytes())))
+            .map(|hash| format!("{}:1", hash))
+            .collect();
+        lines.extend(passwords.iter().map(|(p, n)| format!("{:X}:{}", Sha1::digest(p.as_bytes()), n)));
+        lines.sort();
+        let path = std::env::temp_dir().join(format!("apppass_breach_{}_{}.txt", name, std::process::id()));
+        std::fs::write(&path, lines.join(line_ending) + line_ending).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_count_finds_every_line() {
+        for (name, line_ending) in [("lf", "\n"), ("crlf", "\r\n")] {
+            let path = write_corpus(name, &[("password", 9_545_824), ("hunter2", 17)], line_ending);
+            let mut corpus = BreachCorpus::open(&path).unwrap();
+            assert_eq!(corpus.count("password").unwrap(), 9_545_824);
+            assert_eq!(corpus.count("hunter2").unwrap(), 17);
+            assert_eq!(corpus.count("correct horse battery staple").unwrap(), 0);
+            // The first and last lines are found too
+            for i in 0..500u32 {
+                assert_eq!(corpus.count(&format!("filler {}", i)).unwrap(), 1, "filler {}", i);
+            }
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn test_check_entries() {
+        let path = write_corpus("entries", &[("hunter2", 17)], "\n");
+        let mut corpus = BreachCorpus::open(&path).unwrap();
+        save_to_keyring("test_breach_hunter", "hunter2").unwrap();
+        save_to_keyring("test_breach_random", "q8#Lr2vX!m9Tz").unwrap();
+
+        let names = ["test_breach_hunter".to_string(), "test_breach_random".to_string()];
+        assert_eq!(
+            check_entries(&mut corpus, &names).unwrap(),
+            vec![(names[0].clone(), 17), (names[1].clone(), 0)]
+        );
+        assert!(matches!(
+            check_entries(&mut corpus, &["test_breach_missing".to_string()]),
+            Err(AppError::NotFound(_))
+        ));
+
+        for name in &names {
+            let _ = delete_from_keyring(name);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_malformed_and_missing_corpus() {
+        let path = std::env::temp_dir().join(format!("apppass_breach_bad_{}.txt", std::process::id()));
+        std::fs::write(&path, "NOT-A-HASH:3\n").unwrap();
+        assert!(matches!(BreachCorpus::open(&path).unwrap().count("x"), Err(AppError::Parse { .. })));
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(BreachCorpus::open(&path), Err(AppError::Io { .. })));
+    }
+}
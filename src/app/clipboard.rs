@@ -0,0 +1,89 @@
+use arboard::Clipboard;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crate::app::config::config;
+use crate::app::error::{AppError, Result};
+
+/// Clears the clipboard once the configured timeout expires, unless something else was
+/// copied since.
+struct PendingClear {
+    /// Wakes the clearing thread early (see `clear_clipboard`).
+    clear_now: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+/// The clear scheduled by the last copy.
+static PENDING_CLEAR: Mutex<Option<PendingClear>> = Mutex::new(None);
+
+/// Clipboard kept open after a copy that is never cleared (`clipboard.timeout = 0`). On
+/// Linux the copied text is only available while the clipboard that set it is open.
+static KEPT_CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+/// Copies `secret` to the system clipboard and schedules clearing it after
+/// `clipboard.timeout` seconds from the configuration.
+///
+/// # Arguments
+///
+/// * `secret` - The password to copy.
+///
+/// # Returns
+///
+/// * `Result<u64>` - The number of seconds before the clipboard is cleared (0 if it is
+///   kept), or `AppError::Clipboard` if there is no clipboard (e.g. on a headless machine).
+///
+/// # Behavior
+///
+/// The clipboard is only cleared if it still holds `secret`. The clear runs on a
+/// background thread and is lost when the process exits, so short-lived callers should
+/// call `wait_for_clipboard_clear` before exiting and long-lived ones `clear_clipboard`.
+pub fn copy_to_clipboard(secret: &str) -> Result<u64> {
+    let mut clipboard = Clipboard::new().map_err(clipboard_error)?;
+    clipboard.set_text(secret).map_err(clipboard_error)?;
+
+    let timeout = config().clipboard.timeout;
+    if timeout == 0 {
+        *KEPT_CLIPBOARD.lock().unwrap_or_else(|e| e.into_inner()) = Some(clipboard);
+        return Ok(0);
+    }
+    let secret = secret.to_string();
+    let (clear_now, wake) = mpsc::channel();
+    let thread = thread::spawn(move || {
+        // Also wakes up when a newer copy replaces this clear
+        let _ = wake.recv_timeout(Duration::from_secs(timeout));
+        if clipboard.get_text().is_ok_and(|text| text == secret) {
+            let _ = clipboard.clear();
+        }
+    });
+    let previous = PENDING_CLEAR
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(PendingClear { clear_now, thread });
+    if let Some(previous) = previous {
+        drop(previous.clear_now);
+        let _ = previous.thread.join();
+    }
+    Ok(timeout)
+}
+
+/// Blocks until the clipboard is cleared by the last copy, if a clear is pending.
+pub fn wait_for_clipboard_clear() {
+    let pending = PENDING_CLEAR.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(pending) = pending {
+        let _ = pending.thread.join();
+    }
+}
+
+/// Clears the clipboard now if a clear is pending, instead of waiting for the timeout.
+pub fn clear_clipboard() {
+    let pending = PENDING_CLEAR.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(pending) = pending {
+        let _ = pending.clear_now.send(());
+        let _ = pending.thread.join();
+    }
+}
+
+fn clipboard_error(e: arboard::Error) -> AppError {
+    AppError::Clipboard(e.to_string())
+}
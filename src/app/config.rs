@@ -0,0 +1,625 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use crate::app::error::{AppError, Result};
+use crate::app::passphrase::PassphraseSettings;
+use crate::app::pin::{DEFAULT_PIN_LENGTH, PIN_LENGTH_RANGE};
+use crate::app::policy::PasswordPolicy;
+use crate::app::profile::{active_profile, config_dir};
+use crate::app::strength::Strength;
+use crate::app::trash::DEFAULT_TRASH_RETENTION_DAYS;
+
+/// Length of generated passwords when the configuration does not set one.
+pub const DEFAULT_PASSWORD_LENGTH: usize = 30;
+
+/// Lifetime of one-time passwords, in seconds, when the configuration does not set one.
+pub const DEFAULT_OTP_TTL: u64 = 300;
+
+/// Range of password lengths accepted as the configured default.
+pub const PASSWORD_LENGTH_RANGE: std::ops::RangeInclusive<usize> = 8..=128;
+
+/// The configuration used by this process: the file with the section of the active profile
+/// applied (see `Config::for_profile`). Unit tests keep the defaults so they never depend on
+/// the user's configuration file.
+static CONFIG: Lazy<RwLock<Config>> = Lazy::new(|| RwLock::new(Config::default()));
+
+/// Defaults for generated passwords.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasswordSettings {
+    /// Length of generated passwords when none is given.
+    pub length: usize,
+    /// Rules generated passwords follow (character classes, minimum counts, exclusions).
+    #[serde(flatten)]
+    pub policy: PasswordPolicy,
+    /// Weakest strength accepted for custom passwords; `None` accepts any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_strength: Option<Strength>,
+    /// Sorted `SHA1HASH:COUNT` file custom passwords are looked up in (see `BreachCorpus`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breach_file: Option<PathBuf>,
+}
+
+impl Default for PasswordSettings {
+    fn default() -> Self {
+        Self {
+            length: DEFAULT_PASSWORD_LENGTH,
+            policy: PasswordPolicy::default(),
+            min_strength: None,
+            breach_file: None,
+        }
+    }
+}
+
+/// Defaults for generated PINs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PinSettings {
+    /// Number of digits when `--length` is not given.
+    pub length: usize,
+}
+
+impl Default for PinSettings {
+    fn default() -> Self {
+        Self { length: DEFAULT_PIN_LENGTH }
+    }
+}
+
+/// Defaults for one-time passwords.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OtpSettings {
+    /// Lifetime in seconds when `--ttl` is not given.
+    pub ttl: u64,
+}
+
+impl Default for OtpSettings {
+    fn default() -> Self {
+        Self { ttl: DEFAULT_OTP_TTL }
+    }
+}
+
+/// Clipboard settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    /// Seconds after which a password copied to the clipboard is cleared (0 keeps it,
+    /// see `copy_to_clipboard`).
+    pub timeout: u64,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self { timeout: 45 }
+    }
+}
+
+/// Trash settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// Days deleted entries are kept before being purged (0 keeps them until purged).
+    pub retention_days: u64,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self { retention_days: DEFAULT_TRASH_RETENTION_DAYS }
+    }
+}
+
+/// Options of the interactive TUI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    /// Show passwords in clear text in the password list instead of masking them.
+    pub show_passwords: bool,
+    /// How often the TUI polls for input, in milliseconds.
+    pub tick_rate_ms: u64,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            show_passwords: false,
+            tick_rate_ms: 250,
+        }
+    }
+}
+
+/// User preferences, stored as TOML in `<config dir>/config.toml`.
+///
+/// Every setting is optional in the file; missing ones take their default. The top-level
+/// settings apply to every profile, and a `[profiles.<name>]` section overrides any of them
+/// for one profile. The file holds no secrets, so it is read before a backend is opened.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Profile used when neither `--profile` nor `APPPASS_PROFILE` is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Defaults for generated passwords.
+    pub password: PasswordSettings,
+    /// How memorizable passphrases are built.
+    pub passphrase: PassphraseSettings,
+    /// Defaults for generated PINs.
+    pub pin: PinSettings,
+    /// Defaults for one-time passwords.
+    pub otp: OtpSettings,
+    /// Clipboard settings.
+    pub clipboard: ClipboardSettings,
+    /// Trash settings.
+    pub trash: TrashSettings,
+    /// Options of the interactive TUI.
+    pub ui: UiSettings,
+    /// Settings of each profile that differ from the top-level ones, in the layout of the
+    /// top level (e.g. `[profiles.work.password]`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, toml::Table>,
+}
+
+impl Config {
+    /// Every key accepted by `get` and `set`.
+    pub const KEYS: [&'static str; 25] = [
+        "default_profile",
+        "password.length",
+        "password.classes",
+        "password.min_lower",
+        "password.min_upper",
+        "password.min_digits",
+        "password.min_symbols",
+        "password.symbols",
+        "password.exclude",
+        "password.exclude_ambiguous",
+        "password.no_repeats",
+        "password.min_strength",
+        "password.breach_file",
+        "passphrase.words",
+        "passphrase.separator",
+        "passphrase.capitalize",
+        "passphrase.digit",
+        "passphrase.symbol",
+        "passphrase.wordlist",
+        "pin.length",
+        "otp.ttl",
+        "clipboard.timeout",
+        "trash.retention_days",
+        "ui.show_passwords",
+        "ui.tick_rate_ms",
+    ];
+
+    /// Loads the configuration from `path`. A missing file is the default configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config>` - The configuration, `AppError::Io` if the file cannot be read, or
+    ///   `AppError::Parse` if it is malformed or holds an invalid value.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(AppError::io(path, e)),
+        };
+        let config: Config = toml::from_str(&data).map_err(|e| AppError::parse(path.display().to_string(), e))?;
+        config
+            .validate()
+            .map_err(|e| AppError::parse(path.display().to_string(), e))?;
+        for profile in config.profiles.keys() {
+            config.for_profile(profile).map_err(|e| {
+                AppError::parse(path.display().to_string(), format!("[profiles.{}]: {}", profile, e))
+            })?;
+        }
+        Ok(config)
+    }
+
+    /// Returns the settings of `profile`: the top-level ones with its `[profiles.<name>]`
+    /// section applied. The result has no profile sections.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config>` - The settings, or `AppError::InvalidInput` if the section holds a
+    ///   malformed or out-of-range value.
+    pub fn for_profile(&self, profile: &str) -> Result<Config> {
+        let mut settings = Config {
+            profiles: BTreeMap::new(),
+            ..self.clone()
+        };
+        let Some(overrides) = self.profiles.get(profile) else {
+            return Ok(settings);
+        };
+        let mut table = settings.to_table()?;
+        merge_table(&mut table, overrides);
+        let mut merged: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| AppError::InvalidInput(e.message().to_string()))?;
+        merged.validate().map_err(AppError::InvalidInput)?;
+        // The default profile is chosen before any profile is, so it is only read from the top level
+        merged.default_profile = settings.default_profile.take();
+        Ok(merged)
+    }
+
+    /// Saves `settings` as the settings of `profile`: its section is replaced by the values
+    /// that differ from the top-level ones, and removed if none do.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - `AppError::InvalidInput` if `settings` unsets an optional setting
+    ///   (such as `password.min_strength`) that the top level sets, which a section cannot express.
+    pub fn set_profile_settings(&mut self, profile: &str, settings: &Config) -> Result<()> {
+        let base = Config {
+            default_profile: None,
+            profiles: BTreeMap::new(),
+            ..self.clone()
+        };
+        let wanted = Config {
+            default_profile: None,
+            profiles: BTreeMap::new(),
+            ..settings.clone()
+        };
+        let overrides = diff_table(&base.to_table()?, &wanted.to_table()?);
+        let mut updated = self.clone();
+        if overrides.is_empty() {
+            updated.profiles.remove(profile);
+        } else {
+            updated.profiles.insert(profile.to_string(), overrides);
+        }
+        let saved = Config {
+            default_profile: None,
+            ..updated.for_profile(profile)?
+        };
+        if saved != wanted {
+            return Err(AppError::InvalidInput(format!(
+                "Profile '{}' cannot unset a setting that is set for every profile; change it at the top level of the configuration file",
+                profile
+            )));
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    /// Returns the configuration as a TOML table.
+    fn to_table(&self) -> Result<toml::Table> {
+        toml::Table::try_from(self).map_err(|e| AppError::InvalidInput(e.to_string()))
+    }
+
+    /// Writes the configuration to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+        let data = toml::to_string_pretty(self).map_err(|e| AppError::parse(path.display().to_string(), e))?;
+        fs::write(path, data).map_err(|e| AppError::io(path, e))
+    }
+
+    /// Checks that every value is in range.
+    fn validate(&self) -> std::result::Result<(), String> {
+        if !PASSWORD_LENGTH_RANGE.contains(&self.password.length) {
+            return Err(format!(
+                "password.length must be between {} and {}",
+                PASSWORD_LENGTH_RANGE.start(),
+                PASSWORD_LENGTH_RANGE.end()
+            ));
+        }
+        self.password
+            .policy
+            .validate(self.password.length)
+            .map_err(|e| format!("password policy: {}", e))?;
+        self.passphrase
+            .validate()
+            .map_err(|e| format!("passphrase: {}", e))?;
+        if !PIN_LENGTH_RANGE.contains(&self.pin.length) {
+            return Err(format!(
+                "pin.length must be between {} and {}",
+                PIN_LENGTH_RANGE.start(),
+                PIN_LENGTH_RANGE.end()
+            ));
+        }
+        if self.otp.ttl == 0 {
+            return Err("otp.ttl must be at least 1 second".to_string());
+        }
+        if !(10..=5000).contains(&self.ui.tick_rate_ms) {
+            return Err("ui.tick_rate_ms must be between 10 and 5000".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the value of `key` (one of `Config::KEYS`) as text. An unset default profile is empty.
+    pub fn get(&self, key: &str) -> Result<String> {
+        Ok(match key {
+            "default_profile" => self.default_profile.clone().unwrap_or_default(),
+            "password.length" => self.password.length.to_string(),
+            "password.min_strength" => self.password.min_strength.map_or("none".to_string(), |s| s.to_string()),
+            "password.breach_file" => self.password.breach_file.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+            "pin.length" => self.pin.length.to_string(),
+            "otp.ttl" => self.otp.ttl.to_string(),
+            "clipboard.timeout" => self.clipboard.timeout.to_string(),
+            "trash.retention_days" => self.trash.retention_days.to_string(),
+            "ui.show_passwords" => self.ui.show_passwords.to_string(),
+            "ui.tick_rate_ms" => self.ui.tick_rate_ms.to_string(),
+            other => {
+                let value = match other.split_once('.') {
+                    Some(("password", k)) => self.password.policy.get(k),
+                    Some(("passphrase", k)) => self.passphrase.get(k),
+                    _ => None,
+                };
+                value.ok_or_else(|| unknown_key(other))?
+            }
+        })
+    }
+
+    /// Sets `key` (one of `Config::KEYS`) from its text form.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - `AppError::InvalidInput` if the key is unknown or the value is malformed
+    ///   or out of range. The configuration is unchanged on error.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        self.set_all(&[(key, value)])
+    }
+
+    /// Sets several keys at once, checking the result only after every value is applied, so
+    /// settings that depend on each other (e.g. `password.classes` and `password.min_symbols`)
+    /// can change together.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Same errors as `set`. The configuration is unchanged on error.
+    pub fn set_all(&mut self, values: &[(&str, &str)]) -> Result<()> {
+        let mut updated = self.clone();
+        for (key, value) in values {
+            updated.apply(key, value)?;
+        }
+        updated.validate().map_err(AppError::InvalidInput)?;
+        *self = updated;
+        Ok(())
+    }
+
+    /// Parses `value` into the setting `key`, without checking the whole configuration.
+    fn apply(&mut self, key: &str, value: &str) -> Result<()> {
+        let invalid = |e: &dyn fmt::Display| AppError::InvalidInput(format!("Invalid value '{}' for {}: {}", value, key, e));
+        match key {
+            "default_profile" => {
+                self.default_profile = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+            }
+            "password.length" => self.password.length = value.trim().parse().map_err(|e| invalid(&e))?,
+            "password.min_strength" => {
+                self.password.min_strength = match value.trim() {
+                    "" | "none" => None,
+                    strength => Some(strength.parse().map_err(|e| invalid(&e))?),
+                };
+            }
+            "password.breach_file" => {
+                self.password.breach_file = Some(PathBuf::from(value.trim())).filter(|p| !p.as_os_str().is_empty());
+            }
+            "pin.length" => self.pin.length = value.trim().parse().map_err(|e| invalid(&e))?,
+            "otp.ttl" => self.otp.ttl = value.trim().parse().map_err(|e| invalid(&e))?,
+            "clipboard.timeout" => self.clipboard.timeout = value.trim().parse().map_err(|e| invalid(&e))?,
+            "trash.retention_days" => self.trash.retention_days = value.trim().parse().map_err(|e| invalid(&e))?,
+            "ui.show_passwords" => self.ui.show_passwords = value.trim().parse().map_err(|e| invalid(&e))?,
+            "ui.tick_rate_ms" => self.ui.tick_rate_ms = value.trim().parse().map_err(|e| invalid(&e))?,
+            other => {
+                let result = match other.split_once('.') {
+                    Some(("password", k)) => self.password.policy.set(k, value),
+                    Some(("passphrase", k)) => self.passphrase.set(k, value),
+                    _ => None,
+                };
+                result.ok_or_else(|| unknown_key(other))?.map_err(|e| invalid(&e))?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error for a key that is not one of `Config::KEYS`.
+fn unknown_key(key: &str) -> AppError {
+    AppError::InvalidInput(format!(
+        "Unknown configuration key '{}'. Expected one of: {}",
+        key,
+        Config::KEYS.join(", ")
+    ))
+}
+
+/// Copies the values of `overrides` into `table`, merging nested tables key by key.
+fn merge_table(table: &mut toml::Table, overrides: &toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(key), value) {
+            (Some(toml::Value::Table(current)), toml::Value::Table(nested)) => merge_table(current, nested),
+            _ => {
+                table.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Returns the values of `table` that differ from those of `base` (the inverse of `merge_table`).
+fn diff_table(base: &toml::Table, table: &toml::Table) -> toml::Table {
+    let mut diff = toml::Table::new();
+    for (key, value) in table {
+        match (base.get(key), value) {
+            (Some(toml::Value::Table(current)), toml::Value::Table(nested)) => {
+                let nested = diff_table(current, nested);
+                if !nested.is_empty() {
+                    diff.insert(key.clone(), toml::Value::Table(nested));
+                }
+            }
+            (Some(current), value) if current == value => {}
+            _ => {
+                diff.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    diff
+}
+
+/// Returns the path of the configuration file.
+pub fn config_path() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+/// Loads the configuration file and makes the settings of the active profile the
+/// configuration of this process. Called at startup, and again once the profile is resolved.
+pub fn init_config() -> Result<()> {
+    set_config(Config::load(&config_path())?.for_profile(&active_profile())?);
+    Ok(())
+}
+
+/// Replaces the configuration of this process.
+pub fn set_config(config: Config) {
+    *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config;
+}
+
+/// Returns the configuration of this process.
+pub fn config() -> Config {
+    CONFIG.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Applies `f` to the configuration file and saves it, also updating the configuration
+/// of this process. `f` sees the top-level settings, which apply to every profile.
+///
+/// # Arguments
+///
+/// * `f` - Closure that modifies the configuration.
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if the file is saved, otherwise returns an `AppError`.
+pub fn update_config<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&mut Config) -> Result<()>,
+{
+    let path = config_path();
+    let mut config = Config::load(&path)?;
+    f(&mut config)?;
+    let active = config.for_profile(&active_profile())?;
+    config.save(&path)?;
+    set_config(active);
+    Ok(())
+}
+
+/// Applies `f` to the settings of `profile` and saves them in its `[profiles.<name>]` section
+/// of the configuration file (see `Config::set_profile_settings`), also updating the
+/// configuration of this process if `profile` is the active one.
+///
+/// # Arguments
+///
+/// * `profile` - The profile whose settings change.
+/// * `f` - Closure that modifies the settings of the profile.
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if the file is saved, otherwise returns an `AppError`.
+pub fn update_profile_config<F>(profile: &str, f: F) -> Result<()>
+where
+    F: FnOnce(&mut Config) -> Result<()>,
+{
+    let path = config_path();
+    let mut config = Config::load(&path)?;
+    let mut settings = config.for_profile(profile)?;
+    f(&mut settings)?;
+    config.set_profile_settings(profile, &settings)?;
+    config.save(&path)?;
+    if profile == active_profile() {
+        set_config(config.for_profile(profile)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_file_keeps_defaults() {
+        let config: Config = toml::from_str("[password]\nlength = 40\n").unwrap();
+        assert_eq!(config.password.length, 40);
+        assert_eq!(config.password.policy, PasswordPolicy::default());
+        assert_eq!(config.otp.ttl, DEFAULT_OTP_TTL);
+        assert_eq!(config.default_profile, None);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = std::env::temp_dir().join(format!("apppass_config_test_{}.toml", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        let mut config = Config::default();
+        config.set("default_profile", "work").unwrap();
+        config.set("password.classes", "lower,symbols").unwrap();
+        config.set("password.min_symbols", "2").unwrap();
+        config.set("password.min_strength", "fair").unwrap();
+        config.set("ui.show_passwords", "true").unwrap();
+        config.save(&path).unwrap();
+        assert_eq!(Config::load(&path).unwrap(), config);
+
+        fs::write(&path, "[password]\nlength = 2\n").unwrap();
+        assert!(matches!(Config::load(&path), Err(AppError::Parse { .. })));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_profile_sections_override_the_top_level() {
+        let config: Config = toml::from_str(
+            "default_profile = \"work\"\n[password]\nlength = 40\n[profiles.work.password]\nlength = 20\n[profiles.work.trash]\nretention_days = 7\n",
+        )
+        .unwrap();
+        let work = config.for_profile("work").unwrap();
+        assert_eq!((work.password.length, work.trash.retention_days), (20, 7));
+        assert_eq!(work.default_profile.as_deref(), Some("work"));
+        assert!(work.profiles.is_empty());
+        let other = config.for_profile("other").unwrap();
+        assert_eq!((other.password.length, other.trash.retention_days), (40, DEFAULT_TRASH_RETENTION_DAYS));
+
+        let invalid: Config = toml::from_str("[profiles.work.password]\nlength = 2\n").unwrap();
+        assert!(matches!(invalid.for_profile("work"), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_profile_settings_keep_only_what_differs() {
+        let mut config = Config::default();
+        config.set("password.min_strength", "fair").unwrap();
+        let mut work = config.for_profile("work").unwrap();
+        work.set("otp.ttl", "60").unwrap();
+        config.set_profile_settings("work", &work).unwrap();
+        assert_eq!(toml::to_string(&config.profiles["work"]).unwrap(), "[otp]\nttl = 60\n");
+        assert_eq!(config.for_profile("work").unwrap(), work);
+
+        // Back to the top-level value: the section goes away
+        work.set("otp.ttl", &DEFAULT_OTP_TTL.to_string()).unwrap();
+        config.set_profile_settings("work", &work).unwrap();
+        assert!(config.profiles.is_empty());
+
+        work.set("password.min_strength", "none").unwrap();
+        assert!(matches!(config.set_profile_settings("work", &work), Err(AppError::InvalidInput(_))));
+        assert!(config.profiles.is_empty());
+    }
+
+    #[test]
+    fn test_get_and_set_by_key() {
+        let mut config = Config::default();
+        for key in Config::KEYS {
+            assert!(config.get(key).is_ok(), "{}", key);
+        }
+        config.set("otp.ttl", "60").unwrap();
+        config.set("pin.length", "4").unwrap();
+        config.set("trash.retention_days", "0").unwrap();
+        assert_eq!(config.trash.retention_days, 0);
+        assert!(matches!(config.set("trash.retention_days", "-1"), Err(AppError::InvalidInput(_))));
+        assert!(matches!(config.set("pin.length", "3"), Err(AppError::InvalidInput(_))));
+        assert_eq!(config.get("otp.ttl").unwrap(), "60");
+
+        assert!(matches!(config.set("password.length", "4"), Err(AppError::InvalidInput(_))));
+        assert!(matches!(config.set("password.classes", "emoji"), Err(AppError::InvalidInput(_))));
+        assert!(matches!(config.set("password.min_symbols", "2"), Err(AppError::InvalidInput(_))));
+        config
+            .set_all(&[("password.classes", "lower,symbols"), ("password.min_symbols", "2")])
+            .unwrap();
+        assert_eq!(config.get("password.classes").unwrap(), "lower,symbols");
+        assert!(matches!(config.set("nope", "1"), Err(AppError::InvalidInput(_))));
+        assert_eq!(config.password.length, DEFAULT_PASSWORD_LENGTH);
+    }
+}
@@ -0,0 +1,279 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use keyring::Error as KeyringError;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use crate::app::error::{AppError, Result};
+use crate::app::policy::{EntryPolicy, PasswordPolicy};
+use crate::app::store::store;
+use crate::app::vault::KdfParams;
+use crate::app::{DERIVED_SITES_KEY, DERIVE_SECRET_ENV_VAR};
+
+/// Version of the derivation algorithm, mixed into every derived password. Changing the
+/// algorithm in a way that changes its output requires a new version.
+pub const DERIVE_VERSION: u32 = 1;
+
+/// Argon2id cost of a derivation: 64 MiB, 3 iterations, 1 lane. Part of the algorithm, so
+/// it is the same on every machine.
+pub const DERIVE_KDF: KdfParams = KdfParams {
+    m_cost: 64 * 1024,
+    t_cost: 3,
+    p_cost: 1,
+};
+
+/// Length of derived passwords when none is given. Like the rest of the defaults it does not
+/// come from the configuration, which may differ between machines.
+pub const DEFAULT_DERIVE_LENGTH: usize = 20;
+
+/// Serializes read-modify-write cycles on the saved sites within this process.
+static DERIVED_SITES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Everything that, with the master secret, determines a derived password. Holds neither
+/// the secret nor the password, so it can be saved and shared freely.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeriveParams {
+    /// Site the password is for. Compared without case and surrounding whitespace.
+    pub site: String,
+    /// Account on the site, if the site has several.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Incremented to get a new password for the same site, starting at 1.
+    pub counter: u32,
+    /// Length and policy (or template) of the password.
+    pub generation: EntryPolicy,
+}
+
+impl DeriveParams {
+    /// Parameters for `site` with no username, counter 1 and `DEFAULT_DERIVE_LENGTH`
+    /// characters following the default password policy.
+    pub fn new(site: &str) -> Self {
+        Self {
+            site: normalize_site(site),
+            username: None,
+            counter: 1,
+            generation: EntryPolicy {
+                length: DEFAULT_DERIVE_LENGTH,
+                policy: PasswordPolicy::default(),
+                template: None,
+            },
+        }
+    }
+
+    /// Salt of the key derivation: the algorithm version, site, username and counter, each
+    /// prefixed with its length so that no two parameter sets share a salt.
+    fn salt(&self) -> Vec<u8> {
+        let mut salt = format!("apppass-derive-v{}", DERIVE_VERSION).into_bytes();
+        for field in [normalize_site(&self.site).as_str(), self.username.as_deref().unwrap_or("")] {
+            salt.extend((field.len() as u32).to_be_bytes());
+            salt.extend(field.as_bytes());
+        }
+        salt.extend(self.counter.to_be_bytes());
+        salt
+    }
+}
+
+/// Derives the password of a site from the master secret. The same secret and parameters
+/// give the same password on every machine, without anything being stored.
+///
+/// The secret goes through Argon2id (see `DERIVE_KDF`) salted with the site, username and
+/// counter; the result seeds a ChaCha20 generator that draws the password following the
+/// length and policy (or template).
+///
+/// # Arguments
+///
+/// * `secret` - The master secret.
+/// * `params` - Site, username, counter, length and policy.
+///
+/// # Returns
+///
+/// * `Result<String>` - The password, or `AppError::InvalidInput` if the secret or site is
+///   empty, the counter is 0 or the policy cannot be satisfied.
+pub fn derive_password(secret: &str, params: &DeriveParams) -> Result<String> {
+    derive_password_with(secret, params, DERIVE_KDF)
+}
+
+/// Derives a password like `derive_password`, with other Argon2id costs.
+pub fn derive_password_with(secret: &str, params: &DeriveParams, kdf: KdfParams) -> Result<String> {
+    if secret.is_empty() {
+        return Err(AppError::InvalidInput("The master secret cannot be empty".to_string()));
+    }
+    if normalize_site(&params.site).is_empty() {
+        return Err(AppError::InvalidInput("The site cannot be empty".to_string()));
+    }
+    if params.counter == 0 {
+        return Err(AppError::InvalidInput("The counter starts at 1".to_string()));
+    }
+    params
+        .generation
+        .validate()
+        .map_err(|e| AppError::InvalidInput(format!("Password policy: {}", e)))?;
+
+    let argon2_params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32))
+        .map_err(|e| AppError::InvalidInput(format!("Key derivation parameters: {}", e)))?;
+    let mut seed = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params)
+        .hash_password_into(secret.as_bytes(), &params.salt(), &mut seed)
+        .map_err(|e| AppError::InvalidInput(format!("Key derivation failed: {}", e)))?;
+
+    params.generation.generate_with(&mut ChaCha20Rng::from_seed(seed))
+}
+
+/// Reads the master secret from `APPPASS_DERIVE_SECRET`, or prompts for it on the terminal.
+pub fn read_derive_secret() -> Result<String> {
+    if let Ok(secret) = std::env::var(DERIVE_SECRET_ENV_VAR) {
+        return Ok(secret);
+    }
+    rpassword::prompt_password("Master secret: ")
+        .map_err(|e| AppError::InvalidInput(format!("Cannot read the master secret: {}", e)))
+}
+
+/// Site names are compared without case and surrounding whitespace.
+fn normalize_site(site: &str) -> String {
+    site.trim().to_lowercase()
+}
+
+/// Lists the saved derivation parameters, sorted by site.
+///
+/// # Returns
+///
+/// * `Result<Vec<DeriveParams>>` - The saved parameters (empty if there are none), otherwise returns an `AppError`.
+pub fn list_derived_sites() -> Result<Vec<DeriveParams>> {
+    match store().get(DERIVED_SITES_KEY) {
+        Ok(data) => serde_json::from_str(&data).map_err(|e| AppError::parse(DERIVED_SITES_KEY, e)),
+        Err(KeyringError::NoEntry) => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Returns the saved derivation parameters of `site`, if any.
+pub fn get_derived_site(site: &str) -> Result<Option<DeriveParams>> {
+    let site = normalize_site(site);
+    Ok(list_derived_sites()?.into_iter().find(|p| p.site == site))
+}
+
+/// Saves the derivation parameters of a site, replacing those saved before. The password
+/// itself is never stored.
+pub fn save_derived_site(params: &DeriveParams) -> Result<()> {
+    let mut params = params.clone();
+    params.site = normalize_site(&params.site);
+    update_derived_sites(|sites| {
+        sites.retain(|p| p.site != params.site);
+        sites.push(params);
+        sites.sort_by(|a, b| a.site.cmp(&b.site));
+        Ok(())
+    })
+}
+
+/// Removes the saved derivation parameters of `site`.
+///
+/// # Returns
+///
+/// * `Result<()>` - `AppError::NotFound` if no parameters are saved for the site.
+pub fn forget_derived_site(site: &str) -> Result<()> {
+    let site = normalize_site(site);
+    update_derived_sites(|sites| {
+        let position = sites
+            .iter()
+            .position(|p| p.site == site)
+            .ok_or_else(|| AppError::NotFound(site.clone()))?;
+        sites.remove(position);
+        Ok(())
+    })
+}
+
+/// Applies `f` to the saved sites and writes the result back, holding the store lock. The
+/// record is deleted when no site is left.
+fn update_derived_sites<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&mut Vec<DeriveParams>) -> Result<()>,
+{
+    let _guard = DERIVED_SITES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = store();
+    let _store_lock = store.lock()?;
+    let mut sites = list_derived_sites()?;
+    f(&mut sites)?;
+    if sites.is_empty() {
+        return match store.delete(DERIVED_SITES_KEY) {
+            Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        };
+    }
+    let data = serde_json::to_string(&sites).map_err(|e| AppError::parse(DERIVED_SITES_KEY, e))?;
+    Ok(store.set(DERIVED_SITES_KEY, &data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap costs so the tests run quickly; the output still depends on every input.
+    const TEST_KDF: KdfParams = KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+    fn params(site: &str, counter: u32) -> DeriveParams {
+        DeriveParams { counter, ..DeriveParams::new(site) }
+    }
+
+    #[test]
+    fn test_derivation_is_deterministic() {
+        let password = derive_password_with("correct horse", &params("github", 1), TEST_KDF).unwrap();
+        assert_eq!(password.len(), 20);
+        assert!(PasswordPolicy::default().is_satisfied_by(&password));
+        assert_eq!(derive_password_with("correct horse", &params(" GitHub ", 1), TEST_KDF).unwrap(), password);
+
+        // Every input changes the password
+        let others = [
+            derive_password_with("correct horse!", &params("github", 1), TEST_KDF).unwrap(),
+            derive_password_with("correct horse", &params("gitlab", 1), TEST_KDF).unwrap(),
+            derive_password_with("correct horse", &params("github", 2), TEST_KDF).unwrap(),
+            derive_password_with(
+                "correct horse",
+                &DeriveParams { username: Some("alice".to_string()), ..params("github", 1) },
+                TEST_KDF,
+            )
+            .unwrap(),
+        ];
+        assert!(others.iter().all(|other| *other != password), "{:?}", others);
+    }
+
+    #[test]
+    fn test_known_derivation() {
+        // Locks the algorithm down: a change here breaks every derived password
+        let params = DeriveParams {
+            username: Some("alice@example.com".to_string()),
+            counter: 2,
+            generation: EntryPolicy::from_template("x{16}", PasswordPolicy::default()).unwrap(),
+            ..DeriveParams::new("example.com")
+        };
+        assert_eq!(derive_password_with("correct horse battery staple", &params, TEST_KDF).unwrap(), "5IMLheZHqCW0fXSp");
+        let params = DeriveParams {
+            generation: EntryPolicy { length: 12, policy: PasswordPolicy::default(), template: None },
+            ..params
+        };
+        assert_eq!(derive_password_with("correct horse battery staple", &params, TEST_KDF).unwrap(), "niFN8JkPmFN1");
+    }
+
+    #[test]
+    fn test_invalid_derivations() {
+        assert!(matches!(derive_password_with("", &params("github", 1), TEST_KDF), Err(AppError::InvalidInput(_))));
+        assert!(matches!(derive_password_with("s", &params("  ", 1), TEST_KDF), Err(AppError::InvalidInput(_))));
+        assert!(matches!(derive_password_with("s", &params("github", 0), TEST_KDF), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_saved_sites() {
+        let site = "test_derived_site.example";
+        let _ = forget_derived_site(site);
+
+        let saved = params(site, 3);
+        save_derived_site(&saved).unwrap();
+        assert_eq!(get_derived_site(&site.to_uppercase()).unwrap(), Some(saved.clone()));
+        save_derived_site(&DeriveParams { counter: 4, ..saved.clone() }).unwrap();
+        assert_eq!(list_derived_sites().unwrap().iter().filter(|p| p.site == site).count(), 1);
+        assert_eq!(get_derived_site(site).unwrap().unwrap().counter, 4);
+
+        forget_derived_site(site).unwrap();
+        assert_eq!(get_derived_site(site).unwrap(), None);
+        assert!(matches!(forget_derived_site(site), Err(AppError::NotFound(_))));
+    }
+}
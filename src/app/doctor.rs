@@ -0,0 +1,395 @@
+use keyring::Error as KeyringError;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::app::config::config_path;
+use crate::app::error::AppError;
+use crate::app::index::Index;
+use crate::app::keys::{app_name_from_key, entry_key, is_reserved_key, metadata_key, split_metadata_key,
+                       METADATA_SUFFIXES};
+use crate::app::migrate::{migrate_store, schema_version, STORE_SCHEMA_VERSION};
+use crate::app::profile::active_profile;
+use crate::app::store::SecretStore;
+use crate::app::{APP_INDEX, OTP_EXPIRY_SUFFIX, PASSWORD_TYPE_SUFFIX, SCHEMA_VERSION_KEY};
+
+/// The kinds of problems `apppass doctor` detects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// The backend could not be opened or listed.
+    UnreadableBackend,
+    /// The index record exists but cannot be parsed.
+    UnreadableIndex,
+    /// The index lists an application whose password is missing.
+    OrphanedIndexEntry,
+    /// A metadata key (`_type`, `_otp_expiry`, `_record`, `_history`) whose application has no password.
+    OrphanedMetadata,
+    /// A password stored without an index entry, so it is never listed.
+    MissingFromIndex,
+    /// A one-time password past its expiry that was not cleaned up.
+    ExpiredOtp,
+    /// The store uses an older schema and has not been migrated yet.
+    OutdatedSchema,
+    /// The store was written by a newer apppass and is not checked.
+    UnsupportedSchema,
+}
+
+impl IssueKind {
+    /// Returns true if `--fix` can repair this kind of issue.
+    pub fn is_fixable(self) -> bool {
+        !matches!(
+            self,
+            IssueKind::UnreadableBackend | IssueKind::UnreadableIndex | IssueKind::UnsupportedSchema
+        )
+    }
+}
+
+/// A single problem found in the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+    /// What kind of problem this is.
+    pub kind: IssueKind,
+    /// The store key the problem concerns (empty for backend-wide problems).
+    pub key: String,
+    /// Human-readable description.
+    pub message: String,
+    /// True if the problem was repaired.
+    pub fixed: bool,
+}
+
+/// The result of checking a store, printed by `apppass doctor` (as JSON with `--json`).
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    /// Profile that was checked.
+    pub profile: String,
+    /// Backend that was checked.
+    pub backend: String,
+    /// Number of healthy applications in the index.
+    pub entries: usize,
+    /// Problems found, in the order they were detected.
+    pub issues: Vec<Issue>,
+}
+
+impl DoctorReport {
+    /// Creates an empty report.
+    pub fn new(profile: &str, backend: &str) -> Self {
+        Self {
+            profile: profile.to_string(),
+            backend: backend.to_string(),
+            entries: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Returns true if any problem is left unrepaired.
+    pub fn has_unfixed_issues(&self) -> bool {
+        self.issues.iter().any(|issue| !issue.fixed)
+    }
+
+    fn push(&mut self, kind: IssueKind, key: &str, message: String) -> &mut Issue {
+        self.issues.push(Issue {
+            kind,
+            key: key.to_string(),
+            message,
+            fixed: false,
+        });
+        self.issues.last_mut().expect("issue was just pushed")
+    }
+
+    /// Records that the backend could not be opened or read.
+    pub fn unreadable_backend(&mut self, error: &str) {
+        self.push(IssueKind::UnreadableBackend, "", format!("Cannot read the backend: {}", error));
+    }
+}
+
+/// Checks `store` for inconsistencies between passwords, metadata and the index, optionally repairing them.
+///
+/// # Arguments
+///
+/// * `store` - The store to check.
+/// * `fix` - Repair the problems found: an outdated store is migrated, orphaned index
+///   entries and metadata are removed, unindexed passwords are added to the index and
+///   expired OTPs are deleted.
+/// * `report` - The report the findings are added to.
+///
+/// # Behavior
+///
+/// The store lock is held for the whole check, so other apppass processes cannot change
+/// the store halfway through. Nothing is written unless `fix` is true.
+pub fn check_store(store: &dyn SecretStore, fix: bool, report: &mut DoctorReport) {
+    let _lock = match store.lock() {
+        Ok(lock) => lock,
+        Err(e) => return report.unreadable_backend(&e.to_string()),
+    };
+
+    // Stores written by a newer apppass are left alone
+    match schema_version(store) {
+        Ok(version) if version > STORE_SCHEMA_VERSION => {
+            let error = AppError::UnsupportedSchema {
+                found: version,
+                supported: STORE_SCHEMA_VERSION,
+            };
+            report.push(IssueKind::UnsupportedSchema, SCHEMA_VERSION_KEY, error.to_string());
+            return;
+        }
+        Ok(version) if version < STORE_SCHEMA_VERSION => {
+            let migrated = if fix { Some(migrate_store(store, &config_path(), &active_profile())) } else { None };
+            let issue = report.push(
+                IssueKind::OutdatedSchema,
+                SCHEMA_VERSION_KEY,
+                format!(
+                    "The store uses schema version {} (current: {}) and is migrated the next time apppass opens it",
+                    version, STORE_SCHEMA_VERSION
+                ),
+            );
+            match migrated {
+                Some(Ok(_)) => issue.fixed = true,
+                Some(Err(e)) => issue.message = format!("{}; migration failed: {}", issue.message, e),
+                None => {}
+            }
+        }
+        Ok(_) => {}
+        Err(e) => return report.unreadable_backend(&e.to_string()),
+    }
+
+    let keys: BTreeSet<String> = match store.list() {
+        Ok(keys) => keys.into_iter().collect(),
+        Err(e) => return report.unreadable_backend(&e.to_string()),
+    };
+    let mut index = match store.get(APP_INDEX) {
+        Ok(data) => match Index::parse_in(&data, store) {
+            Ok(index) => index,
+            Err(e) => {
+                report.push(IssueKind::UnreadableIndex, APP_INDEX, format!("The index cannot be read: {}", e));
+                return;
+            }
+        },
+        Err(KeyringError::NoEntry) => Index::default(),
+        Err(e) => return report.unreadable_backend(&e.to_string()),
+    };
+    let mut index_changed = false;
+    let mut deleted = BTreeSet::new();
+
+    // Index entries without a password
+    for name in index.names() {
+        if !keys.contains(&entry_key(&name)) {
+            let issue = report.push(
+                IssueKind::OrphanedIndexEntry,
+                &name,
+                format!("'{}' is listed in the index but has no password", name),
+            );
+            if fix {
+                index.remove(&name);
+                index_changed = true;
+                issue.fixed = true;
+            }
+        }
+    }
+
+    // Expired one-time passwords
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    for key in keys.iter().filter(|k| !is_reserved_key(k)) {
+        let name = app_name_from_key(key);
+        let expiry = store
+            .get(&metadata_key(&name, OTP_EXPIRY_SUFFIX))
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok());
+        if let Some(expiry) = expiry.filter(|&expiry| now >= expiry) {
+            let issue = report.push(
+                IssueKind::ExpiredOtp,
+                key,
+                format!("One-time password '{}' expired {} seconds ago", name, now - expiry),
+            );
+            if fix && delete_key(store, key) {
+                deleted.insert(key.clone());
+                index_changed |= index.remove(&name);
+                for suffix in METADATA_SUFFIXES {
+                    let key = metadata_key(&name, suffix);
+                    if keys.contains(&key) && delete_key(store, &key) {
+                        deleted.insert(key);
+                    }
+                }
+                issue.fixed = true;
+            }
+        }
+    }
+
+    for key in &keys {
+        if deleted.contains(key) || key == APP_INDEX {
+            continue;
+        }
+        if let Some((app_name, _)) = split_metadata_key(key) {
+            // Metadata whose application has no password
+            if !keys.contains(&entry_key(&app_name)) {
+                let issue = report.push(
+                    IssueKind::OrphanedMetadata,
+                    key,
+                    format!("'{}' belongs to '{}', which has no password", key, app_name),
+                );
+                issue.fixed = fix && delete_key(store, key);
+            }
+        } else if !is_reserved_key(key) && index.get(&app_name_from_key(key)).is_none() {
+            // Passwords that are never listed
+            let name = app_name_from_key(key);
+            let issue = report.push(
+                IssueKind::MissingFromIndex,
+                key,
+                format!("'{}' has a password but is not in the index", name),
+            );
+            if fix {
+                index.touch(&name);
+                if let Ok(password_type) = store.get(&metadata_key(&name, PASSWORD_TYPE_SUFFIX)) {
+                    if let Some(entry) = index.entries.get_mut(&name) {
+                        entry.password_type = password_type;
+                    }
+                }
+                index_changed = true;
+                issue.fixed = true;
+            }
+        }
+    }
+
+    if index_changed {
+        let saved = match index.to_json() {
+            Ok(_) if index.is_empty() => matches!(store.delete(APP_INDEX), Ok(()) | Err(KeyringError::NoEntry)),
+            Ok(data) => store.set(APP_INDEX, &data).is_ok(),
+            Err(_) => false,
+        };
+        if !saved {
+            // The repairs that depend on the index were not written
+            for issue in report.issues.iter_mut().filter(|i| {
+                matches!(i.kind, IssueKind::OrphanedIndexEntry | IssueKind::MissingFromIndex)
+            }) {
+                issue.fixed = false;
+            }
+            report.push(IssueKind::UnreadableBackend, APP_INDEX, "Failed to write the repaired index".to_string());
+        }
+    }
+
+    report.entries = index
+        .names()
+        .iter()
+        .map(|name| entry_key(name))
+        .filter(|key| keys.contains(key) && !deleted.contains(key))
+        .count();
+}
+
+/// Deletes `key`, treating a missing key as deleted. Returns true on success.
+fn delete_key(store: &dyn SecretStore, key: &str) -> bool {
+    matches!(store.delete(key), Ok(()) | Err(KeyringError::NoEntry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::store::MemoryStore;
+
+    /// A store with one healthy entry and one problem of each fixable kind.
+    fn broken_store() -> MemoryStore {
+        let store = MemoryStore::new();
+        store.set(SCHEMA_VERSION_KEY, &STORE_SCHEMA_VERSION.to_string()).unwrap();
+        let mut index = Index::default();
+        index.touch("healthy");
+        index.touch("ghost");
+        index.touch("otp");
+        store.set(APP_INDEX, &index.to_json().unwrap()).unwrap();
+
+        store.set("healthy", "pw").unwrap();
+        store.set("healthy_record", r#"{"username":"me"}"#).unwrap();
+        store.set("otp", "123456").unwrap();
+        store.set("otp_otp_expiry", "1").unwrap();
+        store.set("gone_type", "custom").unwrap();
+        store.set("gone_otp_expiry", "99999999999").unwrap();
+        store.set("unlisted", "pw").unwrap();
+        store.set("unlisted_type", "custom").unwrap();
+        store
+    }
+
+    fn kinds(report: &DoctorReport) -> Vec<(IssueKind, String)> {
+        report.issues.iter().map(|i| (i.kind, i.key.clone())).collect()
+    }
+
+    #[test]
+    fn test_check_reports_without_changing_the_store() {
+        let store = broken_store();
+        let before = store.list().unwrap();
+        let mut report = DoctorReport::new("default", "memory");
+        check_store(&store, false, &mut report);
+
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (IssueKind::OrphanedIndexEntry, "ghost".to_string()),
+                (IssueKind::ExpiredOtp, "otp".to_string()),
+                (IssueKind::OrphanedMetadata, "gone_otp_expiry".to_string()),
+                (IssueKind::OrphanedMetadata, "gone_type".to_string()),
+                (IssueKind::MissingFromIndex, "unlisted".to_string()),
+            ]
+        );
+        assert!(report.has_unfixed_issues());
+        assert_eq!(store.list().unwrap(), before);
+    }
+
+    #[test]
+    fn test_fix_repairs_every_issue() {
+        let store = broken_store();
+        let mut report = DoctorReport::new("default", "memory");
+        check_store(&store, true, &mut report);
+        assert!(!report.has_unfixed_issues());
+        assert_eq!(report.entries, 2);
+
+        let index = Index::parse(&store.get(APP_INDEX).unwrap()).unwrap();
+        assert_eq!(index.names(), vec!["healthy", "unlisted"]);
+        assert_eq!(index.get("unlisted").unwrap().password_type, "custom");
+        assert_eq!(store.list().unwrap(), vec![APP_INDEX, SCHEMA_VERSION_KEY, "healthy", "healthy_record", "unlisted", "unlisted_type"]);
+
+        let mut again = DoctorReport::new("default", "memory");
+        check_store(&store, false, &mut again);
+        assert!(again.issues.is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_index_is_not_fixed() {
+        let store = MemoryStore::new();
+        store.set(SCHEMA_VERSION_KEY, &STORE_SCHEMA_VERSION.to_string()).unwrap();
+        store.set(APP_INDEX, "{not json").unwrap();
+        let mut report = DoctorReport::new("default", "memory");
+        check_store(&store, true, &mut report);
+
+        assert_eq!(kinds(&report), vec![(IssueKind::UnreadableIndex, APP_INDEX.to_string())]);
+        assert!(report.has_unfixed_issues());
+        assert_eq!(store.get(APP_INDEX).unwrap(), "{not json");
+    }
+
+    #[test]
+    fn test_outdated_schema_is_migrated_by_fix() {
+        let store = MemoryStore::new();
+        store.set(APP_INDEX, "legacy").unwrap();
+        store.set("legacy", "pw").unwrap();
+        store.set("legacy_type", "custom").unwrap();
+
+        let mut report = DoctorReport::new("default", "memory");
+        check_store(&store, false, &mut report);
+        assert_eq!(kinds(&report), vec![(IssueKind::OutdatedSchema, SCHEMA_VERSION_KEY.to_string())]);
+        assert_eq!(store.get(APP_INDEX).unwrap(), "legacy");
+
+        let mut report = DoctorReport::new("default", "memory");
+        check_store(&store, true, &mut report);
+        assert!(!report.has_unfixed_issues());
+        assert_eq!(report.entries, 1);
+        assert_eq!(store.list().unwrap(), vec![APP_INDEX, SCHEMA_VERSION_KEY, "legacy"]);
+    }
+
+    #[test]
+    fn test_newer_schema_is_not_checked() {
+        let store = broken_store();
+        store.set(SCHEMA_VERSION_KEY, &(STORE_SCHEMA_VERSION + 1).to_string()).unwrap();
+        let mut report = DoctorReport::new("default", "memory");
+        check_store(&store, true, &mut report);
+        assert_eq!(kinds(&report), vec![(IssueKind::UnsupportedSchema, SCHEMA_VERSION_KEY.to_string())]);
+        assert!(report.has_unfixed_issues());
+    }
+}
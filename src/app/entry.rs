@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::app::policy::EntryPolicy;
+
+/// Additional details stored alongside an entry's password.
+///
+/// Stored as JSON under `<app_name>_record`. Every field is optional, and entries
+/// without any details have no record at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryRecord {
+    /// Username or login for the application.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// URLs where the credentials are used.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<String>,
+    /// Free-form notes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Arbitrary custom key/value fields.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    /// Labels used to group and find entries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Length and policy the password was generated with, reused when it is regenerated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<EntryPolicy>,
+}
+
+impl EntryRecord {
+    /// Returns true if the record carries no details.
+    pub fn is_empty(&self) -> bool {
+        self.username.is_none()
+            && self.urls.is_empty()
+            && self.notes.is_none()
+            && self.fields.is_empty()
+            && self.tags.is_empty()
+            && self.policy.is_none()
+    }
+
+    /// Returns the details as `(label, value)` pairs in display order.
+    pub fn display_lines(&self) -> Vec<(String, String)> {
+        let mut lines = Vec::new();
+        if let Some(username) = &self.username {
+            lines.push(("Username".to_string(), username.clone()));
+        }
+        for url in &self.urls {
+            lines.push(("URL".to_string(), url.clone()));
+        }
+        if let Some(notes) = &self.notes {
+            lines.push(("Notes".to_string(), notes.clone()));
+        }
+        for (key, value) in &self.fields {
+            lines.push((key.clone(), value.clone()));
+        }
+        if !self.tags.is_empty() {
+            lines.push(("Tags".to_string(), self.tags.join(", ")));
+        }
+        lines
+    }
+
+    /// Joins the URLs into a single cell, one per line (used by CSV export).
+    pub fn urls_cell(&self) -> String {
+        self.urls.join("\n")
+    }
+
+    /// Joins the custom fields into a single cell as `key=value` lines (used by CSV export).
+    pub fn fields_cell(&self) -> String {
+        self.fields
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Joins the tags into a single cell, one per line (used by CSV export).
+    pub fn tags_cell(&self) -> String {
+        self.tags.join("\n")
+    }
+
+    /// Builds a record from CSV cells produced by `urls_cell`, `fields_cell` and `tags_cell`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<EntryRecord, String>` - The record, or an error message if a field line is malformed.
+    pub fn from_cells(username: &str, urls: &str, notes: &str, fields: &str, tags: &str) -> Result<Self, String> {
+        let mut record = EntryRecord {
+            username: non_empty(username),
+            urls: urls.lines().map(str::trim).filter(|u| !u.is_empty()).map(String::from).collect(),
+            notes: non_empty(notes),
+            fields: BTreeMap::new(),
+            tags: Vec::new(),
+            policy: None,
+        };
+        for tag in tags.lines() {
+            add_tag(&mut record.tags, tag);
+        }
+        for line in fields.lines().filter(|l| !l.trim().is_empty()) {
+            let (key, value) = parse_field(line)?;
+            record.fields.insert(key, value);
+        }
+        Ok(record)
+    }
+}
+
+/// A set of edits to apply to an `EntryRecord`, as given on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct EntryChanges {
+    /// New username. An empty string clears it.
+    pub username: Option<String>,
+    /// URLs to add.
+    pub add_urls: Vec<String>,
+    /// URLs to remove.
+    pub remove_urls: Vec<String>,
+    /// New notes. An empty string clears them.
+    pub notes: Option<String>,
+    /// Custom fields to set.
+    pub set_fields: Vec<(String, String)>,
+    /// Custom fields to remove.
+    pub remove_fields: Vec<String>,
+    /// Tags to add.
+    pub add_tags: Vec<String>,
+    /// Tags to remove.
+    pub remove_tags: Vec<String>,
+}
+
+impl EntryChanges {
+    /// Returns true if no edits were requested.
+    pub fn is_empty(&self) -> bool {
+        self.username.is_none()
+            && self.add_urls.is_empty()
+            && self.remove_urls.is_empty()
+            && self.notes.is_none()
+            && self.set_fields.is_empty()
+            && self.remove_fields.is_empty()
+            && self.add_tags.is_empty()
+            && self.remove_tags.is_empty()
+    }
+
+    /// Applies the edits to `record`. Removals are applied before additions.
+    pub fn apply(&self, record: &mut EntryRecord) {
+        if let Some(username) = &self.username {
+            record.username = non_empty(username);
+        }
+        if let Some(notes) = &self.notes {
+            record.notes = non_empty(notes);
+        }
+        record.urls.retain(|u| !self.remove_urls.contains(u));
+        for url in &self.add_urls {
+            if !record.urls.contains(url) {
+                record.urls.push(url.clone());
+            }
+        }
+        for key in &self.remove_fields {
+            record.fields.remove(key);
+        }
+        for (key, value) in &self.set_fields {
+            record.fields.insert(key.clone(), value.clone());
+        }
+        record.tags.retain(|t| !self.remove_tags.iter().any(|r| r.trim() == t));
+        for tag in &self.add_tags {
+            add_tag(&mut record.tags, tag);
+        }
+    }
+}
+
+/// Adds `tag` (trimmed) to `tags` unless it is empty or already present.
+fn add_tag(tags: &mut Vec<String>, tag: &str) {
+    let tag = tag.trim();
+    if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+        tags.push(tag.to_string());
+    }
+}
+
+/// Parses a custom field given as `key=value`.
+///
+/// # Arguments
+///
+/// * `input` - The field in `key=value` form. The value may contain `=`.
+///
+/// # Returns
+///
+/// * `Result<(String, String), String>` - The key and value, or an error message if the key is missing.
+pub fn parse_field(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("Invalid field '{}'. Expected KEY=VALUE", input)),
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_record_serializes_compactly() {
+        let record = EntryRecord::default();
+        assert!(record.is_empty());
+        assert_eq!(serde_json::to_string(&record).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_changes_apply() {
+        let mut record = EntryRecord {
+            username: Some("old".to_string()),
+            urls: vec!["https://a.example".to_string()],
+            notes: Some("note".to_string()),
+            fields: BTreeMap::from([("pin".to_string(), "1234".to_string())]),
+            tags: vec!["work".to_string()],
+            policy: None,
+        };
+        let changes = EntryChanges {
+            username: Some("new".to_string()),
+            add_urls: vec!["https://b.example".to_string()],
+            remove_urls: vec!["https://a.example".to_string()],
+            notes: Some(String::new()),
+            set_fields: vec![("recovery".to_string(), "abc=def".to_string())],
+            remove_fields: vec!["pin".to_string()],
+            add_tags: vec![" email ".to_string(), "email".to_string()],
+            remove_tags: vec!["work".to_string()],
+        };
+        changes.apply(&mut record);
+
+        assert_eq!(record.username.as_deref(), Some("new"));
+        assert_eq!(record.urls, vec!["https://b.example"]);
+        assert_eq!(record.notes, None);
+        assert_eq!(record.fields.get("recovery").map(String::as_str), Some("abc=def"));
+        assert!(!record.fields.contains_key("pin"));
+        assert_eq!(record.tags, vec!["email"]);
+    }
+
+    #[test]
+    fn test_cells_roundtrip() {
+        let mut record = EntryRecord {
+            username: Some("me@example.com".to_string()),
+            urls: vec!["https://a.example".to_string(), "https://b.example".to_string()],
+            notes: Some("line one, with comma".to_string()),
+            fields: BTreeMap::new(),
+            tags: vec!["mail".to_string(), "personal".to_string()],
+            policy: None,
+        };
+        record.fields.insert("question".to_string(), "a=b".to_string());
+
+        let parsed = EntryRecord::from_cells(
+            "me@example.com",
+            &record.urls_cell(),
+            "line one, with comma",
+            &record.fields_cell(),
+            &record.tags_cell(),
+        )
+        .unwrap();
+        assert_eq!(parsed, record);
+    }
+
+    #[test]
+    fn test_parse_field() {
+        assert_eq!(parse_field("k=v=w"), Ok(("k".to_string(), "v=w".to_string())));
+        assert!(parse_field("novalue").is_err());
+        assert!(parse_field("=value").is_err());
+    }
+}
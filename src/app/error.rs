@@ -0,0 +1,148 @@
+use keyring::Error as KeyringError;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Errors returned by apppass operations.
+///
+/// The CLI, the interactive console and the TUI print these through `Display`, so each
+/// variant carries what is needed for a message that tells the user what went wrong.
+#[derive(Debug)]
+pub enum AppError {
+    /// An entry with this name already exists.
+    AlreadyExists(String),
+    /// No entry with this name exists.
+    NotFound(String),
+    /// The name cannot be used for an application.
+    InvalidName {
+        /// The rejected name.
+        name: String,
+        /// Why it was rejected.
+        reason: String,
+    },
+    /// An argument is out of range or malformed (e.g. an unknown history revision).
+    InvalidInput(String),
+    /// Reading or writing a file failed.
+    Io {
+        /// The file being accessed.
+        path: PathBuf,
+        /// The underlying error.
+        source: std::io::Error,
+    },
+    /// Stored data or an imported file could not be parsed.
+    Parse {
+        /// Where the data came from (a store key, or `file:line`).
+        context: String,
+        /// What is wrong with it.
+        message: String,
+    },
+    /// The store was written by a newer version of apppass.
+    UnsupportedSchema {
+        /// Schema version of the store.
+        found: u32,
+        /// Newest schema version this build understands.
+        supported: u32,
+    },
+    /// The storage backend failed.
+    Backend(KeyringError),
+    /// The system clipboard cannot be used (e.g. there is no display).
+    Clipboard(String),
+}
+
+/// Result type used across apppass.
+pub type Result<T> = std::result::Result<T, AppError>;
+
+impl AppError {
+    /// Converts a store error for the entry `name`, reporting a missing key as `NotFound`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The application (or key) that was accessed.
+    /// * `error` - The error returned by the store.
+    ///
+    /// # Returns
+    ///
+    /// * `AppError` - `NotFound(name)` for `KeyringError::NoEntry`, otherwise `Backend(error)`.
+    pub fn for_entry(name: &str, error: KeyringError) -> Self {
+        match error {
+            KeyringError::NoEntry => AppError::NotFound(name.to_string()),
+            other => AppError::Backend(other),
+        }
+    }
+
+    /// Creates an `Io` error for `path`.
+    pub fn io(path: impl AsRef<Path>, source: std::io::Error) -> Self {
+        AppError::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+
+    /// Creates a `Parse` error.
+    pub fn parse(context: impl Into<String>, message: impl fmt::Display) -> Self {
+        AppError::Parse {
+            context: context.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::AlreadyExists(name) => write!(f, "A password for '{}' already exists", name),
+            AppError::NotFound(name) => write!(f, "No password found for '{}'", name),
+            AppError::InvalidName { name, reason } => write!(f, "Invalid application name '{}': {}", name, reason),
+            AppError::InvalidInput(message) => write!(f, "{}", message),
+            AppError::Io { path, source } => write!(f, "Cannot access '{}': {}", path.display(), source),
+            AppError::Parse { context, message } => write!(f, "Invalid data in {}: {}", context, message),
+            AppError::UnsupportedSchema { found, supported } => write!(
+                f,
+                "The store uses schema version {}, but this version of apppass only supports up to {}. Upgrade apppass to use it",
+                found, supported
+            ),
+            AppError::Backend(e) => write!(f, "Storage error: {}", e),
+            AppError::Clipboard(message) => write!(f, "Clipboard error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Io { source, .. } => Some(source),
+            AppError::Backend(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<KeyringError> for AppError {
+    fn from(error: KeyringError) -> Self {
+        AppError::Backend(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_entry_maps_missing_keys_to_not_found() {
+        assert!(matches!(AppError::for_entry("gmail", KeyringError::NoEntry), AppError::NotFound(_)));
+        assert!(matches!(
+            AppError::for_entry("gmail", KeyringError::NoStorageAccess("locked".into())),
+            AppError::Backend(_)
+        ));
+    }
+
+    #[test]
+    fn test_messages_name_the_entry() {
+        assert_eq!(
+            AppError::AlreadyExists("gmail".to_string()).to_string(),
+            "A password for 'gmail' already exists"
+        );
+        assert_eq!(AppError::NotFound("gmail".to_string()).to_string(), "No password found for 'gmail'");
+        let io = AppError::io("/nowhere/out.csv", std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(io.to_string().starts_with("Cannot access '/nowhere/out.csv'"));
+    }
+}
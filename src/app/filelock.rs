@@ -0,0 +1,170 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+
+/// Locks held by this process, keyed by lock file path.
+///
+/// OS file locks exclude other processes but not other threads of this process (and
+/// locking the same file twice from one thread would deadlock), so ownership within the
+/// process is tracked here: the owning thread may re-acquire a lock it already holds,
+/// and other threads wait on `LOCKS_RELEASED`.
+static HELD_LOCKS: Lazy<Mutex<HashMap<PathBuf, HeldLock>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static LOCKS_RELEASED: Condvar = Condvar::new();
+
+struct HeldLock {
+    owner: ThreadId,
+    depth: usize,
+    /// The locked file. `None` while the owner is still waiting for the OS lock.
+    file: Option<File>,
+}
+
+/// An exclusive lock on a file, shared across threads and processes.
+///
+/// Locks are re-entrant per thread and released when the last guard is dropped.
+pub struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Blocks until the exclusive lock on `path` is held, creating the file if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The lock file. Its parent directory is created if missing.
+    ///
+    /// # Returns
+    ///
+    /// * `std::io::Result<FileLock>` - A guard that releases the lock when dropped.
+    pub fn acquire(path: &Path) -> std::io::Result<FileLock> {
+        let me = thread::current().id();
+        let mut held = HELD_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            match held.get_mut(path) {
+                Some(lock) if lock.owner == me => {
+                    lock.depth += 1;
+                    return Ok(FileLock { path: path.to_path_buf() });
+                }
+                Some(_) => {
+                    held = LOCKS_RELEASED.wait(held).unwrap_or_else(|e| e.into_inner());
+                }
+                None => break,
+            }
+        }
+        held.insert(path.to_path_buf(), HeldLock { owner: me, depth: 1, file: None });
+        drop(held);
+
+        // Wait for other processes without blocking the other locks of this process
+        match lock_file(path) {
+            Ok(file) => {
+                let mut held = HELD_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(lock) = held.get_mut(path) {
+                    lock.file = Some(file);
+                }
+                Ok(FileLock { path: path.to_path_buf() })
+            }
+            Err(e) => {
+                release(path);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        release(&self.path);
+    }
+}
+
+fn lock_file(path: &Path) -> std::io::Result<File> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Drops one level of the lock on `path`, unlocking the file when the last one goes.
+fn release(path: &Path) {
+    let mut held = HELD_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    let remove = match held.get_mut(path) {
+        Some(lock) => {
+            lock.depth -= 1;
+            lock.depth == 0
+        }
+        None => false,
+    };
+    if remove {
+        // Dropping the file releases the OS lock
+        held.remove(path);
+        LOCKS_RELEASED.notify_all();
+    }
+}
+
+/// Returns the lock file guarding the given service namespace of the OS keyring.
+pub fn keyring_lock_path(service: &str) -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("apppass")
+        .join("locks")
+        .join(format!("{}.lock", service))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn temp_lock(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("apppass_test_{}_{}.lock", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_lock_is_reentrant_within_a_thread() {
+        let path = temp_lock("reentrant");
+        let outer = FileLock::acquire(&path).unwrap();
+        let inner = FileLock::acquire(&path).unwrap();
+        drop(inner);
+        drop(outer);
+
+        // Fully released: can be taken again
+        drop(FileLock::acquire(&path).unwrap());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_lock_excludes_other_threads() {
+        let path = temp_lock("exclusive");
+        let inside = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                let inside = Arc::clone(&inside);
+                thread::spawn(move || {
+                    for _ in 0..20 {
+                        let _guard = FileLock::acquire(&path).unwrap();
+                        assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                        thread::yield_now();
+                        inside.fetch_sub(1, Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let _ = fs::remove_file(&path);
+    }
+}
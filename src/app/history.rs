@@ -0,0 +1,191 @@
+use keyring::Error as KeyringError;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::app::error::{AppError, Result};
+use crate::app::store::store;
+use crate::app::keys::metadata_key;
+use crate::app::PASSWORD_HISTORY_SUFFIX;
+
+/// Maximum number of previous passwords kept per entry.
+pub const HISTORY_LIMIT: usize = 10;
+
+/// A previous password of an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// The password that was replaced.
+    pub password: String,
+    /// Unix timestamp of when it was replaced.
+    pub changed_at: u64,
+    /// OS user that replaced it.
+    pub changed_by: String,
+    /// Operation that replaced it (e.g. "update", "update-custom", "import", "restore", "tui").
+    pub source: String,
+}
+
+/// Returns the key under which the password history of `app_name` is stored.
+fn history_key(app_name: &str) -> String {
+    metadata_key(app_name, PASSWORD_HISTORY_SUFFIX)
+}
+
+/// Gets the previous passwords of an application, most recent first.
+///
+/// # Arguments
+///
+/// * `app_name` - The name of the application.
+///
+/// # Returns
+///
+/// * `Result<Vec<HistoryEntry>>` - The history (empty if there is none), `AppError::Parse` if
+///   the stored record is malformed, otherwise returns an `AppError`.
+pub fn get_history(app_name: &str) -> Result<Vec<HistoryEntry>> {
+    match store().get(&history_key(app_name)) {
+        Ok(data) => serde_json::from_str(&data).map_err(|e| AppError::parse(history_key(app_name), e)),
+        Err(KeyringError::NoEntry) => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Replaces the stored history of an application. An empty history removes the record.
+///
+/// # Arguments
+///
+/// * `app_name` - The name of the application.
+/// * `history` - The previous passwords, most recent first. Only the first `HISTORY_LIMIT` are kept.
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if successful, otherwise returns an `AppError`.
+pub fn set_history(app_name: &str, history: &[HistoryEntry]) -> Result<()> {
+    if history.is_empty() {
+        return delete_history(app_name);
+    }
+    let history = &history[..history.len().min(HISTORY_LIMIT)];
+    let data = serde_json::to_string(history).map_err(|e| AppError::parse(history_key(app_name), e))?;
+    Ok(store().set(&history_key(app_name), &data)?)
+}
+
+/// Records `password` as the most recent previous password of an application,
+/// dropping the oldest entry once `HISTORY_LIMIT` is reached.
+///
+/// # Arguments
+///
+/// * `app_name` - The name of the application.
+/// * `password` - The password being replaced.
+/// * `source` - The operation replacing it.
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if successful, otherwise returns an `AppError`.
+pub fn push_history(app_name: &str, password: &str, source: &str) -> Result<()> {
+    let mut history = get_history(app_name)?;
+    history.insert(
+        0,
+        HistoryEntry {
+            password: password.to_string(),
+            changed_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            changed_by: current_user(),
+            source: source.to_string(),
+        },
+    );
+    set_history(app_name, &history)
+}
+
+/// Deletes the password history of an application, if any.
+///
+/// # Arguments
+///
+/// * `app_name` - The name of the application.
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if deleted or there was no history, otherwise returns an `AppError`.
+pub fn delete_history(app_name: &str) -> Result<()> {
+    match store().delete(&history_key(app_name)) {
+        Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD HH:MM:SS` (UTC).
+///
+/// # Arguments
+///
+/// * `timestamp` - Seconds since the Unix epoch.
+///
+/// # Returns
+///
+/// * `String` - The formatted date and time.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let secs = timestamp % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        (secs % 3_600) / 60,
+        secs % 60
+    )
+}
+
+/// Returns the name of the OS user running apppass.
+pub fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_history_is_newest_first_and_bounded() {
+        let app_name = "test_history_bounded_app";
+        delete_history(app_name).unwrap();
+
+        for i in 0..HISTORY_LIMIT + 3 {
+            push_history(app_name, &format!("pwd{}", i), "update").unwrap();
+        }
+
+        let history = get_history(app_name).unwrap();
+        assert_eq!(history.len(), HISTORY_LIMIT);
+        assert_eq!(history[0].password, format!("pwd{}", HISTORY_LIMIT + 2));
+        assert_eq!(history[0].source, "update");
+        assert_eq!(history.last().unwrap().password, "pwd3");
+
+        delete_history(app_name).unwrap();
+        assert!(get_history(app_name).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_history_is_a_parse_error() {
+        let app_name = "test_history_malformed_app";
+        store().set(&history_key(app_name), "not json").unwrap();
+        assert!(matches!(get_history(app_name), Err(AppError::Parse { .. })));
+        delete_history(app_name).unwrap();
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_timestamp(1_736_510_400), "2025-01-10 12:00:00");
+    }
+}
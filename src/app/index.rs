@@ -0,0 +1,298 @@
+use keyring::Error as KeyringError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::app::error::{AppError, Result};
+use crate::app::keys::legacy_key;
+use crate::app::store::{store, SecretStore};
+use crate::app::{APP_INDEX, OTP_EXPIRY_SUFFIX, PASSWORD_LENGTH_KEY, PASSWORD_TYPE_SUFFIX};
+
+/// Current schema version of the index record.
+pub const INDEX_SCHEMA_VERSION: u32 = 1;
+
+/// Serializes read-modify-write cycles on the index within this process
+/// (e.g. OTP cleanup threads racing with the UI thread). Other processes are excluded
+/// by the store lock taken in `update_index`.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Metadata kept in the index for every stored application.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Name of the application.
+    pub name: String,
+    /// Unix timestamp of when the entry was first saved (0 if unknown).
+    pub created: u64,
+    /// Unix timestamp of the last time the password was saved (0 if unknown).
+    pub updated: u64,
+    /// Password type: "auto", "pin" or "custom".
+    #[serde(rename = "type")]
+    pub password_type: String,
+}
+
+/// The structured index of every application stored by apppass.
+///
+/// Stored as JSON under `APP_INDEX`. Older releases stored a comma-joined list of
+/// names instead; that format is still read (see `Index::parse`) and is replaced by
+/// the JSON record on the next write.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Index {
+    /// Schema version of the record.
+    pub version: u32,
+    /// Entries keyed by application name.
+    pub entries: BTreeMap<String, IndexEntry>,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self {
+            version: INDEX_SCHEMA_VERSION,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl Index {
+    /// Parses a stored index record of the active store.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The raw value stored under `APP_INDEX`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Index>` - The parsed index, `AppError::Parse` if the record is malformed, or
+    ///   `AppError::UnsupportedSchema` if it was written by a newer version of apppass.
+    pub fn parse(data: &str) -> Result<Index> {
+        Self::parse_in(data, &*store())
+    }
+
+    /// Parses an index record read from `store`, which is also where the types of a
+    /// legacy index are looked up.
+    pub fn parse_in(data: &str, store: &dyn SecretStore) -> Result<Index> {
+        let trimmed = data.trim();
+        if !trimmed.starts_with('{') {
+            return Ok(Self::parse_legacy(trimmed, store));
+        }
+
+        let index: Index = serde_json::from_str(trimmed).map_err(|e| AppError::parse(APP_INDEX, e))?;
+        if index.version > INDEX_SCHEMA_VERSION {
+            return Err(AppError::UnsupportedSchema {
+                found: index.version,
+                supported: INDEX_SCHEMA_VERSION,
+            });
+        }
+        Ok(index)
+    }
+
+    /// Parses the legacy comma-joined index. Timestamps are unknown and the type is
+    /// taken from the legacy `<app>_type` key when present.
+    ///
+    /// Only the keys 0.2 itself skipped are left out. Names it accepted that are reserved
+    /// now (e.g. `work_history`) are still applications; see `migrate_index`.
+    fn parse_legacy(data: &str, store: &dyn SecretStore) -> Index {
+        let mut index = Index::default();
+        for name in data.split(',').map(str::trim) {
+            if name.is_empty()
+                || name == APP_INDEX
+                || name == PASSWORD_LENGTH_KEY
+                || name.ends_with(PASSWORD_TYPE_SUFFIX)
+                || name.ends_with(OTP_EXPIRY_SUFFIX)
+            {
+                continue;
+            }
+            let password_type = store
+                .get(&legacy_key(name, PASSWORD_TYPE_SUFFIX))
+                .unwrap_or_else(|_| "auto".to_string());
+            index.entries.insert(
+                name.to_string(),
+                IndexEntry {
+                    name: name.to_string(),
+                    created: 0,
+                    updated: 0,
+                    password_type,
+                },
+            );
+        }
+        index
+    }
+
+    /// Serializes the index to its stored JSON form.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| AppError::parse(APP_INDEX, e))
+    }
+
+    /// Returns the application names in the index, sorted.
+    pub fn names(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    /// Returns the entry for `app_name`, if present.
+    pub fn get(&self, app_name: &str) -> Option<&IndexEntry> {
+        self.entries.get(app_name)
+    }
+
+    /// Returns true if the index has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Records that the password of `app_name` was saved now, creating the entry if needed.
+    pub fn touch(&mut self, app_name: &str) {
+        let now = now();
+        self.entries
+            .entry(app_name.to_string())
+            .and_modify(|e| e.updated = now)
+            .or_insert_with(|| IndexEntry {
+                name: app_name.to_string(),
+                created: now,
+                updated: now,
+                password_type: "auto".to_string(),
+            });
+    }
+
+    /// Removes `app_name` from the index. Returns true if it was present.
+    pub fn remove(&mut self, app_name: &str) -> bool {
+        self.entries.remove(app_name).is_some()
+    }
+}
+
+/// Loads the index from the active store.
+///
+/// # Returns
+///
+/// * `Result<Index>` - The index (empty if none is stored), otherwise returns an `AppError`.
+pub fn load_index() -> Result<Index> {
+    match store().get(APP_INDEX) {
+        Ok(data) => Index::parse(&data),
+        Err(KeyringError::NoEntry) => Ok(Index::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes the index to the active store, deleting the record if it is empty.
+fn save_index(index: &Index) -> Result<()> {
+    if index.is_empty() {
+        return match store().delete(APP_INDEX) {
+            Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        };
+    }
+    Ok(store().set(APP_INDEX, &index.to_json()?)?)
+}
+
+/// Applies `f` to the stored index and writes the result back.
+///
+/// The whole cycle holds the store lock, so concurrent apppass processes creating or
+/// deleting entries never overwrite each other's index changes.
+///
+/// # Arguments
+///
+/// * `f` - Closure that modifies the index. The index is only written if it returns true.
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if the index is updated successfully, otherwise returns an `AppError`.
+pub fn update_index<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&mut Index) -> bool,
+{
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = store();
+    let _store_lock = store.lock()?;
+    let mut index = load_index()?;
+    if f(&mut index) {
+        save_index(&index)?;
+    }
+    Ok(())
+}
+
+/// Returns every entry in the index, sorted by name.
+///
+/// # Returns
+///
+/// * `Result<Vec<IndexEntry>>` - The entries (empty if none are stored), or an `AppError`
+///   if the index cannot be read.
+pub fn list_entries() -> Result<Vec<IndexEntry>> {
+    Ok(load_index()?.entries.into_values().collect())
+}
+
+/// Returns the current Unix timestamp in seconds.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_roundtrip() {
+        let mut index = Index::default();
+        index.touch("gmail");
+        index.touch("name,with,commas");
+
+        let parsed = Index::parse(&index.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, index);
+        assert_eq!(parsed.names(), vec!["gmail", "name,with,commas"]);
+    }
+
+    #[test]
+    fn test_parse_legacy_skips_metadata_keys() {
+        let index = Index::parse("github,password_length,github_type,otp_otp_expiry,,gmail").unwrap();
+        assert_eq!(index.names(), vec!["github", "gmail"]);
+        assert_eq!(index.get("gmail").unwrap().created, 0);
+    }
+
+    #[test]
+    fn test_parse_legacy_keeps_names_reserved_since() {
+        let index = Index::parse("work,work_history,notes_record").unwrap();
+        assert_eq!(index.names(), vec!["notes_record", "work", "work_history"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_newer_schema() {
+        let data = format!(r#"{{"version":{},"entries":{{}}}}"#, INDEX_SCHEMA_VERSION + 1);
+        assert!(matches!(Index::parse(&data), Err(AppError::UnsupportedSchema { .. })));
+        assert!(matches!(Index::parse("{\"version\":1"), Err(AppError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_corrupt_index_is_an_error_not_an_empty_list() {
+        let _store = crate::app::store::scoped_store();
+        store().set(APP_INDEX, "{\"version\":").unwrap();
+        assert!(matches!(list_entries(), Err(AppError::Parse { .. })));
+        assert!(matches!(update_index(|_| true), Err(AppError::Parse { .. })));
+        assert_eq!(store().get(APP_INDEX).unwrap(), "{\"version\":");
+    }
+
+    #[test]
+    fn test_index_of_many_entries_fits_a_keyring_sized_store() {
+        use crate::app::keyring::save_to_keyring;
+        use crate::app::store::{scoped_keyring_sized_store, KEYRING_MAX_VALUE_LEN};
+
+        let _store = scoped_keyring_sized_store();
+        for i in 0..100 {
+            save_to_keyring(&format!("test_index_chunked_{:03}", i), "secret").unwrap();
+        }
+        assert_eq!(list_entries().unwrap().len(), 100);
+        assert!(store().get(APP_INDEX).unwrap().len() > KEYRING_MAX_VALUE_LEN);
+    }
+
+    #[test]
+    fn test_touch_keeps_created_and_type() {
+        let mut index = Index::default();
+        index.touch("app");
+        index.entries.get_mut("app").unwrap().password_type = "custom".to_string();
+        index.entries.get_mut("app").unwrap().created = 1;
+
+        index.touch("app");
+        let entry = index.get("app").unwrap();
+        assert_eq!(entry.created, 1);
+        assert!(entry.updated >= entry.created);
+        assert_eq!(entry.password_type, "custom");
+    }
+}
@@ -0,0 +1,228 @@
+use crate::app::error::{AppError, Result};
+use crate::app::{APP_INDEX, DERIVED_SITES_KEY, ENTRY_RECORD_SUFFIX, MIGRATION_BACKUP_KEY, OTP_EXPIRY_SUFFIX, PASSWORD_HISTORY_SUFFIX,
+                 PASSWORD_LENGTH_KEY, PASSWORD_TYPE_SUFFIX, SCHEMA_VERSION_KEY, STORE_MANIFEST_KEY, TRASH_KEY,
+                 TRASH_RETENTION_KEY};
+
+/// Maximum length of an application name, in characters.
+pub const MAX_APP_NAME_LEN: usize = 128;
+
+/// Keys of the records apppass keeps next to the entries (index, trash, settings, schema,
+/// saved derivation parameters).
+pub const SETTING_KEYS: [&str; 8] = [
+    APP_INDEX,
+    PASSWORD_LENGTH_KEY,
+    TRASH_KEY,
+    TRASH_RETENTION_KEY,
+    STORE_MANIFEST_KEY,
+    SCHEMA_VERSION_KEY,
+    MIGRATION_BACKUP_KEY,
+    DERIVED_SITES_KEY,
+];
+
+/// Suffixes of the per-application metadata keys.
+pub const METADATA_SUFFIXES: [&str; 4] = [
+    PASSWORD_TYPE_SUFFIX,
+    OTP_EXPIRY_SUFFIX,
+    ENTRY_RECORD_SUFFIX,
+    PASSWORD_HISTORY_SUFFIX,
+];
+
+/// Line and paragraph separators that are not control characters. Together with the
+/// control characters (line breaks, tabs) they would split a name across lines in listings
+/// and in the legacy `app_name,password` export format.
+const SEPARATORS: [char; 2] = ['\u{2028}', '\u{2029}'];
+
+/// Prefix of escaped entry keys (see `entry_key`).
+const ESCAPE: char = '%';
+
+/// Checks that `name` can be used for an application.
+///
+/// # Arguments
+///
+/// * `name` - The application name.
+///
+/// # Returns
+///
+/// * `Result<()>` - `AppError::InvalidName` if the name is empty, has leading or trailing
+///   whitespace, is longer than `MAX_APP_NAME_LEN`, contains a line break, tab or other
+///   separator, is the key of a setting, or ends with a metadata suffix.
+pub fn validate_app_name(name: &str) -> Result<()> {
+    let invalid = |reason: String| {
+        Err(AppError::InvalidName {
+            name: name.to_string(),
+            reason,
+        })
+    };
+    if name.trim().is_empty() {
+        return invalid("the name cannot be empty".to_string());
+    }
+    if name.trim() != name {
+        return invalid("the name cannot start or end with whitespace".to_string());
+    }
+    if name.chars().count() > MAX_APP_NAME_LEN {
+        return invalid(format!("the name is longer than {} characters", MAX_APP_NAME_LEN));
+    }
+    if name.chars().any(|c| c.is_control() || SEPARATORS.contains(&c)) {
+        return invalid("the name cannot contain line breaks, tabs or other separators".to_string());
+    }
+    if SETTING_KEYS.contains(&name) {
+        return invalid("the name is reserved for apppass settings".to_string());
+    }
+    if let Some(suffix) = METADATA_SUFFIXES.iter().find(|s| name.ends_with(*s)) {
+        return invalid(format!("names ending in '{}' are reserved for entry metadata", suffix));
+    }
+    Ok(())
+}
+
+/// Returns true if `key` is an internal setting or metadata key rather than an application.
+///
+/// # Arguments
+///
+/// * `key` - The store key to check.
+///
+/// # Returns
+///
+/// * `bool` - Returns true for the index itself, the trash, settings and per-app metadata keys.
+pub fn is_reserved_key(key: &str) -> bool {
+    SETTING_KEYS.contains(&key) || METADATA_SUFFIXES.iter().any(|s| key.ends_with(s))
+}
+
+/// Returns the store key holding the password of `app_name`.
+///
+/// Valid names (see `validate_app_name`) are stored under their own name, as every
+/// release did. Any other name is escaped: `%` followed by the name with `%` and `_`
+/// percent-encoded. An escaped key contains no `_`, so it never ends with a metadata
+/// suffix, and it starts with `%`, which no setting or unescaped key does. User names
+/// therefore never collide with settings or metadata, whatever the caller passes.
+///
+/// # Arguments
+///
+/// * `app_name` - The application name.
+///
+/// # Returns
+///
+/// * `String` - The store key.
+pub fn entry_key(app_name: &str) -> String {
+    if !needs_escaping(app_name) {
+        return app_name.to_string();
+    }
+    let mut key = String::from(ESCAPE);
+    for c in app_name.chars() {
+        match c {
+            '%' => key.push_str("%25"),
+            '_' => key.push_str("%5F"),
+            c => key.push(c),
+        }
+    }
+    key
+}
+
+/// Returns the store key of the metadata of `app_name` with the given suffix
+/// (one of `METADATA_SUFFIXES`).
+pub fn metadata_key(app_name: &str, suffix: &str) -> String {
+    format!("{}{}", entry_key(app_name), suffix)
+}
+
+/// Returns the key under which 0.2 stored the metadata of `app_name` with the given suffix:
+/// the name itself followed by the suffix, whatever the name.
+pub fn legacy_key(app_name: &str, suffix: &str) -> String {
+    format!("{}{}", app_name, suffix)
+}
+
+/// Returns the application name stored under the entry key `key` (the inverse of `entry_key`).
+pub fn app_name_from_key(key: &str) -> String {
+    let Some(escaped) = key.strip_prefix(ESCAPE) else {
+        return key.to_string();
+    };
+    let mut name = String::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while let Some(c) = rest.chars().next() {
+        if c == '%' {
+            match rest.get(1..3) {
+                Some("25") => name.push('%'),
+                Some("5F") => name.push('_'),
+                _ => {
+                    name.push(c);
+                    rest = &rest[1..];
+                    continue;
+                }
+            }
+            rest = &rest[3..];
+        } else {
+            name.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    name
+}
+
+/// Splits a metadata key into the application name and the metadata suffix.
+///
+/// # Returns
+///
+/// * `Option<(String, &str)>` - The application name and suffix, or `None` if `key` is not a metadata key.
+pub fn split_metadata_key(key: &str) -> Option<(String, &'static str)> {
+    METADATA_SUFFIXES
+        .iter()
+        .find_map(|&suffix| key.strip_suffix(suffix).map(|stem| (app_name_from_key(stem), suffix)))
+}
+
+/// Returns true if `app_name` cannot be stored under its own name.
+fn needs_escaping(app_name: &str) -> bool {
+    app_name.is_empty() || app_name.starts_with(ESCAPE) || is_reserved_key(app_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_app_name() {
+        for name in ["gmail", "github_credential", "My Bank (joint)", "año-2025", "typewriter", "a,b"] {
+            assert!(validate_app_name(name).is_ok(), "{} should be valid", name);
+        }
+        let too_long = "a".repeat(MAX_APP_NAME_LEN + 1);
+        for name in ["", "   ", " gmail", "gmail\n", "tab\there", "line\u{2028}break", too_long.as_str(),
+                     PASSWORD_LENGTH_KEY, APP_INDEX, "foo_type", "bar_otp_expiry", "baz_record", "qux_history"] {
+            assert!(
+                matches!(validate_app_name(name), Err(AppError::InvalidName { .. })),
+                "{:?} should be rejected",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_valid_names_keep_their_key() {
+        assert_eq!(entry_key("gmail"), "gmail");
+        assert_eq!(entry_key("github_credential"), "github_credential");
+        assert_eq!(metadata_key("gmail", PASSWORD_HISTORY_SUFFIX), "gmail_history");
+    }
+
+    #[test]
+    fn test_reserved_names_never_collide() {
+        for name in ["password_length", "apppass_index", "foo_type", "foo", "%foo", "%25", "%5F_type", "", "a%5Fb"] {
+            let key = entry_key(name);
+            assert_eq!(app_name_from_key(&key), name, "round trip of {:?}", name);
+            assert!(!is_reserved_key(&key), "{:?} maps to reserved key {:?}", name, key);
+            for suffix in METADATA_SUFFIXES {
+                let meta = metadata_key(name, suffix);
+                assert_eq!(split_metadata_key(&meta), Some((name.to_string(), suffix)));
+            }
+        }
+        assert_ne!(entry_key("foo_type"), metadata_key("foo", PASSWORD_TYPE_SUFFIX));
+        assert_ne!(entry_key("%foo"), entry_key("foo"));
+    }
+
+    #[test]
+    fn test_is_reserved_key() {
+        assert!(is_reserved_key(APP_INDEX));
+        assert!(is_reserved_key(PASSWORD_LENGTH_KEY));
+        assert!(is_reserved_key(TRASH_KEY));
+        assert!(is_reserved_key("gmail_type"));
+        assert!(is_reserved_key("gmail_otp_expiry"));
+        assert!(is_reserved_key("gmail_record"));
+        assert!(is_reserved_key("gmail_history"));
+        assert!(!is_reserved_key("gmail"));
+    }
+}
@@ -7,7 +7,6 @@ use std::time::Duration;
 /// # Arguments
 ///
 /// * `timeout_seconds` - The number of seconds to wait before locking the application.
-#[allow(dead_code)]
 pub fn start_auto_lock(timeout_seconds: u64) {
     let is_active = Arc::new(Mutex::new(true));
     let is_active_clone = Arc::clone(&is_active);
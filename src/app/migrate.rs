@@ -0,0 +1,557 @@
+use keyring::Error as KeyringError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use crate::app::config::{Config, PASSWORD_LENGTH_RANGE};
+use crate::app::error::{AppError, Result};
+use crate::app::index::Index;
+use crate::app::keys::{entry_key, legacy_key, metadata_key, METADATA_SUFFIXES, SETTING_KEYS};
+use crate::app::store::SecretStore;
+use crate::app::{APP_INDEX, MIGRATION_BACKUP_KEY, OTP_EXPIRY_SUFFIX, PASSWORD_LENGTH_KEY, PASSWORD_TYPE_SUFFIX,
+                 SCHEMA_VERSION_KEY, TRASH_RETENTION_KEY};
+
+/// A step that upgrades a store by one schema version.
+struct Migration {
+    /// What the step changes, shown when it runs.
+    description: &'static str,
+    /// Applies the step to the store of `profile`. `config_path` is the configuration file
+    /// settings are moved to. Steps must be safe to run again on data they already upgraded.
+    apply: fn(store: &dyn SecretStore, config_path: &Path, profile: &str) -> Result<()>,
+}
+
+/// Every migration, in order: `MIGRATIONS[n]` upgrades a store from version `n` to `n + 1`.
+///
+/// Version 0 is the layout of 0.2 and earlier: a comma-joined index, password types in
+/// `<app>_type` keys (missing meaning "auto") and the default length stored as a secret.
+const MIGRATIONS: [Migration; 3] = [
+    Migration {
+        description: "Convert the index to JSON and move password types into it",
+        apply: migrate_index,
+    },
+    Migration {
+        description: "Move the default password length to the configuration file",
+        apply: migrate_password_length,
+    },
+    Migration {
+        description: "Move the trash retention to the configuration file",
+        apply: migrate_trash_retention,
+    },
+];
+
+/// Schema version of the stores written by this build.
+pub const STORE_SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Copy of every key a migration may change, saved under `MIGRATION_BACKUP_KEY` before
+/// the first step runs and deleted once the schema version is written.
+///
+/// A backup left behind means a migration was interrupted; it is restored before the
+/// migration is tried again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct MigrationBackup {
+    /// Schema version of the backed up data.
+    from_version: u32,
+    /// The backed up keys and their values.
+    entries: BTreeMap<String, String>,
+}
+
+/// The migrations applied by `migrate_store`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Schema version of the store before the migration.
+    pub from_version: u32,
+    /// Schema version of the store after the migration.
+    pub to_version: u32,
+    /// Description of each step that ran, in order.
+    pub applied: Vec<&'static str>,
+}
+
+/// Returns the schema version of `store`.
+///
+/// Stores without a version record are version 0 if they hold any data and are
+/// considered current if they are empty.
+///
+/// # Returns
+///
+/// * `Result<u32>` - The schema version, `AppError::Parse` if the version record is
+///   malformed, or `AppError::Backend` if the store cannot be read.
+pub fn schema_version(store: &dyn SecretStore) -> Result<u32> {
+    match store.get(SCHEMA_VERSION_KEY) {
+        Ok(value) => value.trim().parse().map_err(|e| AppError::parse(SCHEMA_VERSION_KEY, e)),
+        Err(KeyringError::NoEntry) if snapshot(store)?.is_empty() => Ok(STORE_SCHEMA_VERSION),
+        Err(KeyringError::NoEntry) => Ok(0),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Upgrades `store` to `STORE_SCHEMA_VERSION`.
+///
+/// # Arguments
+///
+/// * `store` - The store to migrate.
+/// * `config_path` - The configuration file that settings formerly kept in the store move to.
+/// * `profile` - The profile owning the store; its settings move to its `[profiles.<name>]` section.
+///
+/// # Returns
+///
+/// * `Result<Option<MigrationReport>>` - The migrations applied, `None` if the store was
+///   already current, or `AppError::UnsupportedSchema` if it was written by a newer apppass.
+///
+/// # Behavior
+///
+/// The store lock is held throughout. Every key the migrations may touch is backed up
+/// under `MIGRATION_BACKUP_KEY` before the first step; if a step fails, the store is
+/// restored from the backup and the error is returned. The schema version is written
+/// last, so an interrupted migration is detected, rolled back and run again on the next start.
+pub fn migrate_store(store: &dyn SecretStore, config_path: &Path, profile: &str) -> Result<Option<MigrationReport>> {
+    let _lock = store.lock()?;
+    let has_version = match store.get(SCHEMA_VERSION_KEY) {
+        Ok(_) => true,
+        Err(KeyringError::NoEntry) => false,
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut version = schema_version(store)?;
+    if version > STORE_SCHEMA_VERSION {
+        return Err(AppError::UnsupportedSchema {
+            found: version,
+            supported: STORE_SCHEMA_VERSION,
+        });
+    }
+    if let Some(backup) = load_backup(store)? {
+        if backup.from_version == version {
+            restore(store, &backup.entries)?;
+            version = schema_version(store)?;
+        }
+        delete_key(store, MIGRATION_BACKUP_KEY)?;
+    }
+    if version == STORE_SCHEMA_VERSION {
+        if !has_version {
+            // New (empty) store: record the version so later data is never taken for legacy data
+            store.set(SCHEMA_VERSION_KEY, &STORE_SCHEMA_VERSION.to_string())?;
+        }
+        return Ok(None);
+    }
+
+    let backup = MigrationBackup {
+        from_version: version,
+        entries: snapshot(store)?,
+    };
+    let data = serde_json::to_string(&backup).map_err(|e| AppError::parse(MIGRATION_BACKUP_KEY, e))?;
+    store.set(MIGRATION_BACKUP_KEY, &data)?;
+
+    let mut applied = Vec::new();
+    for migration in &MIGRATIONS[version as usize..] {
+        if let Err(e) = (migration.apply)(store, config_path, profile) {
+            restore(store, &backup.entries)?;
+            delete_key(store, MIGRATION_BACKUP_KEY)?;
+            return Err(e);
+        }
+        applied.push(migration.description);
+    }
+    store.set(SCHEMA_VERSION_KEY, &STORE_SCHEMA_VERSION.to_string())?;
+    delete_key(store, MIGRATION_BACKUP_KEY)?;
+
+    Ok(Some(MigrationReport {
+        from_version: version,
+        to_version: STORE_SCHEMA_VERSION,
+        applied,
+    }))
+}
+
+/// Migration 0 -> 1: rewrites a comma-joined index as the JSON index, taking each type
+/// from its `<app>_type` key ("auto" if missing), then deletes the `_type` keys.
+///
+/// Every key of an indexed application is also written back, which records keys saved
+/// before the keyring backend kept a manifest.
+///
+/// 0.2 stored every password under its own name, so a comma-joined index may list names
+/// that are escaped now (see `entry_key`), such as `work_history`, whose raw key reads as
+/// the history of `work`. Their password and OTP expiry are moved to the escaped keys
+/// before anything else, so no key is taken for metadata of another entry.
+fn migrate_index(store: &dyn SecretStore, _config_path: &Path, _profile: &str) -> Result<()> {
+    let (index, legacy) = match store.get(APP_INDEX) {
+        Ok(data) => (Index::parse_in(&data, store)?, !data.trim().starts_with('{')),
+        Err(KeyringError::NoEntry) => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    if legacy {
+        for name in index.names().into_iter().filter(|name| entry_key(name) != *name) {
+            move_key(store, &name, &entry_key(&name))?;
+            move_key(store, &legacy_key(&name, OTP_EXPIRY_SUFFIX), &metadata_key(&name, OTP_EXPIRY_SUFFIX))?;
+        }
+    }
+
+    for name in index.names() {
+        for key in entry_keys(&name) {
+            if let Some(value) = read_key(store, &key)? {
+                store.set(&key, &value)?;
+            }
+        }
+        delete_key(store, &metadata_key(&name, PASSWORD_TYPE_SUFFIX))?;
+        if legacy {
+            delete_key(store, &legacy_key(&name, PASSWORD_TYPE_SUFFIX))?;
+        }
+    }
+
+    if index.is_empty() {
+        delete_key(store, APP_INDEX)
+    } else {
+        Ok(store.set(APP_INDEX, &index.to_json()?)?)
+    }
+}
+
+/// Migration 1 -> 2: moves the default password length saved by the TUI from the store
+/// to `password.length` in the section of the profile in the configuration file, unless the
+/// section already sets it.
+fn migrate_password_length(store: &dyn SecretStore, config_path: &Path, profile: &str) -> Result<()> {
+    let Some(value) = read_key(store, PASSWORD_LENGTH_KEY)? else {
+        return Ok(());
+    };
+    let length = value.trim().parse::<usize>().ok().filter(|l| PASSWORD_LENGTH_RANGE.contains(l));
+    if let Some(length) = length {
+        move_to_config(config_path, profile, "password.length", &length.to_string())?;
+    }
+    delete_key(store, PASSWORD_LENGTH_KEY)
+}
+
+/// Migration 2 -> 3: moves the trash retention set with `trash retention` from the store
+/// to `trash.retention_days` in the section of the profile in the configuration file, unless
+/// the section already sets it.
+fn migrate_trash_retention(store: &dyn SecretStore, config_path: &Path, profile: &str) -> Result<()> {
+    let Some(value) = read_key(store, TRASH_RETENTION_KEY)? else {
+        return Ok(());
+    };
+    if let Ok(days) = value.trim().parse::<u64>() {
+        move_to_config(config_path, profile, "trash.retention_days", &days.to_string())?;
+    }
+    delete_key(store, TRASH_RETENTION_KEY)
+}
+
+/// Sets `key` (a `section.name` key of `Config::KEYS`) to `value` in the `[profiles.<profile>]`
+/// section of the configuration file at `path`, unless the section already sets it.
+///
+/// Each profile kept these settings in its own store, so each keeps its value in its own
+/// section, whatever the top level or other profiles set. Only that key is added, so the
+/// keys a later step moves are still unset in the section.
+fn move_to_config(path: &Path, profile: &str, key: &str, value: &str) -> Result<()> {
+    let context = || path.display().to_string();
+    let mut file: toml::Table = match fs::read_to_string(path) {
+        Ok(data) => data.parse().map_err(|e| AppError::parse(context(), e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml::Table::new(),
+        Err(e) => return Err(AppError::io(path, e)),
+    };
+    let (section, name) = key.split_once('.').expect("configuration keys moved by migrations have a section");
+    let table_path = ["profiles", profile, section];
+    let current = table_path.iter().try_fold(&file, |table, key| table.get(*key)?.as_table());
+    if current.is_some_and(|table| table.contains_key(name)) {
+        return Ok(());
+    }
+
+    // Parse and check the value through `Config`, then copy it in its TOML form
+    let mut config = Config::load(path)?.for_profile(profile)?;
+    config.set(key, value)?;
+    let parsed = toml::Table::try_from(&config).map_err(|e| AppError::parse(context(), e))?;
+    let Some(value) = parsed.get(section).and_then(|s| s.get(name)).cloned() else {
+        return Ok(());
+    };
+    let mut table = &mut file;
+    for key in table_path {
+        match table
+            .entry(key)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        {
+            toml::Value::Table(nested) => table = nested,
+            _ => return Err(AppError::parse(context(), format!("'{}' is not a table", key))),
+        }
+    }
+    table.insert(name.to_string(), value);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+    }
+    let data = toml::to_string_pretty(&file).map_err(|e| AppError::parse(context(), e))?;
+    fs::write(path, data).map_err(|e| AppError::io(path, e))
+}
+
+/// Moves the value of `from` to `to`, keeping the value already at `to` if there is one.
+fn move_key(store: &dyn SecretStore, from: &str, to: &str) -> Result<()> {
+    if let Some(value) = read_key(store, from)? {
+        if read_key(store, to)?.is_none() {
+            store.set(to, &value)?;
+        }
+        delete_key(store, from)?;
+    }
+    Ok(())
+}
+
+/// Returns the store key of the password of `name` and every metadata key it may have.
+fn entry_keys(name: &str) -> Vec<String> {
+    std::iter::once(entry_key(name))
+        .chain(METADATA_SUFFIXES.iter().map(|suffix| metadata_key(name, suffix)))
+        .collect()
+}
+
+/// Reads every key a migration may change: the keys the store lists, the settings, and
+/// the keys of every application in the index (0.2 wrote keyring entries that are not
+/// in the manifest, so listing alone would miss them).
+fn snapshot(store: &dyn SecretStore) -> Result<BTreeMap<String, String>> {
+    let mut keys: BTreeSet<String> = store.list()?.into_iter().collect();
+    keys.extend(SETTING_KEYS.iter().map(|key| key.to_string()));
+    keys.remove(MIGRATION_BACKUP_KEY);
+    if let Some(data) = read_key(store, APP_INDEX)? {
+        if let Ok(index) = Index::parse_in(&data, store) {
+            for name in index.names() {
+                keys.extend([PASSWORD_TYPE_SUFFIX, OTP_EXPIRY_SUFFIX].map(|suffix| legacy_key(&name, suffix)));
+                keys.insert(name.clone());
+                keys.extend(entry_keys(&name));
+            }
+        }
+    }
+
+    let mut entries = BTreeMap::new();
+    for key in keys {
+        if let Some(value) = read_key(store, &key)? {
+            entries.insert(key, value);
+        }
+    }
+    Ok(entries)
+}
+
+/// Puts `store` back in the state captured by `snapshot`.
+fn restore(store: &dyn SecretStore, entries: &BTreeMap<String, String>) -> Result<()> {
+    for key in store.list()? {
+        if key != MIGRATION_BACKUP_KEY && !entries.contains_key(&key) {
+            delete_key(store, &key)?;
+        }
+    }
+    for (key, value) in entries {
+        store.set(key, value)?;
+    }
+    Ok(())
+}
+
+/// Reads the backup left by an interrupted migration, if any.
+fn load_backup(store: &dyn SecretStore) -> Result<Option<MigrationBackup>> {
+    match read_key(store, MIGRATION_BACKUP_KEY)? {
+        Some(data) => serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| AppError::parse(MIGRATION_BACKUP_KEY, e)),
+        None => Ok(None),
+    }
+}
+
+/// Reads `key`, returning `None` if it does not exist.
+fn read_key(store: &dyn SecretStore, key: &str) -> Result<Option<String>> {
+    match store.get(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(KeyringError::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Deletes `key`, treating a missing key as deleted.
+fn delete_key(store: &dyn SecretStore, key: &str) -> Result<()> {
+    match store.delete(key) {
+        Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::DEFAULT_PASSWORD_LENGTH;
+    use crate::app::profile::DEFAULT_PROFILE;
+    use crate::app::store::MemoryStore;
+    use std::path::PathBuf;
+
+    /// A store as left by apppass 0.2.
+    fn legacy_store() -> MemoryStore {
+        let store = MemoryStore::new();
+        store.set(APP_INDEX, "github,gmail,%bank").unwrap();
+        store.set("github", "auto-pw").unwrap();
+        store.set("gmail", "custom-pw").unwrap();
+        store.set("gmail_type", "custom").unwrap();
+        store.set("%bank", "bank-pw").unwrap();
+        store.set(PASSWORD_LENGTH_KEY, "42").unwrap();
+        store.set(TRASH_RETENTION_KEY, "7").unwrap();
+        store
+    }
+
+    fn config_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("apppass_migrate_{}_{}.toml", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_migrates_legacy_store() {
+        let store = legacy_store();
+        let config_path = config_file("legacy");
+        assert_eq!(schema_version(&store).unwrap(), 0);
+
+        let report = migrate_store(&store, &config_path, DEFAULT_PROFILE).unwrap().unwrap();
+        assert_eq!((report.from_version, report.to_version), (0, STORE_SCHEMA_VERSION));
+        assert_eq!(report.applied.len(), MIGRATIONS.len());
+        assert_eq!(schema_version(&store).unwrap(), STORE_SCHEMA_VERSION);
+
+        let index = Index::parse_in(&store.get(APP_INDEX).unwrap(), &store).unwrap();
+        assert_eq!(index.names(), vec!["%bank", "github", "gmail"]);
+        assert_eq!(index.get("github").unwrap().password_type, "auto");
+        assert_eq!(index.get("gmail").unwrap().password_type, "custom");
+        assert_eq!(store.get(&entry_key("%bank")).unwrap(), "bank-pw");
+        for key in ["gmail_type", "%bank", PASSWORD_LENGTH_KEY, TRASH_RETENTION_KEY, MIGRATION_BACKUP_KEY] {
+            assert!(matches!(store.get(key), Err(KeyringError::NoEntry)), "{} should be gone", key);
+        }
+        let config = Config::load(&config_path).unwrap();
+        assert_eq!(config.password.length, DEFAULT_PASSWORD_LENGTH);
+        let config = config.for_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(config.password.length, 42);
+        assert_eq!(config.trash.retention_days, 7);
+
+        // Running again changes nothing
+        let before = store.list().unwrap();
+        assert_eq!(migrate_store(&store, &config_path, DEFAULT_PROFILE).unwrap(), None);
+        assert_eq!(store.list().unwrap(), before);
+        let _ = fs::remove_file(&config_path);
+    }
+
+    #[test]
+    fn test_migrates_legacy_names_with_reserved_suffixes() {
+        use crate::app::history::get_history;
+        use crate::app::index::list_entries;
+        use crate::app::keyring::{get_from_keyring, get_password_type};
+        use crate::app::password::update_password;
+        use crate::app::store::scope_store;
+        use std::sync::Arc;
+
+        // 0.2 accepted `x_history` as a name, next to `x`
+        let store = Arc::new(MemoryStore::new());
+        store.set(APP_INDEX, "x,x_history").unwrap();
+        store.set("x", "x-pw").unwrap();
+        store.set("x_history", "x-history-pw").unwrap();
+        store.set("x_history_type", "custom").unwrap();
+        let config_path = config_file("suffixes");
+        migrate_store(&*store, &config_path, DEFAULT_PROFILE).unwrap().unwrap();
+
+        let _store = scope_store(store.clone());
+        let names: Vec<String> = list_entries().unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["x", "x_history"]);
+        assert_eq!(get_from_keyring("x_history").unwrap(), "x-history-pw");
+        assert_eq!(get_password_type("x_history").as_deref(), Some("custom"));
+        assert!(get_history("x").unwrap().is_empty());
+        assert!(matches!(store.get("x_history_type"), Err(KeyringError::NoEntry)));
+
+        update_password("x", "x-pw-rotated").unwrap();
+        assert_eq!(get_history("x").unwrap()[0].password, "x-pw");
+        assert_eq!(get_from_keyring("x_history").unwrap(), "x-history-pw");
+        let _ = fs::remove_file(&config_path);
+    }
+
+    #[test]
+    fn test_each_profile_keeps_its_own_settings() {
+        let config_path = config_file("profiles");
+        let work = MemoryStore::new();
+        work.set(APP_INDEX, "vpn").unwrap();
+        work.set("vpn", "vpn-pw").unwrap();
+        work.set(PASSWORD_LENGTH_KEY, "64").unwrap();
+        migrate_store(&legacy_store(), &config_path, DEFAULT_PROFILE).unwrap().unwrap();
+        migrate_store(&work, &config_path, "work").unwrap().unwrap();
+
+        let config = Config::load(&config_path).unwrap();
+        let default = config.for_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!((default.password.length, default.trash.retention_days), (42, 7));
+        let work = config.for_profile("work").unwrap();
+        assert_eq!(work.password.length, 64);
+        assert_eq!(work.trash.retention_days, Config::default().trash.retention_days);
+        // Profiles without a section keep the top-level settings
+        assert_eq!(config.for_profile("personal").unwrap().password.length, DEFAULT_PASSWORD_LENGTH);
+        let _ = fs::remove_file(&config_path);
+    }
+
+    #[test]
+    fn test_migrates_a_large_legacy_store_within_keyring_limits() {
+        use crate::app::store::{ChunkedStore, LimitedStore, KEYRING_MAX_VALUE_LEN};
+
+        // The backup holds every secret and outgrows a single keyring credential
+        let store = ChunkedStore::new(LimitedStore::new(KEYRING_MAX_VALUE_LEN), KEYRING_MAX_VALUE_LEN);
+        let names: Vec<String> = (0..100).map(|i| format!("legacy-site-number-{:03}", i)).collect();
+        store.set(APP_INDEX, &names.join(",")).unwrap();
+        for name in &names {
+            store.set(name, "legacy-password-of-some-length").unwrap();
+        }
+        let config_path = config_file("large");
+
+        migrate_store(&store, &config_path, DEFAULT_PROFILE).unwrap().unwrap();
+        let index = Index::parse_in(&store.get(APP_INDEX).unwrap(), &store).unwrap();
+        assert_eq!(index.names().len(), names.len());
+        assert_eq!(store.get(&names[99]).unwrap(), "legacy-password-of-some-length");
+        assert!(matches!(store.get(MIGRATION_BACKUP_KEY), Err(KeyringError::NoEntry)));
+        let _ = fs::remove_file(&config_path);
+    }
+
+    #[test]
+    fn test_new_store_is_stamped_without_migrating() {
+        let store = MemoryStore::new();
+        assert_eq!(migrate_store(&store, &config_file("new"), DEFAULT_PROFILE).unwrap(), None);
+        assert_eq!(store.get(SCHEMA_VERSION_KEY).unwrap(), STORE_SCHEMA_VERSION.to_string());
+    }
+
+    #[test]
+    fn test_refuses_newer_schema() {
+        let store = legacy_store();
+        store.set(SCHEMA_VERSION_KEY, &(STORE_SCHEMA_VERSION + 1).to_string()).unwrap();
+        let before = store.list().unwrap();
+        assert!(matches!(
+            migrate_store(&store, &config_file("newer"), DEFAULT_PROFILE),
+            Err(AppError::UnsupportedSchema { .. })
+        ));
+        assert_eq!(store.list().unwrap(), before);
+    }
+
+    #[test]
+    fn test_failed_migration_is_rolled_back() {
+        let store = legacy_store();
+        // A config file that cannot be parsed makes the second step fail
+        let config_path = config_file("broken");
+        fs::write(&config_path, "[password\n").unwrap();
+        let before: Vec<(String, String)> = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|key| (key.clone(), store.get(&key).unwrap()))
+            .collect();
+
+        assert!(matches!(migrate_store(&store, &config_path, DEFAULT_PROFILE), Err(AppError::Parse { .. })));
+        let after: Vec<(String, String)> = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|key| (key.clone(), store.get(&key).unwrap()))
+            .collect();
+        assert_eq!(after, before);
+        let _ = fs::remove_file(&config_path);
+    }
+
+    #[test]
+    fn test_interrupted_migration_is_restored_and_retried() {
+        let store = legacy_store();
+        let backup = MigrationBackup {
+            from_version: 0,
+            entries: snapshot(&store).unwrap(),
+        };
+        store.set(MIGRATION_BACKUP_KEY, &serde_json::to_string(&backup).unwrap()).unwrap();
+        // Half-done: the index was converted but the legacy keys were not cleaned up yet
+        store.set(APP_INDEX, "{\"version\":1,\"entries\":{}}").unwrap();
+
+        let config_path = config_file("interrupted");
+        let report = migrate_store(&store, &config_path, DEFAULT_PROFILE).unwrap().unwrap();
+        assert_eq!(report.from_version, 0);
+        let index = Index::parse_in(&store.get(APP_INDEX).unwrap(), &store).unwrap();
+        assert_eq!(index.names(), vec!["%bank", "github", "gmail"]);
+        assert!(matches!(store.get(MIGRATION_BACKUP_KEY), Err(KeyringError::NoEntry)));
+        let _ = fs::remove_file(&config_path);
+    }
+}
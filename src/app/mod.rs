@@ -1,10 +1,48 @@
+pub mod config;
+pub mod error;
 pub mod keyring;
+pub mod index;
+pub mod keys;
+pub mod entry;
+pub mod history;
 pub mod password;
+pub mod passphrase;
+pub mod policy;
+pub mod strength;
+pub mod breach;
+pub mod template;
+pub mod pin;
+pub mod rng;
+pub mod derive;
 pub mod otp;
 pub mod lock;
+pub mod filelock;
+pub mod store;
+pub mod vault;
+pub mod profile;
+pub mod doctor;
+pub mod audit;
+pub mod clipboard;
+pub mod trash;
+pub mod search;
+pub mod migrate;
 
 pub static APP_INDEX: &str = "apppass_index";
 pub static APP_SERVICE: &str = "apppass";
 pub static PASSWORD_LENGTH_KEY: &str = "password_length";
 pub static PASSWORD_TYPE_SUFFIX: &str = "_type";
 pub static OTP_EXPIRY_SUFFIX: &str = "_otp_expiry";
+pub static ENTRY_RECORD_SUFFIX: &str = "_record";
+pub static PASSWORD_HISTORY_SUFFIX: &str = "_history";
+pub static TRASH_KEY: &str = "apppass_trash";
+pub static TRASH_RETENTION_KEY: &str = "trash_retention_days";
+pub static STORE_MANIFEST_KEY: &str = "apppass_keys";
+pub static SCHEMA_VERSION_KEY: &str = "apppass_schema_version";
+pub static MIGRATION_BACKUP_KEY: &str = "apppass_migration_backup";
+pub static DERIVED_SITES_KEY: &str = "apppass_derived";
+pub static BACKEND_ENV_VAR: &str = "APPPASS_BACKEND";
+pub static VAULT_PATH_ENV_VAR: &str = "APPPASS_VAULT";
+pub static MASTER_PASSWORD_ENV_VAR: &str = "APPPASS_MASTER_PASSWORD";
+pub static PROFILE_ENV_VAR: &str = "APPPASS_PROFILE";
+pub static CONFIG_DIR_ENV_VAR: &str = "APPPASS_CONFIG_DIR";
+pub static DERIVE_SECRET_ENV_VAR: &str = "APPPASS_DERIVE_SECRET";
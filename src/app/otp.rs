@@ -1,12 +1,18 @@
-#![allow(dead_code)]
-
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::thread;
-use rand::distributions::Alphanumeric;
-use rand::{thread_rng, Rng};
-use keyring::Entry;
+use crate::app::error::{AppError, Result};
 use crate::app::keyring::{save_to_keyring, delete_from_keyring, set_password_type};
-use crate::app::{APP_SERVICE, APP_INDEX, OTP_EXPIRY_SUFFIX, PASSWORD_TYPE_SUFFIX, PASSWORD_LENGTH_KEY};
+use crate::app::store::store;
+use crate::app::index::list_entries;
+use crate::app::password::generate_with_policy;
+use crate::app::policy::PasswordPolicy;
+use crate::app::keys::metadata_key;
+use crate::app::OTP_EXPIRY_SUFFIX;
+
+/// Returns the key under which the expiry timestamp of an OTP is stored.
+fn expiry_key(app_name: &str) -> String {
+    metadata_key(app_name, OTP_EXPIRY_SUFFIX)
+}
 
 /// Saves the expiry timestamp for an OTP.
 ///
@@ -14,14 +20,8 @@ use crate::app::{APP_SERVICE, APP_INDEX, OTP_EXPIRY_SUFFIX, PASSWORD_TYPE_SUFFIX
 ///
 /// * `app_name` - The name of the application.
 /// * `expiry_timestamp` - Unix timestamp when the OTP expires.
-fn save_otp_expiry(app_name: &str, expiry_timestamp: u64) -> Result<(), String> {
-    let expiry_key = format!("{}{}", app_name, OTP_EXPIRY_SUFFIX);
-    if let Ok(entry) = Entry::new(APP_SERVICE, &expiry_key) {
-        entry.set_password(&expiry_timestamp.to_string())
-            .map_err(|e| format!("Failed to save OTP expiry: {}", e))
-    } else {
-        Err("Failed to create keyring entry for OTP expiry".to_string())
-    }
+fn save_otp_expiry(app_name: &str, expiry_timestamp: u64) -> Result<()> {
+    Ok(store().set(&expiry_key(app_name), &expiry_timestamp.to_string())?)
 }
 
 /// Gets the expiry timestamp for an OTP.
@@ -33,14 +33,10 @@ fn save_otp_expiry(app_name: &str, expiry_timestamp: u64) -> Result<(), String>
 /// # Returns
 ///
 /// * `Option<u64>` - The Unix timestamp when the OTP expires, or None if not set.
-fn get_otp_expiry(app_name: &str) -> Option<u64> {
-    let expiry_key = format!("{}{}", app_name, OTP_EXPIRY_SUFFIX);
-    if let Ok(entry) = Entry::new(APP_SERVICE, &expiry_key) {
-        if let Ok(value) = entry.get_password() {
-            return value.parse::<u64>().ok();
-        }
-    }
-    None
+pub fn get_otp_expiry(app_name: &str) -> Option<u64> {
+    store().get(&expiry_key(app_name))
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
 }
 
 /// Deletes the expiry timestamp for an OTP.
@@ -49,10 +45,7 @@ fn get_otp_expiry(app_name: &str) -> Option<u64> {
 ///
 /// * `app_name` - The name of the application.
 fn delete_otp_expiry(app_name: &str) {
-    let expiry_key = format!("{}{}", app_name, OTP_EXPIRY_SUFFIX);
-    if let Ok(entry) = Entry::new(APP_SERVICE, &expiry_key) {
-        let _ = entry.delete_credential();
-    }
+    let _ = store().delete(&expiry_key(app_name));
 }
 
 /// Deletes an OTP and its associated metadata.
@@ -60,9 +53,9 @@ fn delete_otp_expiry(app_name: &str) {
 /// # Arguments
 ///
 /// * `app_name` - The name of the application.
-pub fn delete_otp(app_name: &str) -> Result<(), String> {
+pub fn delete_otp(app_name: &str) -> Result<()> {
     // Delete the OTP itself
-    delete_from_keyring(app_name).map_err(|e| format!("Failed to delete OTP: {}", e))?;
+    delete_from_keyring(app_name)?;
     // Delete the expiry metadata
     delete_otp_expiry(app_name);
     Ok(())
@@ -92,35 +85,25 @@ pub fn is_otp_expired(app_name: &str) -> bool {
 /// Cleans up all expired OTPs from the keyring.
 /// Should be called at application startup.
 pub fn cleanup_expired_otps() {
-    if let Ok(entry) = Entry::new(APP_SERVICE, APP_INDEX) {
-        if let Ok(data) = entry.get_password() {
-            let app_names: Vec<String> = data.split(',')
-                .filter(|s| !s.is_empty())
-                .map(String::from)
-                .collect();
+    let entries = match list_entries() {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Warning: Failed to check for expired OTPs: {}", e);
+            return;
+        }
+    };
+    for entry in entries {
+        // Check if this is an OTP with expiry
+        if let Some(expiry) = get_otp_expiry(&entry.name) {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::from_secs(0))
+                .as_secs();
             
-            for app_name in app_names {
-                // Skip metadata entries
-                if app_name == PASSWORD_LENGTH_KEY 
-                    || app_name.ends_with(PASSWORD_TYPE_SUFFIX)
-                    || app_name.ends_with(OTP_EXPIRY_SUFFIX)
-                    || app_name == APP_INDEX {
-                    continue;
-                }
-                
-                // Check if this is an OTP with expiry
-                if let Some(expiry) = get_otp_expiry(&app_name) {
-                    let now = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap_or(Duration::from_secs(0))
-                        .as_secs();
-                    
-                    if now >= expiry {
-                        // OTP has expired, delete it
-                        if let Err(e) = delete_otp(&app_name) {
-                            eprintln!("Warning: Failed to cleanup expired OTP '{}': {}", app_name, e);
-                        }
-                    }
+            if now >= expiry {
+                // OTP has expired, delete it
+                if let Err(e) = delete_otp(&entry.name) {
+                    eprintln!("Warning: Failed to cleanup expired OTP '{}': {}", entry.name, e);
                 }
             }
         }
@@ -134,90 +117,58 @@ pub fn cleanup_expired_otps() {
 /// * `app_name` - A string slice that holds the name of the application for the OTP.
 /// * `ttl_seconds` - The time-to-live for the OTP in seconds.
 /// * `length` - The length of the OTP to generate.
+/// * `policy` - An optional password policy (defaults to the configured one).
 ///
 /// # Returns
 ///
-/// * `Result<String, String>` - Returns the generated OTP on success, or an error message on failure.
+/// * `Result<String>` - Returns the generated OTP on success, otherwise returns an `AppError`.
 ///
 /// # Behavior
 ///
 /// The OTP is saved to the system keyring along with its expiry timestamp. A background thread
 /// is spawned to delete it after the TTL expires. If the program exits before the TTL expires,
 /// the OTP will be cleaned up on next startup via `cleanup_expired_otps()`.
-pub fn generate_otp(app_name: &str, ttl_seconds: u64, length: usize) -> Result<String, String> {
-    let otp: String = thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(length)
-        .map(char::from)
-        .collect();
+pub fn generate_otp(app_name: &str, ttl_seconds: u64, length: usize, policy: Option<&PasswordPolicy>) -> Result<String> {
+    let otp = generate_with_policy(Some(length), policy)?;
 
     // Calculate expiry timestamp
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .map_err(|e| format!("Failed to get current time: {}", e))?
+        .map_err(|e| AppError::InvalidInput(format!("Failed to get current time: {}", e)))?
         .as_secs();
     let expiry_timestamp = now + ttl_seconds;
 
     // Save OTP to keyring
-    match save_to_keyring(app_name, &otp) {
-        Ok(_) => {
-            // Mark as auto-generated (OTP type)
-            if let Err(e) = set_password_type(app_name, "auto") {
-                eprintln!("Warning: Failed to set password type for OTP: {}", e);
-            }
-            
-            // Save expiry timestamp
-            if let Err(e) = save_otp_expiry(app_name, expiry_timestamp) {
-                eprintln!("Warning: Failed to save OTP expiry: {}", e);
-            }
-            
-            // Spawn a background thread to delete the OTP after TTL expires
-            let app_name_owned = app_name.to_string();
-            thread::spawn(move || {
-                thread::sleep(Duration::from_secs(ttl_seconds));
-                // Attempt to delete the OTP from keyring
-                if let Err(e) = delete_otp(&app_name_owned) {
-                    eprintln!("Warning: Failed to auto-delete OTP for '{}': {}", app_name_owned, e);
-                }
-            });
+    save_to_keyring(app_name, &otp)?;
 
-            Ok(otp)
-        }
-        Err(e) => Err(format!("Failed to save OTP to keyring: {}", e)),
+    // Mark as auto-generated (OTP type)
+    if let Err(e) = set_password_type(app_name, "auto") {
+        eprintln!("Warning: Failed to set password type for OTP: {}", e);
     }
+    
+    // Save expiry timestamp
+    if let Err(e) = save_otp_expiry(app_name, expiry_timestamp) {
+        eprintln!("Warning: Failed to save OTP expiry: {}", e);
+    }
+    
+    // Spawn a background thread to delete the OTP after TTL expires
+    let app_name_owned = app_name.to_string();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_secs(ttl_seconds));
+        // Attempt to delete the OTP from keyring
+        if let Err(e) = delete_otp(&app_name_owned) {
+            eprintln!("Warning: Failed to auto-delete OTP for '{}': {}", app_name_owned, e);
+        }
+    });
+
+    Ok(otp)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::app::keyring::get_from_keyring;
-    use keyring::Entry;
 
-    /// Check if keyring service is available (returns false on headless CI systems)
-    fn is_keyring_available() -> bool {
-        let test_entry = Entry::new("apppass_test_probe", "keyring_availability_check");
-        match test_entry {
-            Ok(entry) => {
-                match entry.set_password("test") {
-                    Ok(_) => {
-                        let _ = entry.delete_credential();
-                        true
-                    }
-                    Err(_) => false
-                }
-            }
-            Err(_) => false
-        }
-    }
-
-    macro_rules! skip_if_no_keyring {
-        () => {
-            if !is_keyring_available() {
-                eprintln!("Skipping test: keyring service not available (CI environment)");
-                return;
-            }
-        };
-    }
 
     fn cleanup_test_otp(app_name: &str) {
         let _ = delete_otp(app_name);
@@ -225,11 +176,10 @@ mod tests {
 
     #[test]
     fn test_generate_otp_creates_password() {
-        skip_if_no_keyring!();
         let app_name = "test_otp_create_unique_123";
         cleanup_test_otp(app_name);
         
-        let result = generate_otp(app_name, 300, 12); // Longer TTL to avoid auto-delete during test
+        let result = generate_otp(app_name, 300, 12, None); // Longer TTL to avoid auto-delete during test
         assert!(result.is_ok(), "generate_otp should succeed");
         
         let otp = result.unwrap();
@@ -248,11 +198,10 @@ mod tests {
 
     #[test]
     fn test_generate_otp_saves_expiry() {
-        skip_if_no_keyring!();
         let app_name = "test_otp_expiry_save";
         cleanup_test_otp(app_name);
         
-        let _otp = generate_otp(app_name, 60, 10).unwrap();
+        let _otp = generate_otp(app_name, 60, 10, None).unwrap();
         
         // Verify expiry is saved
         let expiry = get_otp_expiry(app_name);
@@ -270,11 +219,10 @@ mod tests {
 
     #[test]
     fn test_is_otp_expired_false_initially() {
-        skip_if_no_keyring!();
         let app_name = "test_otp_not_expired";
         cleanup_test_otp(app_name);
         
-        let _otp = generate_otp(app_name, 60, 10).unwrap();
+        let _otp = generate_otp(app_name, 60, 10, None).unwrap();
         
         // Should not be expired immediately
         assert!(!is_otp_expired(app_name));
@@ -284,12 +232,11 @@ mod tests {
 
     #[test]
     fn test_is_otp_expired_true_after_ttl() {
-        skip_if_no_keyring!();
         let app_name = "test_otp_expired_ttl_check";
         cleanup_test_otp(app_name);
         
         // Create with longer TTL to test expiry logic, not auto-delete
-        let _otp = generate_otp(app_name, 60, 10).unwrap();
+        let _otp = generate_otp(app_name, 60, 10, None).unwrap();
         
         // Get the expiry timestamp
         let expiry = get_otp_expiry(app_name);
@@ -311,11 +258,10 @@ mod tests {
 
     #[test]
     fn test_delete_otp_removes_password_and_expiry() {
-        skip_if_no_keyring!();
         let app_name = "test_otp_delete_unique_456";
         cleanup_test_otp(app_name);
         
-        let _otp = generate_otp(app_name, 300, 10).unwrap(); // Longer TTL
+        let _otp = generate_otp(app_name, 300, 10, None).unwrap(); // Longer TTL
         
         // Small delay to ensure keyring is updated
         thread::sleep(Duration::from_millis(100));
@@ -338,7 +284,6 @@ mod tests {
 
     #[test]
     fn test_save_and_get_otp_expiry() {
-        skip_if_no_keyring!();
         let app_name = "test_otp_expiry_roundtrip";
         let timestamp = 1234567890u64;
         
@@ -352,7 +297,7 @@ mod tests {
         assert_eq!(retrieved.unwrap(), timestamp);
         
         // Cleanup
-        let _ = delete_otp_expiry(app_name);
+        delete_otp_expiry(app_name);
     }
 
     #[test]
@@ -372,19 +317,17 @@ mod tests {
     fn test_cleanup_expired_otps_does_not_panic() {
         // Just verify it doesn't panic
         cleanup_expired_otps();
-        assert!(true);
     }
 
     #[test]
     fn test_otp_length_variations() {
-        skip_if_no_keyring!();
         let test_cases = [5, 10, 20, 50];
         
         for (i, length) in test_cases.iter().enumerate() {
             let app_name = format!("test_otp_length_{}", i);
             cleanup_test_otp(&app_name);
             
-            let result = generate_otp(&app_name, 60, *length);
+            let result = generate_otp(&app_name, 60, *length, None);
             assert!(result.is_ok());
             assert_eq!(result.unwrap().len(), *length);
             
@@ -394,11 +337,10 @@ mod tests {
 
     #[test]
     fn test_otp_contains_alphanumeric_only() {
-        skip_if_no_keyring!();
         let app_name = "test_otp_alphanumeric";
         cleanup_test_otp(app_name);
         
-        let otp = generate_otp(app_name, 60, 100).unwrap();
+        let otp = generate_otp(app_name, 60, 100, None).unwrap();
         
         // All characters should be alphanumeric
         assert!(otp.chars().all(|c| c.is_alphanumeric()));
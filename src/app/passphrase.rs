@@ -0,0 +1,394 @@
+use once_cell::sync::Lazy;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use crate::app::error::{AppError, Result};
+use crate::app::policy::DEFAULT_SYMBOLS;
+use crate::app::rng::with_rng;
+
+/// Number of words in a passphrase when the configuration does not set one.
+pub const DEFAULT_PASSPHRASE_WORDS: usize = 6;
+
+/// Range of word counts accepted for a passphrase.
+pub const PASSPHRASE_WORDS_RANGE: std::ops::RangeInclusive<usize> = 3..=20;
+
+/// The embedded wordlist, one word per line.
+const EMBEDDED_WORDLIST: &str = include_str!("wordlist.txt");
+
+static EMBEDDED: Lazy<Wordlist> =
+    Lazy::new(|| Wordlist::parse(EMBEDDED_WORDLIST).expect("the embedded wordlist is valid"));
+
+/// A list of distinct words passphrases are drawn from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wordlist {
+    words: Vec<String>,
+}
+
+impl Wordlist {
+    /// Returns the embedded list of 7776 common English words.
+    pub fn embedded() -> &'static Wordlist {
+        &EMBEDDED
+    }
+
+    /// Parses a wordlist: one word per line, or diceware lines (`11111 word`) where the
+    /// word is the last column. Empty lines and lines starting with `#` are skipped, and
+    /// duplicates are dropped.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Wordlist, String>` - The list, or an error message if it has fewer than two words.
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        let mut words: Vec<String> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_whitespace().last())
+            .map(String::from)
+            .collect();
+        words.sort_unstable();
+        words.dedup();
+        if words.len() < 2 {
+            return Err("a wordlist needs at least two different words".to_string());
+        }
+        Ok(Self { words })
+    }
+
+    /// Loads a wordlist file (see `parse` for the format).
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Wordlist>` - The list, `AppError::Io` if the file cannot be read, or
+    ///   `AppError::Parse` if it holds fewer than two words.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+        Self::parse(&text).map_err(|e| AppError::parse(path.display().to_string(), e))
+    }
+
+    /// Returns the words, sorted.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Returns the number of words.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Always false: a wordlist holds at least two words.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Bits of entropy one randomly picked word adds.
+    pub fn bits_per_word(&self) -> f64 {
+        (self.words.len() as f64).log2()
+    }
+}
+
+/// How the words of a passphrase are capitalized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Capitalization {
+    /// every word in lowercase
+    #[default]
+    Lower,
+    /// Every Word Capitalized
+    Title,
+    /// EVERY WORD IN UPPERCASE
+    Upper,
+    /// Each word randomly lowercase or Capitalized (one more bit per word)
+    Random,
+}
+
+impl Capitalization {
+    /// All capitalization names accepted in the configuration and on the command line.
+    pub const NAMES: [&'static str; 4] = ["lower", "title", "upper", "random"];
+}
+
+impl fmt::Display for Capitalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Capitalization::Lower => "lower",
+            Capitalization::Title => "title",
+            Capitalization::Upper => "upper",
+            Capitalization::Random => "random",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Capitalization {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "lower" => Ok(Capitalization::Lower),
+            "title" => Ok(Capitalization::Title),
+            "upper" => Ok(Capitalization::Upper),
+            "random" => Ok(Capitalization::Random),
+            other => Err(format!(
+                "Unknown capitalization '{}'. Expected one of: {}",
+                other,
+                Capitalization::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// How passphrases are built.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PassphraseSettings {
+    /// Number of words.
+    pub words: usize,
+    /// Text put between the words.
+    pub separator: String,
+    /// How the words are capitalized.
+    pub capitalize: Capitalization,
+    /// Append a random digit to a random word.
+    pub digit: bool,
+    /// Append a random symbol to a random word.
+    pub symbol: bool,
+    /// Wordlist file used instead of the embedded list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wordlist: Option<PathBuf>,
+}
+
+impl Default for PassphraseSettings {
+    fn default() -> Self {
+        Self {
+            words: DEFAULT_PASSPHRASE_WORDS,
+            separator: "-".to_string(),
+            capitalize: Capitalization::default(),
+            digit: false,
+            symbol: false,
+            wordlist: None,
+        }
+    }
+}
+
+impl PassphraseSettings {
+    /// Every setting accepted by `get` and `set`, named as in the `[passphrase]` configuration section.
+    pub const KEYS: [&'static str; 6] = ["words", "separator", "capitalize", "digit", "symbol", "wordlist"];
+
+    /// Returns the value of the setting `key` (one of `PassphraseSettings::KEYS`) as text.
+    /// The embedded wordlist is an empty `wordlist`.
+    pub fn get(&self, key: &str) -> Option<String> {
+        Some(match key {
+            "words" => self.words.to_string(),
+            "separator" => self.separator.clone(),
+            "capitalize" => self.capitalize.to_string(),
+            "digit" => self.digit.to_string(),
+            "symbol" => self.symbol.to_string(),
+            "wordlist" => self.wordlist.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+            _ => return None,
+        })
+    }
+
+    /// Parses `value` into the setting `key` (one of `PassphraseSettings::KEYS`). An empty
+    /// `wordlist` selects the embedded list.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Result<(), String>>` - `None` if the key is unknown, or the parse error.
+    pub fn set(&mut self, key: &str, value: &str) -> Option<std::result::Result<(), String>> {
+        let result = match key {
+            "words" => value.trim().parse().map(|n| self.words = n).map_err(|e| e.to_string()),
+            "separator" => {
+                self.separator = value.to_string();
+                Ok(())
+            }
+            "capitalize" => value.parse().map(|c| self.capitalize = c),
+            "digit" => value.trim().parse().map(|on| self.digit = on).map_err(|e| e.to_string()),
+            "symbol" => value.trim().parse().map(|on| self.symbol = on).map_err(|e| e.to_string()),
+            "wordlist" => {
+                self.wordlist = Some(PathBuf::from(value.trim())).filter(|p| !p.as_os_str().is_empty());
+                Ok(())
+            }
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Checks that the word count and separator are usable.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if !PASSPHRASE_WORDS_RANGE.contains(&self.words) {
+            return Err(format!(
+                "words must be between {} and {}",
+                PASSPHRASE_WORDS_RANGE.start(),
+                PASSPHRASE_WORDS_RANGE.end()
+            ));
+        }
+        if self.separator.chars().count() > 8 || self.separator.chars().any(char::is_control) {
+            return Err("separator must be at most 8 printable characters".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A generated passphrase and its strength.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passphrase {
+    /// The passphrase.
+    pub text: String,
+    /// Bits of entropy, assuming the attacker knows the wordlist and the settings.
+    pub entropy: f64,
+}
+
+/// Generates a passphrase from the configured wordlist (the embedded one unless
+/// `settings.wordlist` is set).
+///
+/// # Arguments
+///
+/// * `settings` - How the passphrase is built.
+///
+/// # Returns
+///
+/// * `Result<Passphrase>` - The passphrase, `AppError::InvalidInput` if the settings are out
+///   of range, or an error from `Wordlist::load`.
+pub fn generate_passphrase(settings: &PassphraseSettings) -> Result<Passphrase> {
+    match &settings.wordlist {
+        Some(path) => {
+            let wordlist = Wordlist::load(path)?;
+            with_rng(|rng| generate_passphrase_with(&wordlist, settings, rng))
+        }
+        None => with_rng(|rng| generate_passphrase_with(Wordlist::embedded(), settings, rng)),
+    }
+}
+
+/// Generates a passphrase from `wordlist`, drawing randomness from `rng`.
+pub fn generate_passphrase_with<R: Rng + ?Sized>(
+    wordlist: &Wordlist,
+    settings: &PassphraseSettings,
+    rng: &mut R,
+) -> Result<Passphrase> {
+    settings
+        .validate()
+        .map_err(|e| AppError::InvalidInput(format!("Passphrase: {}", e)))?;
+
+    let count = settings.words;
+    let mut entropy = count as f64 * wordlist.bits_per_word();
+    let mut words: Vec<String> = (0..count)
+        .map(|_| {
+            let word = wordlist.words.choose(rng).expect("wordlists are not empty");
+            match settings.capitalize {
+                Capitalization::Lower => word.to_lowercase(),
+                Capitalization::Title => title_case(word),
+                Capitalization::Upper => word.to_uppercase(),
+                Capitalization::Random if rng.gen_bool(0.5) => title_case(word),
+                Capitalization::Random => word.to_lowercase(),
+            }
+        })
+        .collect();
+    if settings.capitalize == Capitalization::Random {
+        entropy += count as f64;
+    }
+
+    let position_bits = (count as f64).log2();
+    if settings.digit {
+        let digit = rng.gen_range(0..10u8);
+        words[rng.gen_range(0..count)].push(char::from(b'0' + digit));
+        entropy += 10f64.log2() + position_bits;
+    }
+    if settings.symbol {
+        let symbols: Vec<char> = DEFAULT_SYMBOLS.chars().collect();
+        words[rng.gen_range(0..count)].push(*symbols.choose(rng).expect("symbols are not empty"));
+        entropy += (symbols.len() as f64).log2() + position_bits;
+    }
+
+    Ok(Passphrase {
+        text: words.join(&settings.separator),
+        entropy,
+    })
+}
+
+/// Uppercases the first letter of `word` and lowercases the rest.
+fn title_case(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_embedded_wordlist() {
+        let wordlist = Wordlist::embedded();
+        assert_eq!(wordlist.len(), 7776);
+        assert!((wordlist.bits_per_word() - 12.925).abs() < 0.001);
+        assert!(wordlist.words().iter().all(|w| w.chars().all(|c| c.is_ascii_lowercase())));
+    }
+
+    #[test]
+    fn test_default_passphrase() {
+        let passphrase = generate_passphrase(&PassphraseSettings::default()).unwrap();
+        let words: Vec<&str> = passphrase.text.split('-').collect();
+        assert_eq!(words.len(), DEFAULT_PASSPHRASE_WORDS);
+        assert!(words.iter().all(|w| Wordlist::embedded().words().iter().any(|known| known == w)));
+        assert!((passphrase.entropy - 6.0 * 12.925).abs() < 0.01, "{}", passphrase.entropy);
+    }
+
+    #[test]
+    fn test_capitalization_digit_and_symbol() {
+        let wordlist = Wordlist::parse("alpha\nbravo\ncharlie\ndelta\n").unwrap();
+        let settings = PassphraseSettings {
+            words: 4,
+            separator: " ".to_string(),
+            capitalize: Capitalization::Title,
+            digit: true,
+            symbol: true,
+            wordlist: None,
+        };
+        let passphrase = generate_passphrase_with(&wordlist, &settings, &mut ChaCha20Rng::seed_from_u64(1)).unwrap();
+        let text = &passphrase.text;
+        assert_eq!(text.split(' ').count(), 4);
+        assert!(text.split(' ').all(|w| w.starts_with(|c: char| c.is_ascii_uppercase())), "{}", text);
+        assert_eq!(text.chars().filter(|c| c.is_ascii_digit()).count(), 1, "{}", text);
+        assert_eq!(text.chars().filter(|c| DEFAULT_SYMBOLS.contains(*c)).count(), 1, "{}", text);
+        // 4 words of 2 bits, a digit and a symbol each with one of 4 positions
+        let expected = 8.0 + (10f64.log2() + 2.0) + ((DEFAULT_SYMBOLS.len() as f64).log2() + 2.0);
+        assert!((passphrase.entropy - expected).abs() < 1e-9);
+
+        let upper = PassphraseSettings { capitalize: Capitalization::Upper, digit: false, symbol: false, ..settings };
+        let text = generate_passphrase_with(&wordlist, &upper, &mut ChaCha20Rng::seed_from_u64(1)).unwrap().text;
+        assert_eq!(text, text.to_uppercase());
+    }
+
+    #[test]
+    fn test_custom_wordlist_file() {
+        let path = std::env::temp_dir().join(format!("apppass_wordlist_test_{}.txt", std::process::id()));
+        fs::write(&path, "# diceware format\n11111 kiwi\n11112 mango\n11113 kiwi\n").unwrap();
+        let settings = PassphraseSettings { words: 3, wordlist: Some(path.clone()), ..PassphraseSettings::default() };
+        let passphrase = generate_passphrase(&settings).unwrap();
+        assert!(passphrase.text.split('-').all(|w| w == "kiwi" || w == "mango"), "{}", passphrase.text);
+        assert!((passphrase.entropy - 3.0).abs() < 1e-9);
+
+        fs::write(&path, "kiwi\n").unwrap();
+        assert!(matches!(generate_passphrase(&settings), Err(AppError::Parse { .. })));
+        let _ = fs::remove_file(&path);
+        assert!(matches!(generate_passphrase(&settings), Err(AppError::Io { .. })));
+    }
+
+    #[test]
+    fn test_settings_by_key() {
+        let mut settings = PassphraseSettings::default();
+        for key in PassphraseSettings::KEYS {
+            let value = settings.get(key).unwrap();
+            assert_eq!(settings.set(key, &value), Some(Ok(())), "{}", key);
+        }
+        assert_eq!(settings, PassphraseSettings::default());
+        assert!(matches!(settings.set("capitalize", "shout"), Some(Err(_))));
+        settings.set("words", "2").unwrap().unwrap();
+        assert!(settings.validate().is_err());
+    }
+}
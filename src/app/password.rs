@@ -1,255 +1,609 @@
-use crate::app::keyring::{delete_from_keyring, get_from_keyring, save_to_keyring, set_password_type};
-use keyring::Error as KeyringError;
-use rand::distributions::Alphanumeric;
-use rand::prelude::SliceRandom;
-use rand::{thread_rng, Rng};
+use crate::app::entry::{EntryChanges, EntryRecord};
+use crate::app::index::load_index;
+use crate::app::history::get_history;
+use crate::app::trash::move_to_trash;
+use crate::app::keyring::{get_entry_record, get_from_keyring, get_password_type, replace_password,
+                          save_to_keyring, set_entry_record, set_password_type};
+use crate::app::breach::BreachCorpus;
+use crate::app::config::config;
+use crate::app::error::{AppError, Result};
+use crate::app::keys::validate_app_name;
+use crate::app::passphrase::{generate_passphrase, Passphrase, PassphraseSettings};
+use crate::app::pin::{generate_pin, pin_entropy};
+use crate::app::policy::{EntryPolicy, PasswordPolicy};
+use crate::app::strength::{check_password_strength, StrengthEstimate};
+
+/// Generates a random password following the configured password policy without saving it.
+///
+/// # Arguments
+///
+/// * `length` - The number of characters.
+///
+/// # Returns
+///
+/// * `Result<String>` - The generated password, or `AppError::InvalidInput` if the policy
+///   cannot be satisfied at this length.
+pub fn generate_password(length: usize) -> Result<String> {
+    config().password.policy.generate(length)
+}
+
+/// Generates a password with `policy`, falling back to the configured length and policy.
+///
+/// # Arguments
+///
+/// * `length` - An optional length (defaults to the configured `password.length`).
+/// * `policy` - An optional policy (defaults to the configured one).
+pub(crate) fn generate_with_policy(length: Option<usize>, policy: Option<&PasswordPolicy>) -> Result<String> {
+    let config = config();
+    policy
+        .unwrap_or(&config.password.policy)
+        .generate(length.unwrap_or(config.password.length))
+}
+
+/// Generates a memorizable passphrase without saving it, built from the configured
+/// `[passphrase]` settings (six words from the embedded wordlist by default).
+///
+/// # Returns
+///
+/// * `Result<Passphrase>` - The passphrase and its entropy, or an error from `generate_passphrase`.
+pub fn generate_memorizable() -> Result<Passphrase> {
+    generate_passphrase(&config().passphrase)
+}
 
 /// Retrieves the password for the specified application from the keyring.
 ///
 /// # Arguments
 ///
 /// * `app_name` - A string slice that holds the name of the application for which the password is retrieved.
-#[allow(dead_code)]
-pub fn get_password_for_specify_app(app_name: &str) -> Result<String, KeyringError> {
+pub fn get_password_for_specify_app(app_name: &str) -> Result<String> {
     get_from_keyring(app_name)
 }
 
+/// Saves a custom password for the specified application.
+///
+/// # Arguments
+///
+/// * `app_name` - A string slice that holds the name of the application.
+/// * `password` - A string slice that holds the password chosen by the user.
+///
+/// # Returns
+///
+/// * `Result<StrengthEstimate>` - The estimated strength of the password, `AppError::AlreadyExists` if the
+///   application already has a password (use `update_password`), or `AppError::InvalidInput` if it is
+///   weaker than the configured `password.min_strength` (see `check_password_strength`).
+pub fn save_custom_password(app_name: &str, password: &str) -> Result<StrengthEstimate> {
+    ensure_new_entry(app_name)?;
+    let estimate = check_password_strength(password)?;
+    save_to_keyring(app_name, password)?;
+    set_password_type(app_name, "custom")?;
+    Ok(estimate)
+}
+
 /// Updates the password for the specified application in the keyring with a custom password.
 ///
 /// # Arguments
 ///
 /// * `app_name` - A string slice that holds the name of the application for which the password is updated.
 /// * `new_password` - A string slice that holds the new password to be saved.
-#[allow(dead_code)]
-pub fn update_password(app_name: &str, new_password: &str) -> Result<(), KeyringError> {
+///
+/// # Returns
+///
+/// * `Result<StrengthEstimate>` - The estimated strength of the new password, `AppError::NotFound` if the
+///   application has no password, or `AppError::InvalidInput` if the password is weaker than the
+///   configured `password.min_strength` (see `check_password_strength`).
+pub fn update_password(app_name: &str, new_password: &str) -> Result<StrengthEstimate> {
     // Check if password exists before updating
-    match get_from_keyring(app_name) {
-        Ok(_) => {
-            // Password exists, proceed with update
-            save_to_keyring(app_name, new_password)?;
-            set_password_type(app_name, "custom")?;
-            Ok(())
-        }
-        Err(KeyringError::NoEntry) => {
-            Err(KeyringError::NoEntry)
-        }
-        Err(e) => Err(e),
-    }
+    get_from_keyring(app_name)?;
+    let estimate = check_password_strength(new_password)?;
+    replace_password(app_name, new_password, "update-custom")?;
+    set_password_type(app_name, "custom")?;
+    Ok(estimate)
 }
 
 /// Updates the password for the specified application by regenerating a new secure password.
 ///
+/// The password is generated with the length and policy (or template) stored with the entry
+/// (see `entry_policy`), unless others are given. The ones used are stored with the entry.
+/// A PIN entry gets a new PIN (see `update_pin_regenerate`) unless a policy or template is given.
+///
 /// # Arguments
 ///
 /// * `app_name` - A string slice that holds the name of the application for which the password is updated.
-/// * `length` - An optional length for the generated password (defaults to 30).
+/// * `length` - An optional length for the generated password.
+/// * `policy` - An optional password policy.
+/// * `template` - An optional template (see `Template`), which cannot be combined with a length or policy.
 ///
 /// # Returns
 ///
-/// * `Result<String, KeyringError>` - Returns the new password on success.
-#[allow(dead_code)]
-pub fn update_password_regenerate(app_name: &str, length: Option<usize>) -> Result<String, KeyringError> {
+/// * `Result<String>` - Returns the new password on success, `AppError::NotFound` if the application has no password,
+///   or `AppError::InvalidInput` if the policy cannot be satisfied or the template is malformed.
+pub fn update_password_regenerate(
+    app_name: &str,
+    length: Option<usize>,
+    policy: Option<&PasswordPolicy>,
+    template: Option<&str>,
+) -> Result<String> {
     // Check if password exists before updating
-    match get_from_keyring(app_name) {
-        Ok(_) => {
-            let length = length.unwrap_or(30);
-            
-            // Generate new secure password
-            let new_password: String = thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(length)
-                .map(char::from)
-                .collect();
-            
-            // Save the new password
-            save_to_keyring(app_name, &new_password)?;
-            set_password_type(app_name, "auto")?;
-            Ok(new_password)
-        }
-        Err(KeyringError::NoEntry) => {
-            Err(KeyringError::NoEntry)
-        }
-        Err(e) => Err(e),
+    get_from_keyring(app_name)?;
+    if is_pin_entry(app_name) && policy.is_none() && template.is_none() {
+        return update_pin_regenerate(app_name, length);
+    }
+
+    // Generate new secure password
+    let generation = match template {
+        Some(template) => template_policy(template, length, policy, entry_policy(app_name, None, None)?.policy)?,
+        None => entry_policy(app_name, length, policy)?,
+    };
+    let new_password = generation.generate()?;
+
+    // Save the new password
+    replace_password(app_name, &new_password, "update")?;
+    set_password_type(app_name, "auto")?;
+    store_entry_policy(app_name, generation)?;
+    Ok(new_password)
+}
+
+/// Returns the length and policy a new password for the specified application is generated
+/// with: the given ones, else those stored with the entry, else the configured defaults. A
+/// stored template is kept only if neither a length nor a policy is given.
+///
+/// # Arguments
+///
+/// * `app_name` - A string slice that holds the name of the application.
+/// * `length` - An optional length overriding the stored one.
+/// * `policy` - An optional password policy overriding the stored one.
+///
+/// # Returns
+///
+/// * `Result<EntryPolicy>` - The length and policy, or an `AppError` if the entry's details cannot be read.
+pub fn entry_policy(app_name: &str, length: Option<usize>, policy: Option<&PasswordPolicy>) -> Result<EntryPolicy> {
+    let stored = get_entry_record(app_name)?.policy;
+    let config = config();
+    let (stored_length, stored_policy, stored_template) = match stored {
+        Some(stored) => (stored.length, stored.policy, stored.template),
+        None => (config.password.length, config.password.policy, None),
+    };
+    Ok(EntryPolicy {
+        length: length.unwrap_or(stored_length),
+        policy: policy.cloned().unwrap_or(stored_policy),
+        template: stored_template.filter(|_| length.is_none() && policy.is_none()),
+    })
+}
+
+/// Returns the generation from `template`, which sets the length and characters itself.
+///
+/// # Arguments
+///
+/// * `template` - The template (see `Template`).
+/// * `length` - Must be `None`.
+/// * `policy` - Must be `None`.
+/// * `fallback` - The policy kept with the template, used again if the template is dropped.
+///
+/// # Returns
+///
+/// * `Result<EntryPolicy>` - The generation, or `AppError::InvalidInput` if the template is
+///   malformed or combined with a length or policy.
+fn template_policy(
+    template: &str,
+    length: Option<usize>,
+    policy: Option<&PasswordPolicy>,
+    fallback: PasswordPolicy,
+) -> Result<EntryPolicy> {
+    if length.is_some() || policy.is_some() {
+        return Err(AppError::InvalidInput(
+            "A template sets the length and characters itself; it cannot be combined with a length or password policy"
+                .to_string(),
+        ));
+    }
+    EntryPolicy::from_template(template, fallback)
+}
+
+/// Stores the length and policy used to regenerate the password of the specified application.
+/// `None` removes it, so the configured defaults apply again.
+///
+/// # Arguments
+///
+/// * `app_name` - A string slice that holds the name of the application.
+/// * `policy` - The length and policy to store.
+///
+/// # Returns
+///
+/// * `Result<()>` - `AppError::NotFound` if the application has no password, or
+///   `AppError::InvalidInput` if the policy cannot be satisfied at its length.
+pub fn set_entry_policy(app_name: &str, policy: Option<EntryPolicy>) -> Result<()> {
+    get_from_keyring(app_name)?;
+    if let Some(policy) = &policy {
+        policy
+            .validate()
+            .map_err(|e| AppError::InvalidInput(format!("Password policy: {}", e)))?;
+    }
+    let mut record = get_entry_record(app_name)?;
+    let pin_to_policy = policy.is_some() && is_pin_entry(app_name);
+    record.policy = policy;
+    set_entry_record(app_name, &record)?;
+    if pin_to_policy {
+        // The next rotation follows the policy instead of generating a PIN
+        set_password_type(app_name, "auto")?;
+    }
+    Ok(())
+}
+
+/// Returns true if the specified application holds a generated PIN (see `generate_save_pin`).
+pub fn is_pin_entry(app_name: &str) -> bool {
+    get_password_type(app_name).as_deref() == Some("pin")
+}
+
+/// Returns the entropy, in bits, of the generated password of the specified application:
+/// that of its PIN length for a PIN entry, otherwise that of its stored length and policy.
+///
+/// # Arguments
+///
+/// * `app_name` - A string slice that holds the name of the application.
+///
+/// # Returns
+///
+/// * `Result<f64>` - The entropy, or an `AppError` if the entry cannot be read.
+pub fn entry_entropy(app_name: &str) -> Result<f64> {
+    if is_pin_entry(app_name) {
+        Ok(pin_entropy(get_from_keyring(app_name)?.len()))
+    } else {
+        Ok(entry_policy(app_name, None, None)?.entropy())
     }
 }
 
+/// Saves `policy` into the details record of a generated entry.
+fn store_entry_policy(app_name: &str, policy: EntryPolicy) -> Result<()> {
+    let mut record = get_entry_record(app_name)?;
+    record.policy = Some(policy);
+    set_entry_record(app_name, &record)
+}
+
+/// Restores a previous password of the specified application from its history.
+///
+/// The current password is itself added to the history, so a restore can be undone.
+///
+/// # Arguments
+///
+/// * `app_name` - A string slice that holds the name of the application.
+/// * `revision` - Which previous password to restore: 1 is the most recent one (see `get_history`).
+///
+/// # Returns
+///
+/// * `Result<String>` - Returns the restored password, `AppError::NotFound` if the application does not exist,
+///   or `AppError::InvalidInput` if the revision does not exist.
+pub fn restore_password(app_name: &str, revision: usize) -> Result<String> {
+    get_from_keyring(app_name)?;
+    let history = get_history(app_name)?;
+    let entry = revision
+        .checked_sub(1)
+        .and_then(|i| history.get(i))
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "'{}' has no revision {} ({} previous passwords kept)",
+                app_name,
+                revision,
+                history.len()
+            ))
+        })?;
+
+    replace_password(app_name, &entry.password, "restore")?;
+    Ok(entry.password.clone())
+}
+
 /// Generates a random password for the specified application and saves it to the keyring.
 ///
-/// The password is composed of alphanumeric characters and has a default length of 30 characters if not specified.
+/// The configured `password.length` and password policy are used unless given. The ones used
+/// (or the template) are stored with the entry, so `update_password_regenerate` reuses them.
 ///
 /// # Arguments
 ///
 /// * `app_name` - A string slice that holds the name of the application for which the password is generated.
 /// * `length` - An optional length for the generated password.
-pub fn generate_save_safety_password(app_name: &str, length: Option<usize>) -> Result<(), KeyringError> {
-    // Check if password already exists
-    match get_from_keyring(app_name) {
-        Ok(_) => {
-            return Err(KeyringError::NoEntry);
-        }
-        Err(KeyringError::NoEntry) => {
-            // Continue - this is what we want
-        }
-        Err(e) => return Err(e),
-    }
-
-    let length = length.unwrap_or(30);
+/// * `policy` - An optional password policy.
+/// * `template` - An optional template (see `Template`), which cannot be combined with a length or policy.
+///
+/// # Returns
+///
+/// * `Result<()>` - `AppError::AlreadyExists` if the application already has a password, or
+///   `AppError::InvalidInput` if the policy cannot be satisfied or the template is malformed.
+pub fn generate_save_safety_password(
+    app_name: &str,
+    length: Option<usize>,
+    policy: Option<&PasswordPolicy>,
+    template: Option<&str>,
+) -> Result<()> {
+    ensure_new_entry(app_name)?;
 
-    let rand_password: String = thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(length)
-        .map(char::from)
-        .collect();
+    let config = config();
+    let generation = match template {
+        Some(template) => template_policy(template, length, policy, config.password.policy)?,
+        None => EntryPolicy {
+            length: length.unwrap_or(config.password.length),
+            policy: policy.cloned().unwrap_or(config.password.policy),
+            template: None,
+        },
+    };
+    let rand_password = generation.generate()?;
 
     save_to_keyring(app_name, &rand_password)?;
     set_password_type(app_name, "auto")?;
+    store_entry_policy(app_name, generation)?;
     Ok(())
 }
 
-/// Deletes the password for the specified application from the keyring.
+/// Generates a random numeric PIN for the specified application and saves it to the keyring.
+///
+/// The PIN avoids sequences, repeats, common PINs and dates (see `pin_weakness`). The entry
+/// is recorded as a PIN, so `update_password_regenerate` keeps producing PINs.
+///
+/// # Arguments
+///
+/// * `app_name` - A string slice that holds the name of the application.
+/// * `length` - An optional number of digits (defaults to the configured `pin.length`).
+///
+/// # Returns
+///
+/// * `Result<String>` - The saved PIN, `AppError::AlreadyExists` if the application already has
+///   a password, or `AppError::InvalidInput` if the length is out of range.
+pub fn generate_save_pin(app_name: &str, length: Option<usize>) -> Result<String> {
+    ensure_new_entry(app_name)?;
+
+    let pin = generate_pin(length.unwrap_or(config().pin.length))?;
+    save_to_keyring(app_name, &pin)?;
+    set_password_type(app_name, "pin")?;
+    Ok(pin)
+}
+
+/// Replaces the password of the specified application with a new PIN and records the entry
+/// as a PIN. A stored password policy is removed, as it no longer applies.
+///
+/// # Arguments
+///
+/// * `app_name` - A string slice that holds the name of the application.
+/// * `length` - An optional number of digits (defaults to the length of the current PIN, or
+///   to the configured `pin.length` if the entry does not hold a PIN).
+///
+/// # Returns
+///
+/// * `Result<String>` - The new PIN, `AppError::NotFound` if the application has no password,
+///   or `AppError::InvalidInput` if the length is out of range.
+pub fn update_pin_regenerate(app_name: &str, length: Option<usize>) -> Result<String> {
+    let current = get_from_keyring(app_name)?;
+    let length = match length {
+        Some(length) => length,
+        None if is_pin_entry(app_name) => current.len(),
+        None => config().pin.length,
+    };
+    let pin = generate_pin(length)?;
+
+    replace_password(app_name, &pin, "update")?;
+    set_password_type(app_name, "pin")?;
+    let mut record = get_entry_record(app_name)?;
+    if record.policy.take().is_some() {
+        set_entry_record(app_name, &record)?;
+    }
+    Ok(pin)
+}
+
+/// Deletes the password for the specified application by moving it to the trash,
+/// from where it can be restored until it is purged.
 ///
 /// # Arguments
 ///
 /// * `app_name` - A string slice that holds the name of the application for which the password is deleted.
-#[allow(dead_code)]
-pub fn delete_password(app_name: &str) -> Result<(), KeyringError> {
-    delete_from_keyring(app_name)
+pub fn delete_password(app_name: &str) -> Result<()> {
+    move_to_trash(app_name)
+}
+
+/// Checks that `app_name` can be used for a new entry.
+///
+/// # Returns
+///
+/// * `Result<()>` - `AppError::InvalidName` if the name is not allowed (see `validate_app_name`),
+///   or `AppError::AlreadyExists` if it has a password.
+fn ensure_new_entry(app_name: &str) -> Result<()> {
+    validate_app_name(app_name)?;
+    match get_from_keyring(app_name) {
+        Ok(_) => Err(AppError::AlreadyExists(app_name.to_string())),
+        Err(AppError::NotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Columns written by `export_passwords`, in order.
+const EXPORT_COLUMNS: [&str; 8] = ["name", "password", "type", "username", "urls", "notes", "fields", "tags"];
+
+/// Updates the details (username, URLs, notes, custom fields, tags) of an existing entry.
+///
+/// # Arguments
+///
+/// * `app_name` - A string slice that holds the name of the application to edit.
+/// * `changes` - The edits to apply to the stored record.
+///
+/// # Returns
+///
+/// * `Result<EntryRecord>` - Returns the updated record, or `AppError::NotFound` if the application has no password.
+pub fn edit_entry(app_name: &str, changes: &EntryChanges) -> Result<EntryRecord> {
+    get_from_keyring(app_name)?;
+    let mut record = get_entry_record(app_name)?;
+    changes.apply(&mut record);
+    set_entry_record(app_name, &record)?;
+    Ok(record)
 }
 
 /// Exports all stored passwords to a specified file.
 ///
-/// The passwords are retrieved from the keyring and written to the file in the format `app_name,password`.
+/// The passwords are retrieved from the keyring and written as CSV with a header row
+/// (`name,password,type,username,urls,notes,fields,tags`). URLs, custom fields and tags are
+/// stored one per line inside their cell, fields as `key=value`.
 ///
 /// # Arguments
 ///
 /// * `file_path` - A string slice that holds the path to the file where passwords will be exported.
-pub fn export_passwords(file_path: &str) -> Result<(), KeyringError> {
-    use crate::app::{APP_SERVICE, APP_INDEX};
-    use keyring::Entry;
-    
-    // Get the index of all applications from keyring
-    let entry = Entry::new(APP_SERVICE, APP_INDEX)?;
-    let app_names_str = match entry.get_password() {
-        Ok(data) => data,
-        Err(KeyringError::NoEntry) => {
-            return Ok(());
-        }
-        Err(e) => return Err(e),
-    };
-    
-    let app_names: Vec<&str> = app_names_str.split(',').filter(|s| !s.is_empty()).collect();
-    let mut content = String::new();
-
-    for app_name in app_names {
-        // Skip metadata entries (password_length, _type suffixes, and internal index)
-        if app_name == crate::app::PASSWORD_LENGTH_KEY 
-            || app_name.ends_with(crate::app::PASSWORD_TYPE_SUFFIX)
-            || app_name == crate::app::APP_INDEX {
-            continue;
-        }
-        if let Ok(password) = get_from_keyring(app_name) {
-            content.push_str(&format!("{},{}\n", app_name, password));
+///
+/// # Returns
+///
+/// * `Result<()>` - `AppError::Io` if the file cannot be written.
+pub fn export_passwords(file_path: &str) -> Result<()> {
+    let index = load_index()?;
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let mut exported = 0;
+
+    let csv_error = |e: csv::Error| AppError::parse(file_path, e);
+    writer.write_record(EXPORT_COLUMNS).map_err(csv_error)?;
+    for entry in index.entries.values() {
+        if let Ok(password) = get_from_keyring(&entry.name) {
+            let record = get_entry_record(&entry.name).unwrap_or_default();
+            writer
+                .write_record([
+                    entry.name.as_str(),
+                    password.as_str(),
+                    entry.password_type.as_str(),
+                    record.username.as_deref().unwrap_or(""),
+                    record.urls_cell().as_str(),
+                    record.notes.as_deref().unwrap_or(""),
+                    record.fields_cell().as_str(),
+                    record.tags_cell().as_str(),
+                ])
+                .map_err(csv_error)?;
+            exported += 1;
         }
     }
 
-    if content.is_empty() {
-        Ok(())
-    } else if std::fs::write(file_path, content).is_ok() {
-        Ok(())
-    } else {
-        Err(KeyringError::NoEntry)
+    let content = writer
+        .into_inner()
+        .map_err(|e| AppError::io(file_path, e.into_error()))?;
+    if exported > 0 {
+        std::fs::write(file_path, content).map_err(|e| AppError::io(file_path, e))?;
     }
+    Ok(())
 }
 
 /// Imports passwords from a specified file and saves them to the keyring.
 ///
-/// The file should contain lines in the format `app_name,password`.
+/// Accepts files written by `export_passwords` (with a header row) as well as the older
+/// headerless format with lines `app_name,password`, which are imported as custom passwords.
+/// The whole file is checked before anything is saved, so a malformed row or an invalid
+/// application name leaves the store untouched. Custom passwords are looked up in the breach
+/// corpus (`password.breach_file`), if one is configured; breached ones are still imported.
 ///
 /// # Arguments
 ///
 /// * `file_path` - A string slice that holds the path to the file from which passwords are imported.
-pub fn import_passwords(file_path: &str) -> Result<(), KeyringError> {
-    if let Ok(content) = std::fs::read_to_string(file_path) {
-        let lines = content.lines();
-        for line in lines {
-            let key_value: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
-            if key_value.len() == 2 {
-                let app_name = key_value[0];
-                let password = key_value[1];
-                save_to_keyring(app_name, password)?;
-                set_password_type(app_name, "custom")?; // Mark imported passwords as custom
+///
+/// # Returns
+///
+/// * `Result<Vec<(String, u64)>>` - The imported custom passwords found in the breach corpus, with
+///   their count; `AppError::Io` if the file cannot be read, `AppError::Parse` if a row is malformed
+///   or names an application that is not allowed (see `validate_app_name`), or an error from
+///   `BreachCorpus::count`.
+pub fn import_passwords(file_path: &str) -> Result<Vec<(String, u64)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(file_path)
+        .map_err(|e| csv_read_error(file_path, e))?;
+
+    // (name, password, type, details) of every row to import; legacy rows have no details
+    let mut rows: Vec<(String, String, &str, Option<EntryRecord>)> = Vec::new();
+    let mut columns: Option<Vec<String>> = None;
+    for (line, row) in reader.records().enumerate() {
+        let row = row.map_err(|e| csv_read_error(file_path, e))?;
+        if line == 0 && row.get(0) == Some("name") && row.get(1) == Some("password") {
+            columns = Some(row.iter().map(String::from).collect());
+            continue;
+        }
+        let row_error = |e: &dyn std::fmt::Display| AppError::parse(format!("{}:{}", file_path, line + 1), e);
+
+        let imported = match &columns {
+            Some(columns) => {
+                let cell = |name: &str| {
+                    columns
+                        .iter()
+                        .position(|c| c == name)
+                        .and_then(|i| row.get(i))
+                        .unwrap_or("")
+                };
+                let (app_name, password) = (cell("name").trim(), cell("password"));
+                if app_name.is_empty() || password.is_empty() {
+                    continue;
+                }
+                let record = EntryRecord::from_cells(cell("username"), cell("urls"), cell("notes"), cell("fields"), cell("tags"))
+                    .map_err(|e| row_error(&e))?;
+                let password_type = match cell("type") {
+                    "auto" => "auto",
+                    "pin" => "pin",
+                    _ => "custom",
+                };
+                (app_name, password, password_type, Some(record))
+            }
+            // Mark imported passwords as custom
+            None if row.len() == 2 => (row[0].trim(), row[1].trim(), "custom", None),
+            None => continue,
+        };
+        validate_app_name(imported.0).map_err(|e| row_error(&e))?;
+        rows.push((imported.0.to_string(), imported.1.to_string(), imported.2, imported.3));
+    }
+
+    let mut breached = Vec::new();
+    if let Some(mut corpus) = BreachCorpus::configured()? {
+        for (app_name, password, _, _) in rows.iter().filter(|row| row.2 == "custom") {
+            match corpus.count(password)? {
+                0 => {}
+                count => breached.push((app_name.clone(), count)),
             }
         }
-        Ok(())
+    }
+
+    for (app_name, password, password_type, record) in rows {
+        replace_password(&app_name, &password, "import")?;
+        set_password_type(&app_name, password_type)?;
+        if let Some(record) = record {
+            set_entry_record(&app_name, &record)?;
+        }
+    }
+    Ok(breached)
+}
+
+/// Converts a CSV read error, keeping I/O failures apart from malformed content.
+fn csv_read_error(file_path: &str, error: csv::Error) -> AppError {
+    if error.is_io_error() {
+        match error.into_kind() {
+            csv::ErrorKind::Io(e) => AppError::io(file_path, e),
+            kind => AppError::parse(file_path, format!("{:?}", kind)),
+        }
     } else {
-        Err(KeyringError::NoEntry)
+        AppError::parse(file_path, error)
     }
 }
 
-/// Generates a memorizable password for the specified application and saves it to the keyring.
-///
-/// The password is composed of two random words from a predefined list and a random number between 10 and 99.
+/// Generates a memorizable passphrase for the specified application and saves it to the keyring.
 ///
 /// # Arguments
 ///
 /// * `app_name` - A string slice that holds the name of the application for which the password is generated.
-pub fn generate_memorizable_password(app_name: &str) -> Result<(), KeyringError> {
-    // Check if password already exists
-    match get_from_keyring(app_name) {
-        Ok(_) => {
-            return Err(KeyringError::NoEntry);
-        }
-        Err(KeyringError::NoEntry) => {
-            // Continue - this is what we want
-        }
-        Err(e) => return Err(e),
-    }
-
-    let words = vec!["Tiger", "Orange", "Mountain", "River", "Cloud", "Sky", "Sun", "Moon"];
-    let mut rng = thread_rng();
-
-    let password = format!(
-        "{}-{}-{}",
-        words.choose(&mut rng).unwrap(),
-        thread_rng().gen_range(10..99),
-        words.choose(&mut rng).unwrap()
-    );
+/// * `settings` - Optional passphrase settings (defaults to the configured `[passphrase]` settings).
+///
+/// # Returns
+///
+/// * `Result<Passphrase>` - The saved passphrase and its entropy, `AppError::AlreadyExists` if the
+///   application already has a password, or an error from `generate_passphrase`.
+pub fn generate_memorizable_password(app_name: &str, settings: Option<&PassphraseSettings>) -> Result<Passphrase> {
+    ensure_new_entry(app_name)?;
 
-    save_to_keyring(app_name, &password)?;
+    let passphrase = match settings {
+        Some(settings) => generate_passphrase(settings)?,
+        None => generate_memorizable()?,
+    };
+    save_to_keyring(app_name, &passphrase.text)?;
     set_password_type(app_name, "auto")?;
-    Ok(())
+    Ok(passphrase)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keyring::Entry;
-
-    /// Check if keyring service is available (returns false on headless CI systems)
-    fn is_keyring_available() -> bool {
-        let test_entry = Entry::new("apppass_test_probe", "keyring_availability_check");
-        match test_entry {
-            Ok(entry) => {
-                match entry.set_password("test") {
-                    Ok(_) => {
-                        let _ = entry.delete_credential();
-                        true
-                    }
-                    Err(_) => false
-                }
-            }
-            Err(_) => false
-        }
-    }
-
-    macro_rules! skip_if_no_keyring {
-        () => {
-            if !is_keyring_available() {
-                eprintln!("Skipping test: keyring service not available (CI environment)");
-                return;
-            }
-        };
-    }
+    use crate::app::store::scoped_store;
 
     fn cleanup_test_password(app_name: &str) {
         let _ = delete_password(app_name);
@@ -257,7 +611,7 @@ mod tests {
 
     #[test]
     fn test_get_password_for_specify_app() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_get_pw_app";
         cleanup_test_password(app_name);
         
@@ -272,13 +626,14 @@ mod tests {
 
     #[test]
     fn test_get_password_not_found() {
+        let _store = scoped_store();
         let result = get_password_for_specify_app("non_existent_app_xyz_456");
         assert!(result.is_err());
     }
 
     #[test]
     fn test_update_password() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_update_pw_app";
         cleanup_test_password(app_name);
         
@@ -295,17 +650,18 @@ mod tests {
 
     #[test]
     fn test_update_password_not_found() {
+        let _store = scoped_store();
         let result = update_password("non_existent_update_app", "password");
         assert!(result.is_err());
     }
 
     #[test]
     fn test_generate_save_safety_password_default_length() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_gen_pw_default";
         cleanup_test_password(app_name);
         
-        let result = generate_save_safety_password(app_name, None);
+        let result = generate_save_safety_password(app_name, None, None, None);
         assert!(result.is_ok());
         
         let password = get_from_keyring(app_name).unwrap();
@@ -316,38 +672,80 @@ mod tests {
 
     #[test]
     fn test_generate_save_safety_password_custom_length() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_gen_pw_custom";
         cleanup_test_password(app_name);
         
-        let result = generate_save_safety_password(app_name, Some(15));
+        let result = generate_save_safety_password(app_name, Some(15), None, None);
         assert!(result.is_ok());
         
         let password = get_from_keyring(app_name).unwrap();
         assert_eq!(password.len(), 15);
-        
+
         cleanup_test_password(app_name);
     }
 
+    #[test]
+    fn test_generate_save_safety_password_with_policy() {
+        use crate::app::policy::CharClass;
+
+        let _store = scoped_store();
+        let app_name = "test_gen_pw_policy";
+        cleanup_test_password(app_name);
+
+        let mut policy = PasswordPolicy { classes: vec![CharClass::Digits], ..PasswordPolicy::default() };
+        policy.require(CharClass::Symbols, 4);
+        policy.symbols = "%".to_string();
+        generate_save_safety_password(app_name, Some(12), Some(&policy), None).unwrap();
+
+        let password = get_from_keyring(app_name).unwrap();
+        assert_eq!(password.len(), 12);
+        assert!(password.chars().filter(|c| *c == '%').count() >= 4);
+        assert!(password.chars().all(|c| c == '%' || c.is_ascii_digit()));
+
+        // A policy that cannot be met saves nothing
+        cleanup_test_password(app_name);
+        assert!(matches!(
+            generate_save_safety_password(app_name, Some(3), Some(&policy), None),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(get_from_keyring(app_name).is_err());
+    }
+
     #[test]
     fn test_generate_save_safety_password_already_exists() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_gen_pw_exists";
         cleanup_test_password(app_name);
         
         // First save should succeed
-        generate_save_safety_password(app_name, None).unwrap();
+        generate_save_safety_password(app_name, None, None, None).unwrap();
         
         // Second save should fail (already exists)
-        let result = generate_save_safety_password(app_name, None);
-        assert!(result.is_err());
+        let result = generate_save_safety_password(app_name, None, None, None);
+        assert!(matches!(result, Err(AppError::AlreadyExists(_))));
         
         cleanup_test_password(app_name);
     }
 
+    #[test]
+    fn test_save_custom_password_already_exists() {
+        let _store = scoped_store();
+        let app_name = "test_custom_pw_exists";
+        cleanup_test_password(app_name);
+
+        save_custom_password(app_name, "Original-Custom-Pass-91!").unwrap();
+
+        let result = save_custom_password(app_name, "Replacement-Custom-Pass-27!");
+        assert!(matches!(result, Err(AppError::AlreadyExists(_))));
+        assert_eq!(get_from_keyring(app_name).unwrap(), "Original-Custom-Pass-91!");
+
+        cleanup_test_password(app_name);
+    }
+
     #[test]
     fn test_delete_password() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_delete_pw_app";
         cleanup_test_password(app_name);
         
@@ -362,19 +760,20 @@ mod tests {
 
     #[test]
     fn test_delete_password_not_found() {
+        let _store = scoped_store();
         let result = delete_password("non_existent_delete_app");
         assert!(result.is_err());
     }
 
     #[test]
     fn test_update_password_regenerate() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_regen_pw_app";
         cleanup_test_password(app_name);
         
         save_to_keyring(app_name, "old_password").unwrap();
         
-        let result = update_password_regenerate(app_name, Some(20));
+        let result = update_password_regenerate(app_name, Some(20), None, None);
         assert!(result.is_ok());
         
         let new_password = result.unwrap();
@@ -384,26 +783,169 @@ mod tests {
         cleanup_test_password(app_name);
     }
 
+    #[test]
+    fn test_seeded_generation_is_reproducible() {
+        use crate::app::otp::{delete_otp, generate_otp};
+        use crate::app::rng::with_seed;
+
+        let _store = scoped_store();
+        let policy = PasswordPolicy::default();
+        let generate = |suffix: &str| {
+            let (name, memorable, otp) =
+                (format!("test_seeded_{}", suffix), format!("test_seeded_words_{}", suffix), format!("test_seeded_otp_{}", suffix));
+            for app_name in [&name, &memorable] {
+                cleanup_test_password(app_name);
+            }
+            let _ = delete_otp(&otp);
+            let generated = with_seed(42, || {
+                generate_save_safety_password(&name, Some(24), Some(&policy), None).unwrap();
+                let first = get_from_keyring(&name).unwrap();
+                let regenerated = update_password_regenerate(&name, None, None, None).unwrap();
+                let words = generate_memorizable_password(&memorable, Some(&PassphraseSettings::default())).unwrap();
+                let otp = generate_otp(&otp, 300, 12, Some(&policy)).unwrap();
+                (first, regenerated, words.text, otp)
+            });
+            for app_name in [&name, &memorable] {
+                cleanup_test_password(app_name);
+            }
+            let _ = delete_otp(&otp);
+            generated
+        };
+
+        let first = generate("a");
+        assert_eq!(generate("b"), first);
+        assert_ne!(first.0, first.1);
+        assert_ne!(generate_password(24).unwrap(), first.0);
+    }
+
+    #[test]
+    fn test_regenerate_reuses_stored_policy() {
+        use crate::app::policy::CharClass;
+
+        let _store = scoped_store();
+        let app_name = "test_regen_stored_policy";
+        cleanup_test_password(app_name);
+
+        let policy = PasswordPolicy {
+            classes: vec![CharClass::Lower, CharClass::Digits],
+            exclude: "%abc".to_string(),
+            ..PasswordPolicy::default()
+        };
+        generate_save_safety_password(app_name, Some(16), Some(&policy), None).unwrap();
+        let stored = get_entry_record(app_name).unwrap().policy.unwrap();
+        assert_eq!(stored, EntryPolicy { length: 16, policy: policy.clone(), template: None });
+
+        // Without arguments the stored length and policy apply
+        let password = update_password_regenerate(app_name, None, None, None).unwrap();
+        assert_eq!(password.len(), 16);
+        assert!(policy.is_satisfied_by(&password), "{}", password);
+
+        // An explicit length replaces the stored one and keeps the policy
+        update_password_regenerate(app_name, Some(12), None, None).unwrap();
+        assert_eq!(entry_policy(app_name, None, None).unwrap(), EntryPolicy { length: 12, policy, template: None });
+
+        // Editing validates, resetting falls back to the configuration
+        let impossible = EntryPolicy {
+            length: 4,
+            policy: PasswordPolicy { min_digits: 5, ..PasswordPolicy::default() },
+            template: None,
+        };
+        assert!(matches!(set_entry_policy(app_name, Some(impossible)), Err(AppError::InvalidInput(_))));
+        set_entry_policy(app_name, None).unwrap();
+        assert_eq!(entry_policy(app_name, None, None).unwrap().length, config().password.length);
+
+        cleanup_test_password(app_name);
+    }
+
+    #[test]
+    fn test_template_is_stored_and_reused() {
+        let _store = scoped_store();
+        let app_name = "test_template_stored";
+        cleanup_test_password(app_name);
+
+        assert!(matches!(
+            generate_save_safety_password(app_name, Some(12), None, Some("99-99")),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            generate_save_safety_password(app_name, None, None, Some("99-9q")),
+            Err(AppError::InvalidInput(_))
+        ));
+        generate_save_safety_password(app_name, None, None, Some("Cv99-\\A")).unwrap();
+        let is_templated = |password: &str| {
+            let chars: Vec<char> = password.chars().collect();
+            chars.len() == 6 && chars[0].is_ascii_uppercase() && chars[2..4].iter().all(char::is_ascii_digit)
+                && password.ends_with("-A")
+        };
+        assert!(is_templated(&get_from_keyring(app_name).unwrap()));
+
+        // Rotation reuses the template; a length or policy replaces it
+        let password = update_password_regenerate(app_name, None, None, None).unwrap();
+        assert!(is_templated(&password), "{}", password);
+        update_password_regenerate(app_name, Some(20), None, None).unwrap();
+        assert_eq!(entry_policy(app_name, None, None).unwrap().template, None);
+        let password = update_password_regenerate(app_name, None, None, Some("9{8}")).unwrap();
+        assert!(password.len() == 8 && password.chars().all(|c| c.is_ascii_digit()), "{}", password);
+
+        cleanup_test_password(app_name);
+    }
+
+    #[test]
+    fn test_pin_entry_keeps_producing_pins() {
+        use crate::app::pin::pin_weakness;
+
+        let _store = scoped_store();
+        let app_name = "test_pin_entry";
+        cleanup_test_password(app_name);
+
+        assert!(matches!(generate_save_pin(app_name, Some(3)), Err(AppError::InvalidInput(_))));
+        let pin = generate_save_pin(app_name, Some(8)).unwrap();
+        assert!(pin.len() == 8 && pin.chars().all(|c| c.is_ascii_digit()), "{}", pin);
+        assert!(is_pin_entry(app_name));
+        assert!(matches!(generate_save_pin(app_name, None), Err(AppError::AlreadyExists(_))));
+
+        // Rotation keeps the type and the number of digits
+        let pin = update_password_regenerate(app_name, None, None, None).unwrap();
+        assert!(pin.len() == 8 && pin_weakness(&pin).is_none(), "{}", pin);
+        assert!((entry_entropy(app_name).unwrap() - pin_entropy(8)).abs() < 1e-9);
+        let pin = update_password_regenerate(app_name, Some(4), None, None).unwrap();
+        assert_eq!(pin.len(), 4);
+        assert!(is_pin_entry(app_name));
+
+        // A policy turns it back into a password, and update_pin_regenerate into a PIN again
+        let password = update_password_regenerate(app_name, None, Some(&PasswordPolicy::default()), None).unwrap();
+        assert_eq!(password.len(), config().password.length);
+        assert!(!is_pin_entry(app_name));
+        let pin = update_pin_regenerate(app_name, None).unwrap();
+        assert_eq!(pin.len(), config().pin.length);
+        assert!(is_pin_entry(app_name));
+        assert_eq!(get_entry_record(app_name).unwrap().policy, None);
+
+        cleanup_test_password(app_name);
+    }
+
     #[test]
     fn test_update_password_regenerate_not_found() {
-        let result = update_password_regenerate("non_existent_regen_app", None);
+        let _store = scoped_store();
+        let result = update_password_regenerate("non_existent_regen_app", None, None, None);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_generate_memorizable_password() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_memo_pw_app";
         cleanup_test_password(app_name);
         
-        let result = generate_memorizable_password(app_name);
+        let result = generate_memorizable_password(app_name, None);
         assert!(result.is_ok());
         
         let password = get_from_keyring(app_name).unwrap();
+        assert_eq!(password, result.unwrap().text);
         
-        // Should have 3 words separated by dashes
+        // Should have 6 words separated by dashes
         let parts: Vec<&str> = password.split('-').collect();
-        assert_eq!(parts.len(), 3);
+        assert_eq!(parts.len(), 6);
         
         // Each part should not be empty
         for part in parts {
@@ -411,17 +953,24 @@ mod tests {
         }
         
         cleanup_test_password(app_name);
+
+        let settings = PassphraseSettings { words: 4, separator: ".".to_string(), ..PassphraseSettings::default() };
+        let passphrase = generate_memorizable_password(app_name, Some(&settings)).unwrap();
+        assert_eq!(get_from_keyring(app_name).unwrap().split('.').count(), 4);
+        assert!(passphrase.entropy > 51.0);
+
+        cleanup_test_password(app_name);
     }
 
     #[test]
     fn test_generate_memorizable_password_already_exists() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_memo_pw_exists";
         cleanup_test_password(app_name);
         
-        generate_memorizable_password(app_name).unwrap();
+        generate_memorizable_password(app_name, None).unwrap();
         
-        let result = generate_memorizable_password(app_name);
+        let result = generate_memorizable_password(app_name, None);
         assert!(result.is_err());
         
         cleanup_test_password(app_name);
@@ -432,11 +981,11 @@ mod tests {
 
     #[test]
     fn test_generated_password_is_alphanumeric() {
-        skip_if_no_keyring!();
+        let _store = scoped_store();
         let app_name = "test_alphanum_pw";
         cleanup_test_password(app_name);
         
-        generate_save_safety_password(app_name, Some(50)).unwrap();
+        generate_save_safety_password(app_name, Some(50), None, None).unwrap();
         
         let password = get_from_keyring(app_name).unwrap();
         assert!(password.chars().all(|c| c.is_alphanumeric()));
@@ -446,61 +995,136 @@ mod tests {
 
     #[test]
     fn test_export_import_passwords_roundtrip() {
-        skip_if_no_keyring!();
-        // Use unique name to avoid conflicts with other tests
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
-        let app_name = format!("test_export_import_app_{}", nanos);
-        let test_file = format!("test_export_temp_{}.csv", nanos);
+        let _store = scoped_store();
+        let app_name = "test_export_import_app";
+        let test_file = std::env::temp_dir().join(format!("test_export_roundtrip_{}.csv", std::process::id()));
+        let test_file = test_file.to_str().unwrap();
         let test_password = "export_test_pwd";
-        
-        cleanup_test_password(&app_name);
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        
-        // Create a password
-        let save_result = save_to_keyring(&app_name, test_password);
-        assert!(save_result.is_ok(), "Failed to save password: {:?}", save_result);
-        
-        // Small delay to ensure keyring is updated
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        
-        // Export
-        let export_result = export_passwords(&test_file);
+
+        save_to_keyring(app_name, test_password).unwrap();
+        save_to_keyring("test_export_import_other", "other_pwd").unwrap();
+
+        let export_result = export_passwords(test_file);
         assert!(export_result.is_ok(), "Failed to export passwords: {:?}", export_result);
-        
-        // Delete original
-        let delete_result = delete_password(&app_name);
+
+        let delete_result = delete_password(app_name);
         assert!(delete_result.is_ok(), "Failed to delete password: {:?}", delete_result);
-        
-        // Small delay to ensure deletion is complete
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        
-        // Verify password was deleted
-        let check_deleted = get_from_keyring(&app_name);
-        assert!(check_deleted.is_err(), "Password should be deleted before import");
-        
-        // Import
-        let import_result = import_passwords(&test_file);
+        assert!(get_from_keyring(app_name).is_err(), "Password should be deleted before import");
+
+        let import_result = import_passwords(test_file);
         assert!(import_result.is_ok(), "Failed to import passwords: {:?}", import_result);
-        
-        // Small delay to ensure import is complete
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        
-        // Verify imported with retry logic
-        let mut retrieved_ok = false;
-        for _ in 0..5 {
-            let retrieved = get_from_keyring(&app_name);
-            if retrieved.is_ok() {
-                assert_eq!(retrieved.unwrap(), test_password);
-                retrieved_ok = true;
-                break;
+        assert_eq!(get_from_keyring(app_name).unwrap(), test_password);
+        assert_eq!(get_from_keyring("test_export_import_other").unwrap(), "other_pwd");
+
+        let _ = std::fs::remove_file(test_file);
+    }
+
+    #[test]
+    fn test_export_import_preserves_entry_details() {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let _store = scoped_store();
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let app_name = format!("test_export_details_{}", nanos);
+        let test_file = std::env::temp_dir().join(format!("test_export_details_{}.csv", nanos));
+        let test_file = test_file.to_str().unwrap();
+
+        save_to_keyring(&app_name, "pass,with\"quotes").unwrap();
+        let changes = EntryChanges {
+            username: Some("alice".to_string()),
+            add_urls: vec!["https://a.example".to_string(), "https://b.example".to_string()],
+            notes: Some("multi-line\nnotes, with commas".to_string()),
+            set_fields: vec![("pin".to_string(), "12=34".to_string())],
+            add_tags: vec!["work".to_string(), "mail".to_string()],
+            ..Default::default()
+        };
+        let record = edit_entry(&app_name, &changes).unwrap();
+
+        export_passwords(test_file).unwrap();
+        delete_password(&app_name).unwrap();
+        import_passwords(test_file).unwrap();
+
+        assert_eq!(get_from_keyring(&app_name).unwrap(), "pass,with\"quotes");
+        assert_eq!(get_entry_record(&app_name).unwrap(), record);
+
+        cleanup_test_password(&app_name);
+        let _ = std::fs::remove_file(test_file);
+    }
+
+    #[test]
+    fn test_import_legacy_format() {
+        let _store = scoped_store();
+        let test_file = std::env::temp_dir().join("test_import_legacy_format.csv");
+        std::fs::write(&test_file, "legacy_import_app, legacy_pwd\nmalformed line\n").unwrap();
+
+        import_passwords(test_file.to_str().unwrap()).unwrap();
+        assert_eq!(get_from_keyring("legacy_import_app").unwrap(), "legacy_pwd");
+        assert_eq!(crate::app::keyring::get_password_type("legacy_import_app").as_deref(), Some("custom"));
+
+        cleanup_test_password("legacy_import_app");
+        let _ = std::fs::remove_file(test_file);
+    }
+
+    #[test]
+    fn test_import_with_invalid_name_saves_nothing() {
+        let _store = scoped_store();
+        let test_file = std::env::temp_dir().join("test_import_invalid_name.csv");
+        std::fs::write(&test_file, "name,password\ninvalid_import_first,pw1\npassword_length,8\n").unwrap();
+
+        let result = import_passwords(test_file.to_str().unwrap());
+        match result {
+            Err(AppError::Parse { context, message }) => {
+                assert!(context.ends_with(":3"), "{}", context);
+                assert!(message.contains("reserved"), "{}", message);
             }
-            std::thread::sleep(std::time::Duration::from_millis(100));
+            other => panic!("expected a parse error, got {:?}", other),
         }
-        assert!(retrieved_ok, "Failed to retrieve imported password after multiple attempts");
-        
-        // Cleanup
-        cleanup_test_password(&app_name);
-        let _ = std::fs::remove_file(&test_file);
+        assert!(matches!(get_from_keyring("invalid_import_first"), Err(AppError::NotFound(_))));
+
+        let _ = std::fs::remove_file(test_file);
+    }
+
+    #[test]
+    fn test_import_missing_file_is_io_error() {
+        let _store = scoped_store();
+        let result = import_passwords("/nonexistent/dir/apppass_import.csv");
+        assert!(matches!(result, Err(AppError::Io { .. })));
+    }
+
+    #[test]
+    fn test_export_to_unwritable_path_is_io_error() {
+        let _store = scoped_store();
+        let app_name = "test_export_unwritable_app";
+        save_to_keyring(app_name, "pw").unwrap();
+        let result = export_passwords("/nonexistent/dir/apppass_export.csv");
+        assert!(matches!(result, Err(AppError::Io { .. })));
+        cleanup_test_password(app_name);
+    }
+
+    #[test]
+    fn test_edit_entry_missing_app() {
+        let _store = scoped_store();
+        let result = edit_entry("non_existent_edit_app", &EntryChanges::default());
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_restore_password_after_update() {
+        let _store = scoped_store();
+        let app_name = "test_restore_after_update";
+        cleanup_test_password(app_name);
+        save_to_keyring(app_name, "original").unwrap();
+
+        update_password(app_name, "rotated").unwrap();
+        let restored = restore_password(app_name, 1).unwrap();
+        assert_eq!(restored, "original");
+        assert_eq!(get_from_keyring(app_name).unwrap(), "original");
+
+        // The rotated password is now the most recent history entry, so the restore can be undone
+        let history = get_history(app_name).unwrap();
+        assert_eq!(history[0].password, "rotated");
+        assert_eq!(history[0].source, "restore");
+        assert!(restore_password(app_name, 10).is_err());
+
+        cleanup_test_password(app_name);
     }
 }
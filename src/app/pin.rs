@@ -0,0 +1,274 @@
+use rand::Rng;
+use std::collections::HashSet;
+use crate::app::error::{AppError, Result};
+use crate::app::rng::with_rng;
+
+/// Number of digits of a PIN when the configuration does not set one.
+pub const DEFAULT_PIN_LENGTH: usize = 6;
+
+/// Range of PIN lengths accepted.
+pub const PIN_LENGTH_RANGE: std::ops::RangeInclusive<usize> = 4..=12;
+
+/// PINs people pick most often that are not caught by the other checks: keypad shapes,
+/// words spelled on the keypad and mirrored halves.
+const COMMON_PINS: [&str; 24] = [
+    "0258", "0852", "1004", "1122", "1379", "1397", "1470", "1793", "1973", "2580", "3690", "3971", "5683", "7378",
+    "112233", "123321", "147258", "147852", "159357", "159753", "258456", "789456", "852456", "12344321",
+];
+
+/// Days in each month, counting February 29.
+const DAYS_IN_MONTH: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// Oldest and newest years a four-digit year in a PIN is taken for.
+const YEARS: std::ops::RangeInclusive<u32> = 1900..=2099;
+
+/// Ways a PIN can be laid out as a date, by length: `D` day, `M` month, `Y` year digits.
+const DATE_FORMATS: [&str; 10] = [
+    "MMDD", "DDMM", "YYYY", "DDMMYY", "MMDDYY", "YYMMDD", "MMYYYY", "DDMMYYYY", "MMDDYYYY", "YYYYMMDD",
+];
+
+/// Returns why `pin` is easy to guess, or `None` if it is not: a common PIN, digits
+/// repeating a shorter block (`1111`, `1212`), a sequence with a constant step (`1234`,
+/// `9753`, `7890`) or a date (`0612`, `1987`, `250890`).
+///
+/// # Arguments
+///
+/// * `pin` - The PIN to check.
+pub fn pin_weakness(pin: &str) -> Option<&'static str> {
+    if !pin.bytes().all(|b| b.is_ascii_digit()) {
+        return Some("is not made of digits only");
+    }
+    let digits: Vec<u8> = pin.bytes().map(|b| b - b'0').collect();
+    if is_periodic(&digits) {
+        Some("repeats digits")
+    } else if is_sequence(&digits) {
+        Some("is a sequence")
+    } else if COMMON_PINS.contains(&pin) {
+        Some("is a common PIN")
+    } else if DATE_FORMATS.iter().any(|format| is_date(format, &digits)) {
+        Some("looks like a date")
+    } else {
+        None
+    }
+}
+
+/// Generates a random PIN that `pin_weakness` accepts.
+///
+/// # Arguments
+///
+/// * `length` - Number of digits, within `PIN_LENGTH_RANGE`.
+///
+/// # Returns
+///
+/// * `Result<String>` - The PIN, or `AppError::InvalidInput` if the length is out of range.
+pub fn generate_pin(length: usize) -> Result<String> {
+    with_rng(|rng| generate_pin_with(length, rng))
+}
+
+/// Generates a PIN like `generate_pin`, drawing randomness from `rng`.
+pub fn generate_pin_with<R: Rng + ?Sized>(length: usize, rng: &mut R) -> Result<String> {
+    validate_pin_length(length)?;
+    // At most about one PIN in ten is rejected, so this ends quickly
+    loop {
+        let pin: String = (0..length).map(|_| char::from(b'0' + rng.gen_range(0..10u8))).collect();
+        if pin_weakness(&pin).is_none() {
+            return Ok(pin);
+        }
+    }
+}
+
+/// Checks that `length` is within `PIN_LENGTH_RANGE`.
+pub fn validate_pin_length(length: usize) -> Result<()> {
+    if PIN_LENGTH_RANGE.contains(&length) {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "PIN length must be between {} and {}",
+            PIN_LENGTH_RANGE.start(),
+            PIN_LENGTH_RANGE.end()
+        )))
+    }
+}
+
+/// Entropy, in bits, of the PINs `generate_pin` produces: the number of digits minus the
+/// PINs `pin_weakness` rejects.
+pub fn pin_entropy(length: usize) -> f64 {
+    let total = 10u64.pow(length as u32);
+    // PINs repeating a shorter block: all of them minus those that do not (primitive words)
+    let primitive: i64 = divisors(length)
+        .into_iter()
+        .map(|d| mobius(length / d) * 10i64.pow(d as u32))
+        .sum();
+    let periodic = total - primitive as u64;
+
+    // The other weak PINs, without counting the periodic ones twice
+    let mut others: HashSet<String> = HashSet::new();
+    for start in 0..10u8 {
+        for step in 1..10u8 {
+            others.insert((0..length).map(|i| char::from(b'0' + (start + step * i as u8) % 10)).collect());
+        }
+    }
+    others.extend(COMMON_PINS.iter().filter(|pin| pin.len() == length).map(|pin| pin.to_string()));
+    for format in DATE_FORMATS.iter().filter(|format| format.len() == length) {
+        others.extend(dates(format));
+    }
+    let others = others
+        .iter()
+        .filter(|pin| !is_periodic(&pin.bytes().map(|b| b - b'0').collect::<Vec<u8>>()))
+        .count() as u64;
+
+    ((total - periodic - others) as f64).log2()
+}
+
+/// True if `digits` repeat a shorter block.
+fn is_periodic(digits: &[u8]) -> bool {
+    let n = digits.len();
+    (1..n).filter(|p| n.is_multiple_of(*p)).any(|p| (p..n).all(|i| digits[i] == digits[i - p]))
+}
+
+/// True if every digit is the previous one plus the same step, wrapping after 9.
+fn is_sequence(digits: &[u8]) -> bool {
+    let step = |i: usize| (digits[i] + 10 - digits[i - 1]) % 10;
+    digits.len() > 1 && (2..digits.len()).all(|i| step(i) == step(1))
+}
+
+/// True if `digits` read as a valid date in `format` (see `DATE_FORMATS`).
+fn is_date(format: &str, digits: &[u8]) -> bool {
+    if format.len() != digits.len() {
+        return false;
+    }
+    let field = |c: char| {
+        format
+            .char_indices()
+            .filter(|(_, f)| *f == c)
+            .fold(None, |value: Option<u32>, (i, _)| Some(value.unwrap_or(0) * 10 + digits[i] as u32))
+    };
+    let month = field('M');
+    let valid_day = match (field('D'), month) {
+        (Some(day), Some(month)) => (1..=12).contains(&month) && (1..=DAYS_IN_MONTH[month as usize - 1]).contains(&day),
+        (None, Some(month)) => (1..=12).contains(&month),
+        _ => true,
+    };
+    let valid_year = match field('Y') {
+        Some(year) if format.matches('Y').count() == 4 => YEARS.contains(&year),
+        _ => true,
+    };
+    valid_day && valid_year
+}
+
+/// Every PIN `is_date` accepts for `format`, possibly more than once.
+fn dates(format: &str) -> Vec<String> {
+    let years: Vec<u32> = match format.matches('Y').count() {
+        4 => YEARS.collect(),
+        2 => (0..100).collect(),
+        _ => vec![0],
+    };
+    let mut pins = Vec::new();
+    for month in 1..=12u32 {
+        let days = if format.contains('D') { 1..=DAYS_IN_MONTH[month as usize - 1] } else { 1..=1 };
+        for day in days {
+            for &year in &years {
+                let mut fields = [('D', day), ('M', month), ('Y', year)];
+                let mut pin: Vec<char> = format.chars().collect();
+                // Fill each field from its last digit
+                for (c, value) in fields.iter_mut() {
+                    for slot in pin.iter_mut().rev().filter(|slot| **slot == *c) {
+                        *slot = char::from(b'0' + (*value % 10) as u8);
+                        *value /= 10;
+                    }
+                }
+                pins.push(pin.into_iter().collect());
+            }
+        }
+    }
+    pins
+}
+
+/// Divisors of `n`.
+fn divisors(n: usize) -> Vec<usize> {
+    (1..=n).filter(|d| n.is_multiple_of(*d)).collect()
+}
+
+/// The Möbius function: 0 if `n` has a squared prime factor, otherwise -1 to the number of
+/// its prime factors.
+fn mobius(mut n: usize) -> i64 {
+    let mut result = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
+            }
+            result = -result;
+        }
+        p += 1;
+    }
+    if n > 1 {
+        result = -result;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    proptest! {
+        #[test]
+        fn prop_generated_pins_are_not_weak(length in PIN_LENGTH_RANGE, seed in any::<u64>()) {
+            let pin = generate_pin_with(length, &mut ChaCha20Rng::seed_from_u64(seed)).unwrap();
+            prop_assert_eq!(pin.len(), length);
+            prop_assert_eq!(pin_weakness(&pin), None, "{}", pin);
+        }
+    }
+
+    #[test]
+    fn test_weak_pins() {
+        for (pin, reason) in [
+            ("0000", "repeats digits"),
+            ("1212", "repeats digits"),
+            ("123123", "repeats digits"),
+            ("1234", "is a sequence"),
+            ("8642", "is a sequence"),
+            ("7890", "is a sequence"),
+            ("2580", "is a common PIN"),
+            ("0612", "looks like a date"),
+            ("3112", "looks like a date"),
+            ("1987", "looks like a date"),
+            ("250890", "looks like a date"),
+            ("19870614", "looks like a date"),
+        ] {
+            assert_eq!(pin_weakness(pin), Some(reason), "{}", pin);
+        }
+        for pin in ["7394", "3261", "3002", "481736", "58203917"] {
+            assert_eq!(pin_weakness(pin), None, "{}", pin);
+        }
+    }
+
+    #[test]
+    fn test_generate_pin() {
+        for length in PIN_LENGTH_RANGE {
+            let pin = generate_pin(length).unwrap();
+            assert_eq!(pin.len(), length);
+            assert!(pin.chars().all(|c| c.is_ascii_digit()));
+            assert_eq!(pin_weakness(&pin), None, "{}", pin);
+        }
+        assert!(matches!(generate_pin(3), Err(AppError::InvalidInput(_))));
+        assert!(matches!(generate_pin(13), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_pin_entropy_counts_accepted_pins() {
+        let accepted = (0..10_000u32)
+            .map(|n| format!("{:04}", n))
+            .filter(|pin| pin_weakness(pin).is_none())
+            .count();
+        assert!((pin_entropy(4) - (accepted as f64).log2()).abs() < 1e-9);
+        assert!(pin_entropy(6) < 6.0 * 10f64.log2());
+        assert!(pin_entropy(6) > 19.8);
+    }
+}
@@ -0,0 +1,612 @@
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use crate::app::error::{AppError, Result};
+use crate::app::rng::with_rng;
+use crate::app::template::Template;
+
+/// Symbols used by the `symbols` class when no custom set is configured.
+pub const DEFAULT_SYMBOLS: &str = "!#$%&()*+,-./:;<=>?@[]^_{|}~";
+
+/// Characters that are easily confused with each other, left out with `exclude_ambiguous`.
+pub const AMBIGUOUS_CHARACTERS: &str = "0O1lI";
+
+/// How many times the generator reshuffles before giving up on `no_repeats`.
+const MAX_ATTEMPTS: usize = 100;
+
+/// A class of characters generated passwords are made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CharClass {
+    /// Lowercase ASCII letters.
+    Lower,
+    /// Uppercase ASCII letters.
+    Upper,
+    /// ASCII digits.
+    Digits,
+    /// The policy's symbol set.
+    Symbols,
+}
+
+impl CharClass {
+    /// Every class, in the order they are listed.
+    pub const ALL: [CharClass; 4] = [CharClass::Lower, CharClass::Upper, CharClass::Digits, CharClass::Symbols];
+
+    /// All class names accepted in the configuration and on the command line.
+    pub const NAMES: [&'static str; 4] = ["lower", "upper", "digits", "symbols"];
+
+    /// Parses a comma-separated list of class names (e.g. `lower,digits`).
+    pub fn parse_list(list: &str) -> std::result::Result<Vec<CharClass>, String> {
+        let mut classes = Vec::new();
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let class: CharClass = name.parse()?;
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        Ok(classes)
+    }
+
+    /// Formats classes as a comma-separated list, the inverse of `parse_list`.
+    pub fn join(classes: &[CharClass]) -> String {
+        classes.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(",")
+    }
+}
+
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CharClass::Lower => "lower",
+            CharClass::Upper => "upper",
+            CharClass::Digits => "digits",
+            CharClass::Symbols => "symbols",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for CharClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "lower" => Ok(CharClass::Lower),
+            "upper" => Ok(CharClass::Upper),
+            "digits" => Ok(CharClass::Digits),
+            "symbols" => Ok(CharClass::Symbols),
+            other => Err(format!(
+                "Unknown character class '{}'. Expected one of: {}",
+                other,
+                CharClass::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Rules a generated password must follow: which character classes it draws from, how
+/// many characters of each class it needs at least, and which characters it must avoid.
+///
+/// The default policy uses letters and digits with no further constraint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    /// Classes the password draws from.
+    pub classes: Vec<CharClass>,
+    /// Minimum number of lowercase letters.
+    pub min_lower: usize,
+    /// Minimum number of uppercase letters.
+    pub min_upper: usize,
+    /// Minimum number of digits.
+    pub min_digits: usize,
+    /// Minimum number of symbols.
+    pub min_symbols: usize,
+    /// Characters of the `symbols` class.
+    pub symbols: String,
+    /// Characters never used.
+    pub exclude: String,
+    /// Leave out `AMBIGUOUS_CHARACTERS`.
+    pub exclude_ambiguous: bool,
+    /// Never put the same character twice in a row.
+    pub no_repeats: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            classes: vec![CharClass::Lower, CharClass::Upper, CharClass::Digits],
+            min_lower: 0,
+            min_upper: 0,
+            min_digits: 0,
+            min_symbols: 0,
+            symbols: DEFAULT_SYMBOLS.to_string(),
+            exclude: String::new(),
+            exclude_ambiguous: false,
+            no_repeats: false,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Every setting accepted by `get` and `set`, named as in the `[password]` configuration section.
+    pub const KEYS: [&'static str; 9] = [
+        "classes",
+        "min_lower",
+        "min_upper",
+        "min_digits",
+        "min_symbols",
+        "symbols",
+        "exclude",
+        "exclude_ambiguous",
+        "no_repeats",
+    ];
+
+    /// Returns the value of the setting `key` (one of `PasswordPolicy::KEYS`) as text.
+    pub fn get(&self, key: &str) -> Option<String> {
+        Some(match key {
+            "classes" => CharClass::join(&self.classes),
+            "min_lower" => self.min_lower.to_string(),
+            "min_upper" => self.min_upper.to_string(),
+            "min_digits" => self.min_digits.to_string(),
+            "min_symbols" => self.min_symbols.to_string(),
+            "symbols" => self.symbols.clone(),
+            "exclude" => self.exclude.clone(),
+            "exclude_ambiguous" => self.exclude_ambiguous.to_string(),
+            "no_repeats" => self.no_repeats.to_string(),
+            _ => return None,
+        })
+    }
+
+    /// Parses `value` into the setting `key` (one of `PasswordPolicy::KEYS`). The policy as a
+    /// whole is not checked; call `validate` once every setting is applied.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Result<(), String>>` - `None` if the key is unknown, or the parse error.
+    pub fn set(&mut self, key: &str, value: &str) -> Option<std::result::Result<(), String>> {
+        fn parse<T: FromStr>(value: &str) -> std::result::Result<T, String>
+        where
+            T::Err: fmt::Display,
+        {
+            value.trim().parse().map_err(|e: T::Err| e.to_string())
+        }
+
+        let result = match key {
+            "classes" => CharClass::parse_list(value).map(|classes| self.classes = classes),
+            "min_lower" => parse(value).map(|min| self.min_lower = min),
+            "min_upper" => parse(value).map(|min| self.min_upper = min),
+            "min_digits" => parse(value).map(|min| self.min_digits = min),
+            "min_symbols" => parse(value).map(|min| self.min_symbols = min),
+            "symbols" => {
+                self.symbols = value.trim().to_string();
+                Ok(())
+            }
+            "exclude" => {
+                self.exclude = value.to_string();
+                Ok(())
+            }
+            "exclude_ambiguous" => parse(value).map(|on| self.exclude_ambiguous = on),
+            "no_repeats" => parse(value).map(|on| self.no_repeats = on),
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Returns the minimum number of characters of `class`.
+    pub fn min_count(&self, class: CharClass) -> usize {
+        match class {
+            CharClass::Lower => self.min_lower,
+            CharClass::Upper => self.min_upper,
+            CharClass::Digits => self.min_digits,
+            CharClass::Symbols => self.min_symbols,
+        }
+    }
+
+    /// Sets the minimum number of characters of `class`, adding the class if it is not used yet.
+    pub fn require(&mut self, class: CharClass, min: usize) {
+        match class {
+            CharClass::Lower => self.min_lower = min,
+            CharClass::Upper => self.min_upper = min,
+            CharClass::Digits => self.min_digits = min,
+            CharClass::Symbols => self.min_symbols = min,
+        }
+        if min > 0 {
+            self.enable(class);
+        }
+    }
+
+    /// Adds `class` to the classes the password draws from.
+    pub fn enable(&mut self, class: CharClass) {
+        if !self.classes.contains(&class) {
+            self.classes.push(class);
+        }
+    }
+
+    /// Returns the characters of `class` left after the exclusions.
+    pub fn alphabet(&self, class: CharClass) -> Vec<char> {
+        let mut characters: Vec<char> = match class {
+            CharClass::Lower => ('a'..='z').collect(),
+            CharClass::Upper => ('A'..='Z').collect(),
+            CharClass::Digits => ('0'..='9').collect(),
+            CharClass::Symbols => self.symbols.chars().collect(),
+        };
+        characters.sort_unstable();
+        characters.dedup();
+        characters.retain(|c| !self.is_excluded(*c));
+        characters
+    }
+
+    /// Returns every character the password may contain.
+    pub fn characters(&self) -> Vec<char> {
+        let mut characters: Vec<char> = self.classes.iter().flat_map(|class| self.alphabet(*class)).collect();
+        characters.sort_unstable();
+        characters.dedup();
+        characters
+    }
+
+    /// Whether `c` is left out by `exclude` or `exclude_ambiguous`.
+    fn is_excluded(&self, c: char) -> bool {
+        self.exclude.contains(c) || (self.exclude_ambiguous && AMBIGUOUS_CHARACTERS.contains(c))
+    }
+
+    /// Checks that a password of `length` characters can satisfy the policy.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - A description of the first problem found.
+    pub fn validate(&self, length: usize) -> std::result::Result<(), String> {
+        if self.classes.is_empty() {
+            return Err("at least one character class is required".to_string());
+        }
+        if let Some(c) = self.symbols.chars().find(|c| !c.is_ascii_punctuation()) {
+            return Err(format!("'{}' is not an ASCII symbol", c));
+        }
+        for class in CharClass::ALL {
+            let enabled = self.classes.contains(&class);
+            if !enabled && self.min_count(class) > 0 {
+                return Err(format!("min_{} is set but the {} class is not used", class, class));
+            }
+            if enabled && self.alphabet(class).is_empty() {
+                return Err(format!("no {} characters are left after the exclusions", class));
+            }
+        }
+        let required: usize = CharClass::ALL.iter().map(|c| self.min_count(*c)).sum();
+        if required > length {
+            return Err(format!(
+                "the minimum counts add up to {} characters, more than the length of {}",
+                required, length
+            ));
+        }
+        if self.no_repeats && length > 1 && self.characters().len() < 2 {
+            return Err("no_repeats needs at least two different characters".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether `password` follows the policy (ignoring its length).
+    pub fn is_satisfied_by(&self, password: &str) -> bool {
+        let allowed = self.characters();
+        let chars: Vec<char> = password.chars().collect();
+        chars.iter().all(|c| allowed.contains(c))
+            && CharClass::ALL.iter().all(|class| {
+                let alphabet = self.alphabet(*class);
+                chars.iter().filter(|c| alphabet.contains(c)).count() >= self.min_count(*class)
+            })
+            && !(self.no_repeats && chars.windows(2).any(|w| w[0] == w[1]))
+    }
+
+    /// Estimated entropy, in bits, of a password of `length` characters generated with the
+    /// policy: every character is drawn from `characters()` (minus the previous one with
+    /// `no_repeats`). Minimum counts remove a few combinations, which is ignored.
+    pub fn entropy(&self, length: usize) -> f64 {
+        let choices = self.characters().len() as f64;
+        match length {
+            0 => 0.0,
+            _ if self.no_repeats => choices.log2() + (length - 1) as f64 * (choices - 1.0).max(1.0).log2(),
+            _ => length as f64 * choices.log2(),
+        }
+    }
+
+    /// Generates a password of `length` characters following the policy.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The password, or `AppError::InvalidInput` if the policy cannot be
+    ///   satisfied at this length.
+    pub fn generate(&self, length: usize) -> Result<String> {
+        with_rng(|rng| self.generate_with(length, rng))
+    }
+
+    /// Generates a password like `generate`, drawing randomness from `rng`.
+    pub fn generate_with<R: Rng + ?Sized>(&self, length: usize, rng: &mut R) -> Result<String> {
+        self.validate(length)
+            .map_err(|e| AppError::InvalidInput(format!("Password policy: {}", e)))?;
+
+        // One slot per required character, the rest drawn from every allowed character.
+        let mut slots: Vec<Option<CharClass>> = CharClass::ALL
+            .iter()
+            .flat_map(|class| std::iter::repeat_n(Some(*class), self.min_count(*class)))
+            .collect();
+        slots.resize(length, None);
+        let alphabets: Vec<Vec<char>> = CharClass::ALL.iter().map(|class| self.alphabet(*class)).collect();
+        let any = self.characters();
+
+        'attempt: for _ in 0..MAX_ATTEMPTS {
+            slots.shuffle(rng);
+            let mut password = String::with_capacity(length);
+            let mut previous = None;
+            for slot in &slots {
+                let alphabet = match slot {
+                    Some(class) => &alphabets[*class as usize],
+                    None => &any,
+                };
+                let c = if self.no_repeats {
+                    let candidates: Vec<char> = alphabet.iter().copied().filter(|c| Some(*c) != previous).collect();
+                    match candidates.choose(rng) {
+                        Some(c) => *c,
+                        None => continue 'attempt,
+                    }
+                } else {
+                    *alphabet.choose(rng).expect("validated alphabets are not empty")
+                };
+                password.push(c);
+                previous = Some(c);
+            }
+            return Ok(password);
+        }
+        Err(AppError::InvalidInput(
+            "Password policy: could not avoid repeated characters with these classes".to_string(),
+        ))
+    }
+}
+
+/// The length and policy an entry's password is generated with. Stored with the entry, so
+/// regenerating the password produces one the site still accepts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryPolicy {
+    /// Number of characters.
+    pub length: usize,
+    /// Rules the characters follow.
+    pub policy: PasswordPolicy,
+    /// Fixed format (see `Template`) used instead of the length and policy, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+impl EntryPolicy {
+    /// Generation from `template`. `policy` is kept so that it applies again if the
+    /// template is dropped.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<EntryPolicy>` - The generation, or `AppError::InvalidInput` if the template is malformed.
+    pub fn from_template(template: &str, policy: PasswordPolicy) -> Result<Self> {
+        let parsed = Template::parse(template)?;
+        Ok(Self {
+            length: parsed.len(),
+            policy,
+            template: Some(template.to_string()),
+        })
+    }
+
+    /// Checks that the template is well formed, or that the policy can be satisfied at the
+    /// stored length.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if let Some(template) = &self.template {
+            return Template::parse(template).map(|_| ()).map_err(|e| e.to_string());
+        }
+        if self.length == 0 {
+            return Err("the length must be at least 1".to_string());
+        }
+        self.policy.validate(self.length)
+    }
+
+    /// Generates a password from the template, or following the policy.
+    pub fn generate(&self) -> Result<String> {
+        with_rng(|rng| self.generate_with(rng))
+    }
+
+    /// Generates a password like `generate`, drawing randomness from `rng`.
+    pub fn generate_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<String> {
+        match &self.template {
+            Some(template) => Ok(Template::parse(template)?.generate_with(rng)),
+            None => self.policy.generate_with(self.length, rng),
+        }
+    }
+
+    /// Estimated entropy, in bits, of a generated password.
+    pub fn entropy(&self) -> f64 {
+        match self.template.as_deref().map(Template::parse) {
+            Some(Ok(template)) => template.entropy(),
+            Some(Err(_)) => 0.0,
+            None => self.policy.entropy(self.length),
+        }
+    }
+
+    /// Returns every setting as `(key, value)` pairs: `length` followed by `template` if there
+    /// is one, otherwise by `PasswordPolicy::KEYS`.
+    pub fn settings(&self) -> Vec<(&'static str, String)> {
+        let mut settings = vec![("length", self.length.to_string())];
+        match &self.template {
+            Some(template) => settings.push(("template", template.clone())),
+            None => {
+                for key in PasswordPolicy::KEYS {
+                    settings.push((key, self.policy.get(key).unwrap_or_default()));
+                }
+            }
+        }
+        settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    /// Policies with any classes, minimum counts, exclusions and options, valid or not.
+    fn any_policy() -> impl Strategy<Value = PasswordPolicy> {
+        (
+            proptest::sample::subsequence(CharClass::ALL.to_vec(), 0..=4),
+            [0..4usize, 0..4usize, 0..4usize, 0..4usize],
+            "[!@#$%^&*]{0,4}",
+            "[a-zA-Z0-9!@#]{0,12}",
+            any::<bool>(),
+            any::<bool>(),
+        )
+            .prop_map(|(classes, [min_lower, min_upper, min_digits, min_symbols], symbols, exclude, exclude_ambiguous, no_repeats)| {
+                PasswordPolicy {
+                    classes,
+                    min_lower,
+                    min_upper,
+                    min_digits,
+                    min_symbols,
+                    symbols,
+                    exclude,
+                    exclude_ambiguous,
+                    no_repeats,
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_generated_passwords_follow_the_policy(policy in any_policy(), length in 1..48usize, seed in any::<u64>()) {
+            let result = policy.generate_with(length, &mut ChaCha20Rng::seed_from_u64(seed));
+            match (policy.validate(length), result) {
+                (Ok(()), Ok(password)) => {
+                    prop_assert_eq!(password.chars().count(), length);
+                    prop_assert!(policy.is_satisfied_by(&password), "{}", password);
+                }
+                // Required characters from a one-character alphabet may not fit apart
+                (Ok(()), Err(AppError::InvalidInput(_))) => prop_assert!(policy.no_repeats),
+                (Err(_), result) => prop_assert!(matches!(result, Err(AppError::InvalidInput(_)))),
+                (Ok(()), Err(e)) => prop_assert!(false, "{}", e),
+            }
+        }
+    }
+
+    proptest! {
+        // Each case draws 12400 characters
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn prop_characters_are_uniform(seed in any::<u64>()) {
+            // Without minimum counts every character is drawn from the same alphabet
+            let policy = PasswordPolicy::default();
+            let alphabet = policy.characters();
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let mut counts = vec![0usize; alphabet.len()];
+            for _ in 0..200 {
+                for c in policy.generate_with(alphabet.len(), &mut rng).unwrap().chars() {
+                    counts[alphabet.iter().position(|a| *a == c).unwrap()] += 1;
+                }
+            }
+            // Chi-squared with 61 degrees of freedom: above 130 has a probability below 1e-6
+            let expected = 200.0;
+            let chi_squared: f64 = counts.iter().map(|n| (*n as f64 - expected).powi(2) / expected).sum();
+            prop_assert_eq!(alphabet.len(), 62);
+            prop_assert!(chi_squared < 130.0, "chi-squared {} for {:?}", chi_squared, counts);
+        }
+    }
+
+    #[test]
+    fn test_default_policy_is_alphanumeric() {
+        let policy = PasswordPolicy::default();
+        let password = policy.generate(64).unwrap();
+        assert_eq!(password.len(), 64);
+        assert!(password.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert!(policy.is_satisfied_by(&password));
+    }
+
+    #[test]
+    fn test_minimum_counts_and_symbols() {
+        let mut policy = PasswordPolicy::default();
+        policy.require(CharClass::Symbols, 3);
+        policy.require(CharClass::Digits, 2);
+        policy.symbols = "@#".to_string();
+        assert!(policy.classes.contains(&CharClass::Symbols));
+
+        for _ in 0..50 {
+            let password = policy.generate(8).unwrap();
+            assert!(password.chars().filter(|c| "@#".contains(*c)).count() >= 3, "{}", password);
+            assert!(password.chars().filter(|c| c.is_ascii_digit()).count() >= 2, "{}", password);
+            assert!(password.chars().all(|c| c.is_ascii_alphanumeric() || "@#".contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_exclusions_and_no_repeats() {
+        let policy = PasswordPolicy {
+            classes: vec![CharClass::Digits],
+            exclude: "2345678".to_string(),
+            exclude_ambiguous: true,
+            no_repeats: true,
+            ..PasswordPolicy::default()
+        };
+        assert_eq!(policy.characters(), vec!['9']);
+        assert!(policy.validate(10).is_err());
+
+        let policy = PasswordPolicy { exclude: "234567".to_string(), ..policy };
+        let password = policy.generate(128).unwrap();
+        assert!(password.chars().all(|c| c == '8' || c == '9'));
+        assert!(password.as_bytes().windows(2).all(|w| w[0] != w[1]), "{}", password);
+    }
+
+    #[test]
+    fn test_invalid_policies() {
+        let policy = PasswordPolicy { min_symbols: 1, ..PasswordPolicy::default() };
+        assert!(policy.validate(30).is_err(), "min for an unused class");
+
+        let mut policy = PasswordPolicy::default();
+        policy.require(CharClass::Upper, 20);
+        policy.require(CharClass::Lower, 20);
+        assert!(policy.validate(30).is_err(), "minimums longer than the password");
+        assert!(matches!(policy.generate(30), Err(AppError::InvalidInput(_))));
+        assert!(policy.validate(40).is_ok());
+
+        let policy = PasswordPolicy { symbols: "a!".to_string(), ..PasswordPolicy::default() };
+        assert!(policy.validate(30).is_err(), "letters are not symbols");
+
+        let policy = PasswordPolicy { classes: Vec::new(), ..PasswordPolicy::default() };
+        assert!(policy.validate(30).is_err());
+    }
+
+    #[test]
+    fn test_entropy() {
+        let policy = PasswordPolicy { classes: vec![CharClass::Digits], ..PasswordPolicy::default() };
+        assert!((policy.entropy(10) - 10.0 * 10f64.log2()).abs() < 1e-9);
+        let policy = PasswordPolicy { no_repeats: true, ..policy };
+        assert!((policy.entropy(10) - (10f64.log2() + 9.0 * 9f64.log2())).abs() < 1e-9);
+        assert_eq!(policy.entropy(0), 0.0);
+    }
+
+    #[test]
+    fn test_parse_class_list() {
+        assert_eq!(
+            CharClass::parse_list("lower, digits,lower").unwrap(),
+            vec![CharClass::Lower, CharClass::Digits]
+        );
+        assert!(CharClass::parse_list("lower,emoji").is_err());
+        assert_eq!(CharClass::join(&CharClass::ALL), "lower,upper,digits,symbols");
+    }
+
+    #[test]
+    fn test_get_and_set_by_key() {
+        let mut policy = PasswordPolicy::default();
+        for key in PasswordPolicy::KEYS {
+            let value = policy.get(key).unwrap();
+            assert_eq!(policy.set(key, &value), Some(Ok(())), "{}", key);
+        }
+        assert_eq!(policy, PasswordPolicy::default());
+
+        assert_eq!(policy.set("min_digits", "3"), Some(Ok(())));
+        assert_eq!(policy.min_digits, 3);
+        assert!(matches!(policy.set("no_repeats", "maybe"), Some(Err(_))));
+        assert_eq!(policy.set("length", "3"), None);
+        assert_eq!(policy.get("length"), None);
+    }
+}
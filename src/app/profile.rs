@@ -0,0 +1,281 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use crate::app::config::{config, update_config};
+use crate::app::{APP_SERVICE, CONFIG_DIR_ENV_VAR, PROFILE_ENV_VAR};
+
+/// Name of the built-in profile, which uses the original `apppass` namespace.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Maximum length of a profile name.
+const MAX_PROFILE_NAME_LEN: usize = 32;
+
+/// The profile selected for this process.
+static ACTIVE_PROFILE: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new(DEFAULT_PROFILE.to_string()));
+
+/// The set of named profiles.
+///
+/// Stored as JSON in `<config dir>/profiles.json`. Profile names are not secret, so the
+/// registry lives outside the secret store and can be read before a backend is opened.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileRegistry {
+    /// Default profile written by older releases. The default now lives in the
+    /// configuration file (`default_profile`); this one is only used when that is unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Profiles created by the user. The built-in `default` profile is never listed here.
+    #[serde(default)]
+    pub profiles: BTreeSet<String>,
+}
+
+impl ProfileRegistry {
+    /// Loads the registry from `path`. A missing file is an empty registry.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data)
+                .map_err(|e| format!("Malformed profile registry '{}': {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read '{}': {}", path.display(), e)),
+        }
+    }
+
+    /// Writes the registry to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        let data = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, data).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+    }
+
+    /// Returns true if `name` is the built-in profile or a created one.
+    pub fn contains(&self, name: &str) -> bool {
+        name == DEFAULT_PROFILE || self.profiles.contains(name)
+    }
+
+    /// Returns the default profile: `default_profile` from the configuration, then the
+    /// default written by older releases, then `default`.
+    pub fn default_profile(&self) -> String {
+        config()
+            .default_profile
+            .or_else(|| self.default.clone())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+    }
+
+    /// Returns every profile, including the built-in one, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: BTreeSet<String> = self.profiles.clone();
+        names.insert(DEFAULT_PROFILE.to_string());
+        names.into_iter().collect()
+    }
+}
+
+/// Returns the directory holding apppass configuration files.
+///
+/// Uses `APPPASS_CONFIG_DIR` if set, otherwise `<config dir>/apppass`.
+pub fn config_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var(CONFIG_DIR_ENV_VAR) {
+        return PathBuf::from(dir);
+    }
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("apppass")
+}
+
+/// Returns the path of the profile registry.
+pub fn registry_path() -> PathBuf {
+    config_dir().join("profiles.json")
+}
+
+/// Checks that `name` can be used as a profile name.
+///
+/// # Arguments
+///
+/// * `name` - The profile name.
+///
+/// # Returns
+///
+/// * `Result<(), String>` - An error message if the name is empty, too long, or has characters other than letters, digits, `-` and `_`.
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_PROFILE_NAME_LEN {
+        return Err(format!(
+            "Profile name must be between 1 and {} characters",
+            MAX_PROFILE_NAME_LEN
+        ));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!(
+            "Invalid profile name '{}'. Use only letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the secret store namespace (service name) of a profile.
+///
+/// The `default` profile keeps the original `apppass` namespace so existing entries
+/// remain visible; other profiles use `apppass-<name>`.
+pub fn service_name(profile: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        APP_SERVICE.to_string()
+    } else {
+        format!("{}-{}", APP_SERVICE, profile)
+    }
+}
+
+/// Returns the profile selected for this process.
+pub fn active_profile() -> String {
+    ACTIVE_PROFILE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Sets the profile used by this process. Must be called before the store is initialized.
+pub fn set_active_profile(name: &str) {
+    *ACTIVE_PROFILE.write().unwrap_or_else(|e| e.into_inner()) = name.to_string();
+}
+
+/// Resolves the profile to use: `flag`, then `APPPASS_PROFILE`, then the default profile.
+///
+/// # Arguments
+///
+/// * `flag` - The value of `--profile`, if given.
+///
+/// # Returns
+///
+/// * `Result<String, String>` - The profile name, or an error message if it does not exist.
+pub fn resolve_profile(flag: Option<&str>) -> Result<String, String> {
+    let registry = ProfileRegistry::load(&registry_path())?;
+    let name = match flag {
+        Some(name) => name.to_string(),
+        None => std::env::var(PROFILE_ENV_VAR).unwrap_or_else(|_| registry.default_profile()),
+    };
+    validate_profile_name(&name)?;
+    if !registry.contains(&name) {
+        return Err(format!(
+            "Profile '{}' does not exist. Create it with: apppass profile create {}",
+            name, name
+        ));
+    }
+    Ok(name)
+}
+
+/// Creates a new profile.
+pub fn create_profile(name: &str) -> Result<(), String> {
+    validate_profile_name(name)?;
+    let path = registry_path();
+    let mut registry = ProfileRegistry::load(&path)?;
+    if registry.contains(name) {
+        return Err(format!("Profile '{}' already exists", name));
+    }
+    registry.profiles.insert(name.to_string());
+    registry.save(&path)
+}
+
+/// Makes `name` the profile used when `--profile` is not given, saving it as
+/// `default_profile` in the configuration file.
+pub fn set_default_profile(name: &str) -> Result<(), String> {
+    let path = registry_path();
+    let mut registry = ProfileRegistry::load(&path)?;
+    if !registry.contains(name) {
+        return Err(format!("Profile '{}' does not exist", name));
+    }
+    update_config(|config| {
+        config.default_profile = Some(name.to_string()).filter(|name| name != DEFAULT_PROFILE);
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?;
+    if registry.default.take().is_some() {
+        registry.save(&path)?;
+    }
+    Ok(())
+}
+
+/// Removes `name` from the registry and its section from the configuration file. The caller
+/// is responsible for purging its secrets.
+///
+/// If it was the default profile, the built-in `default` profile becomes the default again.
+pub fn remove_profile(name: &str) -> Result<(), String> {
+    if name == DEFAULT_PROFILE {
+        return Err("The default profile cannot be deleted".to_string());
+    }
+    let path = registry_path();
+    let mut registry = ProfileRegistry::load(&path)?;
+    if !registry.profiles.remove(name) {
+        return Err(format!("Profile '{}' does not exist", name));
+    }
+    if registry.default.as_deref() == Some(name) {
+        registry.default = None;
+    }
+    registry.save(&path)?;
+    update_config(|config| {
+        config.profiles.remove(name);
+        if config.default_profile.as_deref() == Some(name) {
+            config.default_profile = None;
+        }
+        Ok(())
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Lists every profile with a flag telling whether it is the default one.
+pub fn list_profiles() -> Result<Vec<(String, bool)>, String> {
+    let registry = ProfileRegistry::load(&registry_path())?;
+    let default = registry.default_profile();
+    Ok(registry
+        .names()
+        .into_iter()
+        .map(|name| {
+            let is_default = name == default;
+            (name, is_default)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "apppass_profiles_test_{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        assert_eq!(ProfileRegistry::load(&path).unwrap(), ProfileRegistry::default());
+
+        let mut registry = ProfileRegistry::default();
+        registry.profiles.insert("work".to_string());
+        registry.default = Some("work".to_string());
+        registry.save(&path).unwrap();
+
+        let loaded = ProfileRegistry::load(&path).unwrap();
+        assert_eq!(loaded, registry);
+        assert_eq!(loaded.default_profile(), "work");
+        assert_eq!(loaded.names(), vec!["default", "work"]);
+        assert!(loaded.contains("default"));
+        assert!(!loaded.contains("personal"));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_validate_profile_name() {
+        assert!(validate_profile_name("work").is_ok());
+        assert!(validate_profile_name("side-project_2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("has space").is_err());
+        assert!(validate_profile_name("../etc").is_err());
+        assert!(validate_profile_name(&"x".repeat(MAX_PROFILE_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_service_name() {
+        assert_eq!(service_name(DEFAULT_PROFILE), APP_SERVICE);
+        assert_eq!(service_name("work"), "apppass-work");
+    }
+}
@@ -0,0 +1,367 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::app::entry::EntryRecord;
+use crate::app::error::Result;
+use crate::app::index::load_index;
+use crate::app::keyring::get_entry_record;
+
+/// How a search query is matched against names, usernames, URLs and tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Case-insensitive substring.
+    Substring,
+    /// Case-insensitive glob over the whole text: `*` matches any run of characters, `?` one character.
+    Glob,
+    /// Substrings first, then the query's characters in order (`gml` finds `gmail`), then
+    /// texts within a typo or two of the query (`gmial` finds `gmail`).
+    Fuzzy,
+}
+
+impl SearchMode {
+    /// All mode names accepted on the command line.
+    pub const NAMES: [&'static str; 3] = ["substring", "glob", "fuzzy"];
+
+    /// Returns the mode to use for `query` when none is given: `Glob` if it contains a
+    /// wildcard, `Fuzzy` otherwise.
+    pub fn detect(query: &str) -> Self {
+        if query.contains(['*', '?']) {
+            SearchMode::Glob
+        } else {
+            SearchMode::Fuzzy
+        }
+    }
+}
+
+impl FromStr for SearchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "substring" => Ok(SearchMode::Substring),
+            "glob" => Ok(SearchMode::Glob),
+            "fuzzy" => Ok(SearchMode::Fuzzy),
+            other => Err(format!(
+                "Unknown search mode '{}'. Expected one of: {}",
+                other,
+                SearchMode::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// The part of an entry a query matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchField {
+    /// The application name.
+    Name,
+    /// The username.
+    Username,
+    /// One of the URLs.
+    Url,
+    /// One of the tags.
+    Tag,
+}
+
+impl fmt::Display for MatchField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MatchField::Name => "name",
+            MatchField::Username => "username",
+            MatchField::Url => "url",
+            MatchField::Tag => "tag",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An entry found by `search_entries`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    /// Name of the application.
+    pub name: String,
+    /// The best matching part of the entry.
+    pub field: MatchField,
+    /// The text that matched.
+    pub value: String,
+    /// How well it matched; higher is better.
+    pub score: u32,
+}
+
+/// Scores how well `text` matches `query`.
+///
+/// # Arguments
+///
+/// * `query` - The search query. An empty query matches everything.
+/// * `text` - The text to match (a name, username, URL or tag).
+/// * `mode` - How to match.
+///
+/// # Returns
+///
+/// * `Option<u32>` - The score (higher is better), or `None` if `text` does not match.
+///   Exact matches score highest, then prefixes, substrings, subsequences and typos.
+pub fn match_score(query: &str, text: &str, mode: SearchMode) -> Option<u32> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return Some(1);
+    }
+    match mode {
+        SearchMode::Substring => substring_score(&query, &text),
+        SearchMode::Glob => glob_match(&query, &text).then_some(500),
+        SearchMode::Fuzzy => substring_score(&query, &text)
+            .or_else(|| subsequence_score(&query, &text))
+            .or_else(|| typo_score(&query, &text)),
+    }
+}
+
+/// Returns the best match of `query` in an entry, checking the name, username, URLs and
+/// tags. On equal scores the name wins, then the username, URLs and tags.
+///
+/// # Returns
+///
+/// * `Option<(MatchField, String, u32)>` - The field, the text that matched and its score.
+pub fn match_entry(query: &str, mode: SearchMode, name: &str, record: &EntryRecord) -> Option<(MatchField, String, u32)> {
+    let candidates = std::iter::once((MatchField::Name, name))
+        .chain(record.username.as_deref().map(|u| (MatchField::Username, u)))
+        .chain(record.urls.iter().map(|u| (MatchField::Url, u.as_str())))
+        .chain(record.tags.iter().map(|t| (MatchField::Tag, t.as_str())));
+    let mut best: Option<(MatchField, String, u32)> = None;
+    for (field, text) in candidates {
+        if let Some(score) = match_score(query, text, mode) {
+            if best.as_ref().is_none_or(|(_, _, best)| score > *best) {
+                best = Some((field, text.to_string(), score));
+            }
+        }
+    }
+    best
+}
+
+/// Searches every entry's name, username, URLs and tags.
+///
+/// # Arguments
+///
+/// * `query` - The search query.
+/// * `mode` - How to match (see `SearchMode::detect` for a default).
+///
+/// # Returns
+///
+/// * `Result<Vec<SearchHit>>` - The matching entries, best first (ties sorted by name), or
+///   an `AppError` if the index cannot be read.
+pub fn search_entries(query: &str, mode: SearchMode) -> Result<Vec<SearchHit>> {
+    let index = load_index()?;
+    let mut hits: Vec<SearchHit> = index
+        .names()
+        .into_iter()
+        .filter_map(|name| {
+            let record = get_entry_record(&name).unwrap_or_default();
+            match_entry(query, mode, &name, &record).map(|(field, value, score)| SearchHit {
+                name,
+                field,
+                value,
+                score,
+            })
+        })
+        .collect();
+    sort_hits(&mut hits);
+    Ok(hits)
+}
+
+/// Returns up to `limit` application names closest to `name`, best first. Used to
+/// suggest entries when no entry has exactly that name.
+pub fn closest_names(name: &str, limit: usize) -> Vec<String> {
+    let mut hits: Vec<SearchHit> = load_index()
+        .map(|index| index.names())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|candidate| {
+            match_score(name, &candidate, SearchMode::Fuzzy).map(|score| SearchHit {
+                value: candidate.clone(),
+                name: candidate,
+                field: MatchField::Name,
+                score,
+            })
+        })
+        .collect();
+    sort_hits(&mut hits);
+    hits.into_iter().take(limit).map(|hit| hit.name).collect()
+}
+
+/// Sorts hits best first, then by name.
+fn sort_hits(hits: &mut [SearchHit]) {
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+}
+
+/// Scores `query` as a substring of `text`: exact 1000, prefix 800, start of a word 600,
+/// anywhere else 400.
+fn substring_score(query: &[char], text: &[char]) -> Option<u32> {
+    let positions: Vec<usize> = text
+        .windows(query.len())
+        .enumerate()
+        .filter(|(_, window)| *window == query)
+        .map(|(i, _)| i)
+        .collect();
+    let first = *positions.first()?;
+    Some(if query.len() == text.len() {
+        1000
+    } else if first == 0 {
+        800
+    } else if positions.iter().any(|&i| !text[i - 1].is_alphanumeric()) {
+        600
+    } else {
+        400
+    })
+}
+
+/// Scores `query` as a subsequence of `text` (between 100 and 399): characters that
+/// follow each other or start a word score higher, gaps lower.
+fn subsequence_score(query: &[char], text: &[char]) -> Option<u32> {
+    let mut score: i64 = 200;
+    let mut last: Option<usize> = None;
+    let mut from = 0;
+    for &c in query {
+        let offset = text[from..].iter().position(|&t| t == c)?;
+        let i = from + offset;
+        if last.is_some_and(|last| i == last + 1) {
+            score += 15;
+        } else if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 10;
+        }
+        score -= offset as i64;
+        last = Some(i);
+        from = i + 1;
+    }
+    Some(score.clamp(100, 399) as u32)
+}
+
+/// Scores texts within a typo or two of `query` (between 10 and 99). The query is compared
+/// with the whole text and with each of its words, so `gogle` finds `https://google.com`.
+fn typo_score(query: &[char], text: &[char]) -> Option<u32> {
+    let allowed = match query.len() {
+        0..=2 => return None,
+        3..=5 => 1,
+        _ => 2,
+    };
+    let distance = std::iter::once(text)
+        .chain(text.split(|c| !c.is_alphanumeric()).filter(|word| !word.is_empty()))
+        .map(|word| edit_distance(query, word))
+        .min()?;
+    (distance <= allowed).then(|| 90 - 30 * distance as u32)
+}
+
+/// Number of insertions, deletions, substitutions and swaps of adjacent characters that
+/// turn `a` into `b` (optimal string alignment distance).
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Matches `text` against the glob `pattern` (`*` and `?`), both already lowercased.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it currently stands for
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::keyring::{delete_from_keyring, save_to_keyring, set_entry_record};
+
+    #[test]
+    fn test_substring_ranking() {
+        let score = |text| match_score("git", text, SearchMode::Substring);
+        assert!(score("git") > score("github"));
+        assert!(score("github") > score("my-git"));
+        assert!(score("my-git") > score("legit"));
+        assert_eq!(score("gitlab"), score("GitHub"));
+        assert_eq!(score("gmail"), None);
+    }
+
+    #[test]
+    fn test_glob() {
+        let matches = |pattern, text| match_score(pattern, text, SearchMode::Glob).is_some();
+        assert!(matches("git*", "GitHub"));
+        assert!(matches("*.example.com", "mail.example.com"));
+        assert!(matches("g?ail", "gmail"));
+        assert!(matches("*a*b*", "xxaxxbxx"));
+        assert!(!matches("git*", "my-github"));
+        assert!(!matches("g?ail", "gmaail"));
+    }
+
+    #[test]
+    fn test_fuzzy_ranking() {
+        let score = |query, text| match_score(query, text, SearchMode::Fuzzy);
+        // Substring beats subsequence beats typo
+        assert!(score("mail", "gmail") > score("gml", "gmail"));
+        assert!(score("gml", "gmail") > score("gmial", "gmail"));
+        assert!(score("gmial", "gmail").is_some());
+        assert!(score("gogle", "https://google.com").is_some());
+        assert!(score("gh", "github").is_some());
+        assert_eq!(score("bank", "gmail"), None);
+        assert_eq!(score("xyz", "github"), None);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let d = |a: &str, b: &str| edit_distance(&a.chars().collect::<Vec<_>>(), &b.chars().collect::<Vec<_>>());
+        assert_eq!(d("gmail", "gmail"), 0);
+        assert_eq!(d("gmial", "gmail"), 1);
+        assert_eq!(d("githb", "github"), 1);
+        assert_eq!(d("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_search_entries_checks_every_field() {
+        let apps = ["test_search_alpha", "test_search_beta", "test_search_gamma"];
+        for app in apps {
+            save_to_keyring(app, "pw").unwrap();
+        }
+        let record = EntryRecord {
+            username: Some("searchable-user".to_string()),
+            tags: vec!["finance".to_string()],
+            ..EntryRecord::default()
+        };
+        set_entry_record("test_search_beta", &record).unwrap();
+
+        let names = |hits: Vec<SearchHit>| hits.into_iter().map(|h| h.name).collect::<Vec<_>>();
+        assert_eq!(names(search_entries("searchable", SearchMode::Substring).unwrap()), vec!["test_search_beta"]);
+        let hits = search_entries("finance", SearchMode::Fuzzy).unwrap();
+        assert_eq!(hits[0].field, MatchField::Tag);
+        assert_eq!(names(search_entries("test_search_*a", SearchMode::Glob).unwrap()), apps);
+        assert_eq!(closest_names("test_search_alhpa", 1), vec!["test_search_alpha"]);
+
+        for app in apps {
+            delete_from_keyring(app).unwrap();
+        }
+    }
+}
@@ -0,0 +1,671 @@
+use keyring::{Entry, Error as KeyringError};
+use once_cell::sync::Lazy;
+#[cfg(test)]
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use crate::app::filelock::{keyring_lock_path, FileLock};
+use crate::app::profile::{active_profile, service_name};
+use crate::app::vault::{read_master_password, vault_path, VaultStore};
+use crate::app::{APP_SERVICE, BACKEND_ENV_VAR, STORE_MANIFEST_KEY};
+
+/// A key/value storage backend for secrets and their metadata.
+///
+/// Every read and write performed by the application goes through this trait, so the
+/// CLI, the interactive console and the TUI all run against whichever backend was
+/// selected at startup.
+pub trait SecretStore: Send + Sync {
+    /// Short name of the backend, used in messages.
+    fn name(&self) -> &'static str;
+
+    /// Retrieves the value stored under `key`.
+    ///
+    /// Returns `KeyringError::NoEntry` if the key does not exist.
+    fn get(&self, key: &str) -> Result<String, KeyringError>;
+
+    /// Stores `value` under `key`, overwriting any previous value.
+    fn set(&self, key: &str, value: &str) -> Result<(), KeyringError>;
+
+    /// Deletes the value stored under `key`.
+    ///
+    /// Returns `KeyringError::NoEntry` if the key does not exist.
+    fn delete(&self, key: &str) -> Result<(), KeyringError>;
+
+    /// Lists every key held by the store, sorted.
+    fn list(&self) -> Result<Vec<String>, KeyringError>;
+
+    /// Takes an exclusive lock on the store, shared with other apppass processes using it.
+    ///
+    /// Held around read-modify-write cycles (see `update_index`) so concurrent processes
+    /// cannot lose each other's updates. The lock is re-entrant within a thread and is
+    /// released when the guard is dropped. Backends that are not shared between processes
+    /// return `None`.
+    fn lock(&self) -> Result<Option<FileLock>, KeyringError> {
+        Ok(None)
+    }
+}
+
+/// Available storage backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// The operating system keyring (Keychain, Credential Manager, Secret Service).
+    Keyring,
+    /// A process-local in-memory store. Nothing is persisted.
+    Memory,
+    /// An encrypted single-file vault protected by a master password.
+    Vault,
+}
+
+impl Backend {
+    /// All backend names accepted on the command line.
+    pub const NAMES: [&'static str; 3] = ["keyring", "memory", "vault"];
+
+    /// Returns the name of the backend as accepted on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Keyring => "keyring",
+            Backend::Memory => "memory",
+            Backend::Vault => "vault",
+        }
+    }
+
+    /// Reads the backend from the `APPPASS_BACKEND` environment variable, if set.
+    pub fn from_env() -> Option<Result<Backend, String>> {
+        std::env::var(BACKEND_ENV_VAR).ok().map(|v| v.parse())
+    }
+
+    /// Opens a store of this backend for the given service namespace.
+    ///
+    /// Opening a vault asks for the master password (see `read_master_password`) and
+    /// fails with `AppError::Backend` if it does not decrypt the existing vault file.
+    pub fn open(self, service: &str) -> crate::app::error::Result<Arc<dyn SecretStore>> {
+        match self {
+            Backend::Keyring => Ok(Arc::new(ChunkedStore::new(KeyringStore::new(service), KEYRING_MAX_VALUE_LEN))),
+            Backend::Memory => Ok(Arc::new(MemoryStore::new())),
+            Backend::Vault => {
+                let path = vault_path(service);
+                let master_password = read_master_password(&path)?;
+                Ok(Arc::new(VaultStore::open(&path, &master_password)?))
+            }
+        }
+    }
+
+    /// Returns true if this backend holds any secret for the given service namespace.
+    ///
+    /// For the vault backend this checks whether the vault file exists, so no master
+    /// password is needed.
+    pub fn has_data(self, service: &str) -> Result<bool, KeyringError> {
+        match self {
+            Backend::Keyring => Ok(!KeyringStore::new(service).list()?.is_empty()),
+            Backend::Memory => Ok(false),
+            Backend::Vault => Ok(vault_path(service).exists()),
+        }
+    }
+
+    /// Deletes every secret this backend holds for the given service namespace.
+    ///
+    /// For the vault backend the vault file is removed, so no master password is needed.
+    pub fn purge(self, service: &str) -> Result<(), KeyringError> {
+        match self {
+            Backend::Keyring => {
+                let store = KeyringStore::new(service);
+                for key in store.list()? {
+                    match store.delete(&key) {
+                        Ok(()) | Err(KeyringError::NoEntry) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(())
+            }
+            Backend::Memory => Ok(()),
+            Backend::Vault => match std::fs::remove_file(vault_path(service)) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(KeyringError::PlatformFailure(Box::new(e))),
+            },
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "keyring" | "os" => Ok(Backend::Keyring),
+            "memory" | "mem" => Ok(Backend::Memory),
+            "vault" | "file" => Ok(Backend::Vault),
+            other => Err(format!(
+                "Unknown backend '{}'. Expected one of: {}",
+                other,
+                Backend::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// The store used by the whole application. Unit tests default to the in-memory backend
+/// so they never touch (or depend on) the OS keyring.
+static STORE: Lazy<RwLock<Arc<dyn SecretStore>>> = Lazy::new(|| {
+    let store: Arc<dyn SecretStore> = if cfg!(test) {
+        Arc::new(MemoryStore::new())
+    } else {
+        Arc::new(ChunkedStore::new(KeyringStore::new(APP_SERVICE), KEYRING_MAX_VALUE_LEN))
+    };
+    RwLock::new(store)
+});
+
+#[cfg(test)]
+thread_local! {
+    /// Store that replaces the global one on this thread while a `ScopedStore` is alive.
+    static SCOPED: RefCell<Option<Arc<dyn SecretStore>>> = const { RefCell::new(None) };
+}
+
+/// Selects the backend used by the application, opened in the namespace of the active
+/// profile. Should be called once at startup, before any other store access.
+///
+/// # Returns
+///
+/// * `Result<()>` - `AppError::Backend` if the backend cannot be opened (for a vault, a
+///   wrong master password or an unreadable file).
+pub fn init_store(backend: Backend) -> crate::app::error::Result<()> {
+    set_store(backend.open(&service_name(&active_profile()))?);
+    Ok(())
+}
+
+/// Replaces the active store with an already constructed one.
+pub fn set_store(store: Arc<dyn SecretStore>) {
+    *STORE.write().unwrap_or_else(|e| e.into_inner()) = store;
+}
+
+/// Returns the active store.
+pub fn store() -> Arc<dyn SecretStore> {
+    #[cfg(test)]
+    if let Some(store) = SCOPED.with(|scoped| scoped.borrow().clone()) {
+        return store;
+    }
+    STORE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Gives the current thread its own empty in-memory store until the guard is dropped, so
+/// a test can list, export or import every entry without seeing the ones other tests
+/// running in parallel are changing.
+#[cfg(test)]
+pub(crate) fn scoped_store() -> ScopedStore {
+    scope_store(Arc::new(MemoryStore::new()))
+}
+
+/// Makes `store` the store of the current thread until the guard is dropped.
+#[cfg(test)]
+pub(crate) fn scope_store(store: Arc<dyn SecretStore>) -> ScopedStore {
+    ScopedStore {
+        previous: SCOPED.with(|scoped| scoped.replace(Some(store))),
+    }
+}
+
+/// Guard returned by `scoped_store`; puts the previous store of the thread back when dropped.
+#[cfg(test)]
+pub(crate) struct ScopedStore {
+    previous: Option<Arc<dyn SecretStore>>,
+}
+
+#[cfg(test)]
+impl Drop for ScopedStore {
+    fn drop(&mut self) {
+        SCOPED.with(|scoped| *scoped.borrow_mut() = self.previous.take());
+    }
+}
+
+/// Longest value the OS keyring accepts in a single credential, in UTF-16 code units
+/// (Windows Credential Manager rejects credentials larger than 2560 bytes of UTF-16).
+pub const KEYRING_MAX_VALUE_LEN: usize = 1280;
+
+/// In-memory store that rejects values longer than a limit, as the Windows credential
+/// manager does, for testing how records behave when they outgrow a keyring credential.
+#[cfg(test)]
+pub(crate) struct LimitedStore {
+    entries: MemoryStore,
+    max_len: usize,
+}
+
+#[cfg(test)]
+impl LimitedStore {
+    pub(crate) fn new(max_len: usize) -> Self {
+        Self { entries: MemoryStore::new(), max_len }
+    }
+}
+
+#[cfg(test)]
+impl SecretStore for LimitedStore {
+    fn name(&self) -> &'static str {
+        "limited"
+    }
+
+    fn get(&self, key: &str) -> Result<String, KeyringError> {
+        self.entries.get(key)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), KeyringError> {
+        if value.encode_utf16().count() > self.max_len {
+            return Err(KeyringError::TooLong("password".to_string(), self.max_len as u32));
+        }
+        self.entries.set(key, value)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), KeyringError> {
+        self.entries.delete(key)
+    }
+
+    fn list(&self) -> Result<Vec<String>, KeyringError> {
+        self.entries.list()
+    }
+}
+
+/// Installs a chunked store over an empty `LimitedStore` of `KEYRING_MAX_VALUE_LEN` on the
+/// current thread until the guard is dropped (see `scope_store`).
+#[cfg(test)]
+pub(crate) fn scoped_keyring_sized_store() -> ScopedStore {
+    scope_store(Arc::new(ChunkedStore::new(LimitedStore::new(KEYRING_MAX_VALUE_LEN), KEYRING_MAX_VALUE_LEN)))
+}
+
+/// Secret store backed by the operating system keyring.
+///
+/// OS keyrings cannot enumerate their entries, so the store keeps a manifest of the
+/// keys it has written under `STORE_MANIFEST_KEY` to support `list`. The manifest is
+/// split into pages of at most `KEYRING_MAX_VALUE_LEN`; values themselves are not split,
+/// so wrap the store in a `ChunkedStore` to write longer ones.
+pub struct KeyringStore {
+    service: String,
+}
+
+impl KeyringStore {
+    /// Creates a keyring store for the given service name.
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+        }
+    }
+
+    fn entry(&self, key: &str) -> Result<Entry, KeyringError> {
+        Entry::new(&self.service, key)
+    }
+
+    /// Key of the given page of the manifest: `STORE_MANIFEST_KEY` for the first one, which
+    /// is where releases before paging kept the whole manifest.
+    fn manifest_page_key(page: usize) -> String {
+        match page {
+            0 => STORE_MANIFEST_KEY.to_string(),
+            page => chunk_key(STORE_MANIFEST_KEY, 0, page),
+        }
+    }
+
+    fn read_manifest(&self) -> Result<Vec<String>, KeyringError> {
+        let mut data = String::new();
+        for page in 0.. {
+            match self.entry(&Self::manifest_page_key(page))?.get_password() {
+                Ok(text) => data.push_str(&text),
+                Err(KeyringError::NoEntry) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(data.lines().filter(|l| !l.is_empty()).map(String::from).collect())
+    }
+
+    fn update_manifest(&self, key: &str, add: bool) -> Result<(), KeyringError> {
+        let mut keys = self.read_manifest()?;
+        let present = keys.iter().any(|k| k == key);
+        if add == present {
+            return Ok(());
+        }
+        if add {
+            keys.push(key.to_string());
+            keys.sort();
+        } else {
+            keys.retain(|k| k != key);
+        }
+
+        let data = keys.join("\n");
+        let pages = split_value(&data, KEYRING_MAX_VALUE_LEN);
+        for (page, text) in pages.iter().enumerate() {
+            self.entry(&Self::manifest_page_key(page))?.set_password(text)?;
+        }
+        // Remove the pages the manifest no longer needs
+        for page in pages.len().. {
+            match self.entry(&Self::manifest_page_key(page))?.delete_credential() {
+                Ok(()) => {}
+                Err(KeyringError::NoEntry) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn get(&self, key: &str) -> Result<String, KeyringError> {
+        self.entry(key)?.get_password()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), KeyringError> {
+        let _lock = self.lock()?;
+        self.entry(key)?.set_password(value)?;
+        self.update_manifest(key, true)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), KeyringError> {
+        let _lock = self.lock()?;
+        self.entry(key)?.delete_credential()?;
+        self.update_manifest(key, false)
+    }
+
+    fn list(&self) -> Result<Vec<String>, KeyringError> {
+        let _lock = self.lock()?;
+        self.read_manifest()
+    }
+
+    fn lock(&self) -> Result<Option<FileLock>, KeyringError> {
+        FileLock::acquire(&keyring_lock_path(&self.service))
+            .map(Some)
+            .map_err(|e| KeyringError::PlatformFailure(Box::new(e)))
+    }
+}
+
+/// Marks a value that was split into chunks by `ChunkedStore`; followed by the generation
+/// and the number of chunks.
+const CHUNK_HEADER: &str = "\u{0}apppass-chunks:";
+
+/// Prefix of the keys holding chunks. No entry, metadata or setting key starts with it
+/// (escaped keys start with a single `%` followed by `%25` or `%5F`, see `entry_key`).
+const CHUNK_KEY_PREFIX: &str = "%%chunk:";
+
+/// Returns the key of the chunk `index` of generation `generation` of the value of `key`.
+fn chunk_key(key: &str, generation: u64, index: usize) -> String {
+    format!("{}{}:{}:{}", CHUNK_KEY_PREFIX, generation, index, key)
+}
+
+/// Splits `value` into pieces of at most `max_len` UTF-16 code units, without splitting a character.
+fn split_value(value: &str, max_len: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut units = 0;
+    for (i, c) in value.char_indices() {
+        if units + c.len_utf16() > max_len {
+            pieces.push(&value[start..i]);
+            start = i;
+            units = 0;
+        }
+        units += c.len_utf16();
+    }
+    if start < value.len() {
+        pieces.push(&value[start..]);
+    }
+    pieces
+}
+
+/// Parses the generation and chunk count of a chunked value, or returns `None` for a plain value.
+fn parse_chunk_header(value: &str) -> Option<(u64, usize)> {
+    let (generation, count) = value.strip_prefix(CHUNK_HEADER)?.split_once(':')?;
+    Some((generation.parse().ok()?, count.parse().ok()?))
+}
+
+/// Secret store that splits values longer than the wrapped store accepts over several keys.
+///
+/// A long value is written as chunks of at most `max_len` UTF-16 code units under keys
+/// starting with `CHUNK_KEY_PREFIX`, followed by a header under its own key naming the
+/// chunks. Each rewrite uses a new generation of chunk keys and switches the header last,
+/// so readers never see a mix of old and new chunks. Chunk keys are hidden from `list`.
+pub struct ChunkedStore<S> {
+    inner: S,
+    max_len: usize,
+}
+
+impl<S: SecretStore> ChunkedStore<S> {
+    /// Wraps `inner`, which accepts values of at most `max_len` UTF-16 code units.
+    pub fn new(inner: S, max_len: usize) -> Self {
+        Self { inner, max_len }
+    }
+
+    /// Returns the generation and chunk count stored under `key`, if its value is chunked.
+    fn stored_header(&self, key: &str) -> Result<Option<(u64, usize)>, KeyringError> {
+        match self.inner.get(key) {
+            Ok(value) => Ok(parse_chunk_header(&value)),
+            Err(KeyringError::NoEntry) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read_chunks(&self, key: &str, generation: u64, count: usize) -> Result<String, KeyringError> {
+        let mut value = String::new();
+        for index in 0..count {
+            value.push_str(&self.inner.get(&chunk_key(key, generation, index))?);
+        }
+        Ok(value)
+    }
+
+    fn delete_chunks(&self, key: &str, generation: u64, count: usize) -> Result<(), KeyringError> {
+        for index in 0..count {
+            match self.inner.delete(&chunk_key(key, generation, index)) {
+                Ok(()) | Err(KeyringError::NoEntry) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: SecretStore> SecretStore for ChunkedStore<S> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn get(&self, key: &str) -> Result<String, KeyringError> {
+        loop {
+            let value = self.inner.get(key)?;
+            let Some((generation, count)) = parse_chunk_header(&value) else {
+                return Ok(value);
+            };
+            match self.read_chunks(key, generation, count) {
+                // Rewritten by another process while reading: read the new generation
+                Err(KeyringError::NoEntry) if self.inner.get(key).ok().as_deref() != Some(value.as_str()) => continue,
+                Err(KeyringError::NoEntry) => {
+                    return Err(KeyringError::NoStorageAccess(format!("a chunk of '{}' is missing", key).into()));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), KeyringError> {
+        let _lock = self.inner.lock()?;
+        let previous = self.stored_header(key)?;
+        let chunks = split_value(value, self.max_len);
+        if chunks.len() <= 1 && !value.starts_with(CHUNK_HEADER) {
+            self.inner.set(key, value)?;
+        } else {
+            let generation = previous.map_or(0, |(generation, _)| generation + 1);
+            for (index, chunk) in chunks.iter().enumerate() {
+                if let Err(e) = self.inner.set(&chunk_key(key, generation, index), chunk) {
+                    let _ = self.delete_chunks(key, generation, index);
+                    return Err(e);
+                }
+            }
+            let header = format!("{}{}:{}", CHUNK_HEADER, generation, chunks.len());
+            if let Err(e) = self.inner.set(key, &header) {
+                let _ = self.delete_chunks(key, generation, chunks.len());
+                return Err(e);
+            }
+        }
+        match previous {
+            Some((generation, count)) => self.delete_chunks(key, generation, count),
+            None => Ok(()),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<(), KeyringError> {
+        let _lock = self.inner.lock()?;
+        let previous = self.stored_header(key)?;
+        self.inner.delete(key)?;
+        match previous {
+            Some((generation, count)) => self.delete_chunks(key, generation, count),
+            None => Ok(()),
+        }
+    }
+
+    fn list(&self) -> Result<Vec<String>, KeyringError> {
+        Ok(self
+            .inner
+            .list()?
+            .into_iter()
+            .filter(|key| !key.starts_with(CHUNK_KEY_PREFIX))
+            .collect())
+    }
+
+    fn lock(&self) -> Result<Option<FileLock>, KeyringError> {
+        self.inner.lock()
+    }
+}
+
+/// Secret store that keeps everything in memory for the lifetime of the process.
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: Mutex<BTreeMap<String, String>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SecretStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, key: &str) -> Result<String, KeyringError> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(key).cloned().ok_or(KeyringError::NoEntry)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), KeyringError> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), KeyringError> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(key).map(|_| ()).ok_or(KeyringError::NoEntry)
+    }
+
+    fn list(&self) -> Result<Vec<String>, KeyringError> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.keys().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_store_set_get_delete() {
+        let store = MemoryStore::new();
+        store.set("app", "secret").unwrap();
+        assert_eq!(store.get("app").unwrap(), "secret");
+
+        store.delete("app").unwrap();
+        assert!(matches!(store.get("app"), Err(KeyringError::NoEntry)));
+    }
+
+    #[test]
+    fn test_memory_store_delete_missing_is_no_entry() {
+        let store = MemoryStore::new();
+        assert!(matches!(store.delete("missing"), Err(KeyringError::NoEntry)));
+    }
+
+    #[test]
+    fn test_memory_store_list_is_sorted() {
+        let store = MemoryStore::new();
+        store.set("zeta", "1").unwrap();
+        store.set("alpha", "2").unwrap();
+        store.set("mid", "3").unwrap();
+        assert_eq!(store.list().unwrap(), vec!["alpha", "mid", "zeta"]);
+    }
+
+    #[test]
+    fn test_chunked_store_splits_values_the_inner_store_rejects() {
+        let limited = LimitedStore::new(32);
+        let long = "añ€😀".repeat(20);
+        assert!(matches!(limited.set("record", &long), Err(KeyringError::TooLong(_, 32))));
+
+        let store = ChunkedStore::new(limited, 32);
+        store.set("record", &long).unwrap();
+        assert_eq!(store.get("record").unwrap(), long);
+        assert_eq!(store.list().unwrap(), vec!["record"]);
+        assert!(store.inner.list().unwrap().len() > 1);
+
+        // A rewrite replaces the chunks, and a short value is stored as is
+        let longer = "x".repeat(100);
+        store.set("record", &longer).unwrap();
+        assert_eq!(store.get("record").unwrap(), longer);
+        assert_eq!(store.inner.list().unwrap().len(), 1 + 4);
+        store.set("record", "short").unwrap();
+        assert_eq!(store.get("record").unwrap(), "short");
+        assert_eq!(store.inner.list().unwrap(), vec!["record"]);
+
+        store.set("record", &long).unwrap();
+        store.delete("record").unwrap();
+        assert!(store.inner.list().unwrap().is_empty());
+        assert!(matches!(store.get("record"), Err(KeyringError::NoEntry)));
+    }
+
+    #[test]
+    fn test_chunked_store_keeps_values_that_look_like_a_header() {
+        let store = ChunkedStore::new(LimitedStore::new(64), 64);
+        let value = format!("{}0:1", CHUNK_HEADER);
+        store.set("tricky", &value).unwrap();
+        assert_eq!(store.get("tricky").unwrap(), value);
+    }
+
+    #[test]
+    fn test_split_value_respects_utf16_length() {
+        assert!(split_value("", 4).is_empty());
+        assert_eq!(split_value("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        // 😀 takes two UTF-16 code units and is never split
+        assert_eq!(split_value("a😀b😀", 2), vec!["a", "😀", "b", "😀"]);
+    }
+
+    #[test]
+    fn test_backend_from_str() {
+        assert_eq!("keyring".parse::<Backend>(), Ok(Backend::Keyring));
+        assert_eq!("Memory".parse::<Backend>(), Ok(Backend::Memory));
+        assert_eq!("vault".parse::<Backend>(), Ok(Backend::Vault));
+        assert!("floppy".parse::<Backend>().is_err());
+    }
+
+    #[test]
+    fn test_tests_use_memory_backend() {
+        assert_eq!(store().name(), "memory");
+    }
+
+    #[test]
+    fn test_scoped_store_is_private_to_the_thread() {
+        let global = store();
+        {
+            let _store = scoped_store();
+            store().set("scoped_only", "1").unwrap();
+            assert!(!Arc::ptr_eq(&store(), &global));
+            let other = std::thread::spawn(|| store().get("scoped_only").is_err());
+            assert!(other.join().unwrap());
+        }
+        assert!(Arc::ptr_eq(&store(), &global));
+        assert!(store().get("scoped_only").is_err());
+    }
+}
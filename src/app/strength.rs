@@ -0,0 +1,723 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use crate::app::breach::breach_count;
+use crate::app::config::config;
+use crate::app::error::{AppError, Result};
+use crate::app::passphrase::Wordlist;
+
+/// Frequently used passwords, most common first. A password found here is among the first
+/// guesses of any attacker, so it costs only about log2(rank) bits.
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345", "1234", "111111",
+    "1234567", "dragon", "123123", "baseball", "abc123", "football", "monkey", "letmein",
+    "696969", "shadow", "master", "666666", "qwertyuiop", "123321", "mustang", "1234567890",
+    "michael", "654321", "superman", "1qaz2wsx", "7777777", "121212", "000000", "qazwsx",
+    "123qwe", "killer", "trustno1", "jordan", "jennifer", "zxcvbnm", "asdfgh", "hunter",
+    "buster", "soccer", "harley", "batman", "andrew", "tigger", "sunshine", "iloveyou",
+    "2000", "charlie", "robert", "thomas", "hockey", "ranger", "daniel", "starwars",
+    "112233", "george", "computer", "michelle", "jessica", "pepper", "1111",
+    "zxcvbn", "555555", "11111111", "131313", "freedom", "777777", "pass", "maggie",
+    "159753", "aaaaaa", "ginger", "princess", "joshua", "cheese", "amanda", "summer",
+    "love", "ashley", "nicole", "chelsea", "biteme", "matthew", "access", "yankees",
+    "987654321", "dallas", "austin", "thunder", "taylor", "matrix", "welcome", "admin", "login",
+    "passw0rd", "password1", "password123", "qwerty123", "hello", "whatever", "secret",
+    "flower", "loveme", "zaq1zaq1", "lovely", "888888", "google", "internet", "changeme",
+    "default", "root", "toor", "guest", "test", "solo", "ninja", "azerty", "winter",
+    "autumn", "spring", "orange", "banana", "chocolate", "pokemon", "samsung", "apple",
+];
+
+/// Number of leading characters searched for patterns. The search grows roughly with the
+/// cube of the length, so longer passwords have the characters past this point rated as
+/// random ones, as zxcvbn does.
+const MAX_ANALYZED_LENGTH: usize = 100;
+
+/// Number of keys a keyboard walk can start on.
+const KEYBOARD_STARTING_KEYS: f64 = 47.0;
+
+/// Average number of neighbours of a key, the choices at each turn of a keyboard walk.
+const KEYBOARD_AVERAGE_DEGREE: f64 = 6.0;
+
+/// QWERTY rows, unshifted and shifted. Every row but the first starts one column in, so
+/// `x2 = 2 * column + row` puts each key half a key right of the one above.
+const KEYBOARD_ROWS: [(&str, &str, i32); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+", 0),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|", 1),
+    ("asdfghjkl;'", "ASDFGHJKL:\"", 1),
+    ("zxcvbnm,./", "ZXCVBNM<>?", 1),
+];
+
+/// Dictionary tokens (lowercase) with their cost in bits and whether they are common passwords.
+static DICTIONARY: Lazy<HashMap<String, (f64, bool)>> = Lazy::new(|| {
+    let mut dictionary = HashMap::new();
+    let word_bits = Wordlist::embedded().bits_per_word();
+    for word in Wordlist::embedded().words() {
+        dictionary.insert(word.clone(), (word_bits, false));
+    }
+    for (rank, password) in COMMON_PASSWORDS.iter().enumerate() {
+        dictionary.insert(password.to_string(), (((rank + 2) as f64).log2(), true));
+    }
+    dictionary
+});
+
+/// Longest token in the dictionary, in characters.
+static LONGEST_WORD: Lazy<usize> = Lazy::new(|| DICTIONARY.keys().map(|w| w.chars().count()).max().unwrap_or(0));
+
+/// Keyboard position of every key: `(row, x2, shifted)`.
+static KEYBOARD: Lazy<HashMap<char, (i32, i32, bool)>> = Lazy::new(|| {
+    let mut keys = HashMap::new();
+    for (row, (plain, shifted, first_column)) in KEYBOARD_ROWS.iter().enumerate() {
+        let row = row as i32;
+        for (column, (p, s)) in plain.chars().zip(shifted.chars()).enumerate() {
+            let x2 = 2 * (column as i32 + first_column) + row;
+            keys.insert(p, (row, x2, false));
+            keys.insert(s, (row, x2, true));
+        }
+    }
+    keys
+});
+
+/// How strong a password is, from its estimated entropy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strength {
+    /// Under 28 bits: guessed in seconds.
+    VeryWeak,
+    /// Under 40 bits: guessed by an online attack.
+    Weak,
+    /// Under 60 bits: resists online attacks.
+    Fair,
+    /// Under 75 bits: resists offline attacks on a slow hash.
+    Strong,
+    /// 75 bits or more.
+    VeryStrong,
+}
+
+impl Strength {
+    /// All strength names accepted in the configuration, weakest first.
+    pub const NAMES: [&'static str; 5] = ["very-weak", "weak", "fair", "strong", "very-strong"];
+
+    /// Classifies an entropy, in bits.
+    pub fn from_entropy(bits: f64) -> Self {
+        match bits {
+            b if b < 28.0 => Strength::VeryWeak,
+            b if b < 40.0 => Strength::Weak,
+            b if b < 60.0 => Strength::Fair,
+            b if b < 75.0 => Strength::Strong,
+            _ => Strength::VeryStrong,
+        }
+    }
+}
+
+impl fmt::Display for Strength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(Strength::NAMES[*self as usize])
+    }
+}
+
+impl FromStr for Strength {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['_', ' '], "-").as_str() {
+            "very-weak" => Ok(Strength::VeryWeak),
+            "weak" => Ok(Strength::Weak),
+            "fair" => Ok(Strength::Fair),
+            "strong" => Ok(Strength::Strong),
+            "very-strong" => Ok(Strength::VeryStrong),
+            other => Err(format!(
+                "unknown strength '{}' (expected one of: {})",
+                other,
+                Strength::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// The kind of guessable pattern found in a password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    /// One of the most used passwords.
+    CommonPassword,
+    /// A word of the embedded wordlist.
+    DictionaryWord,
+    /// Neighbouring keys, like `qwerty` or `zxcvb`.
+    KeyboardWalk,
+    /// Evenly spaced letters or digits, like `abcd` or `97531`.
+    Sequence,
+    /// A repeated character or chunk, like `aaaa` or `abcabc`.
+    Repeat,
+    /// A year or a date, like `1987` or `12/05/1990`.
+    Date,
+}
+
+/// A guessable part of a password.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    /// What was found.
+    pub kind: PatternKind,
+    /// The characters of the password it covers.
+    pub token: String,
+    /// Whether letters were replaced by look-alike digits or symbols (`p@ssw0rd`).
+    pub substituted: bool,
+    /// Bits of entropy the part is worth.
+    pub entropy: f64,
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            PatternKind::CommonPassword => "is a common password",
+            PatternKind::DictionaryWord => "is a dictionary word",
+            PatternKind::KeyboardWalk => "is a keyboard pattern",
+            PatternKind::Sequence => "is a sequence",
+            PatternKind::Repeat => "is a repetition",
+            PatternKind::Date => "looks like a date",
+        };
+        write!(f, "'{}' {}", self.token, what)?;
+        if self.substituted {
+            f.write_str(" with predictable substitutions")?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimated strength of a password.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthEstimate {
+    /// Estimated entropy, in bits: log2 of the guesses an attacker trying common patterns needs.
+    pub entropy: f64,
+    /// The class of the entropy.
+    pub strength: Strength,
+    /// Guessable parts found in the password, in order.
+    pub patterns: Vec<Pattern>,
+    /// Times the password appears in the breach corpus (`password.breach_file`), if it was
+    /// looked up. A breached password is very weak whatever its entropy.
+    pub breaches: Option<u64>,
+}
+
+impl StrengthEstimate {
+    /// Estimate of a password drawn uniformly at random, such as a generated one.
+    pub fn random(entropy: f64) -> Self {
+        Self {
+            entropy,
+            strength: Strength::from_entropy(entropy),
+            patterns: Vec::new(),
+            breaches: None,
+        }
+    }
+}
+
+impl fmt::Display for StrengthEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:.1} bits", self.strength, self.entropy)?;
+        match self.breaches {
+            Some(count) if count > 0 => write!(f, ", found {} times in known breaches)", count),
+            _ => f.write_str(")"),
+        }
+    }
+}
+
+/// A pattern found at `start..end` of the password.
+struct Candidate {
+    start: usize,
+    end: usize,
+    pattern: Pattern,
+}
+
+/// Estimates the strength of a password chosen by a person.
+///
+/// The password is split into the cheapest sequence of guessable patterns (common passwords,
+/// dictionary words with capitals and look-alike substitutions, keyboard walks, sequences,
+/// repeats and dates) and characters guessed one by one; the entropy is the total cost.
+/// Only the first `MAX_ANALYZED_LENGTH` characters are searched for patterns; the rest
+/// count as random characters.
+///
+/// # Arguments
+///
+/// * `password` - The password to rate.
+///
+/// # Returns
+///
+/// * `StrengthEstimate` - The entropy, its strength class and the patterns found.
+pub fn estimate_strength(password: &str) -> StrengthEstimate {
+    let chars: Vec<char> = password.chars().collect();
+    let (analyzed, rest) = chars.split_at(chars.len().min(MAX_ANALYZED_LENGTH));
+    let mut cache = HashMap::new();
+    let (mut entropy, patterns) = minimum_entropy(analyzed, &mut cache);
+    if !rest.is_empty() {
+        entropy += rest.len() as f64 * cardinality(&chars).log2();
+    }
+    StrengthEstimate {
+        entropy,
+        strength: Strength::from_entropy(entropy),
+        patterns,
+        breaches: None,
+    }
+}
+
+/// Estimates the strength of a custom password, looks it up in the configured breach corpus
+/// (`password.breach_file`) and checks it against the configured `password.min_strength`.
+///
+/// # Returns
+///
+/// * `Result<StrengthEstimate>` - The estimate, `AppError::InvalidInput` naming the patterns
+///   found if the password is weaker than the configured minimum, or an error from `breach_count`.
+pub fn check_password_strength(password: &str) -> Result<StrengthEstimate> {
+    let mut estimate = estimate_strength(password);
+    estimate.breaches = breach_count(password)?;
+    if estimate.breaches.is_some_and(|count| count > 0) {
+        estimate.strength = Strength::VeryWeak;
+    }
+    match config().password.min_strength {
+        Some(minimum) if estimate.strength < minimum => {
+            let mut message = format!(
+                "Password is too weak: {}, but password.min_strength is {}",
+                estimate, minimum
+            );
+            if !estimate.patterns.is_empty() {
+                let patterns: Vec<String> = estimate.patterns.iter().map(Pattern::to_string).collect();
+                message.push_str(&format!(" ({})", patterns.join("; ")));
+            }
+            Err(AppError::InvalidInput(message))
+        }
+        _ => Ok(estimate),
+    }
+}
+
+/// Cheapest cost of `chars`, in bits, and the patterns it is made of. `cache` holds the
+/// cost of chunks already rated while looking for repeats.
+fn minimum_entropy(chars: &[char], cache: &mut HashMap<String, f64>) -> (f64, Vec<Pattern>) {
+    let candidates = find_patterns(chars, cache);
+    let brute_force = cardinality(chars).log2();
+
+    // best[i] is the cheapest cost of chars[..i]; choice[i] the pattern ending there, if any.
+    let mut best = vec![0.0; chars.len() + 1];
+    let mut choice: Vec<Option<usize>> = vec![None; chars.len() + 1];
+    for end in 1..=chars.len() {
+        best[end] = best[end - 1] + brute_force;
+        for (index, candidate) in candidates.iter().enumerate().filter(|(_, c)| c.end == end) {
+            let cost = best[candidate.start] + candidate.pattern.entropy;
+            if cost < best[end] {
+                best[end] = cost;
+                choice[end] = Some(index);
+            }
+        }
+    }
+
+    let mut patterns = Vec::new();
+    let mut end = chars.len();
+    while end > 0 {
+        match choice[end] {
+            Some(index) => {
+                patterns.push(candidates[index].pattern.clone());
+                end = candidates[index].start;
+            }
+            None => end -= 1,
+        }
+    }
+    patterns.reverse();
+    (best[chars.len()], patterns)
+}
+
+/// Every pattern found in `chars`.
+fn find_patterns(chars: &[char], cache: &mut HashMap<String, f64>) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    dictionary_patterns(chars, &mut candidates);
+    keyboard_patterns(chars, &mut candidates);
+    sequence_patterns(chars, &mut candidates);
+    repeat_patterns(chars, cache, &mut candidates);
+    date_patterns(chars, &mut candidates);
+    candidates
+}
+
+/// Size of the character set an attacker guessing `chars` one by one has to try.
+fn cardinality(chars: &[char]) -> f64 {
+    let mut size = 0.0;
+    if chars.iter().any(char::is_ascii_lowercase) {
+        size += 26.0;
+    }
+    if chars.iter().any(char::is_ascii_uppercase) {
+        size += 26.0;
+    }
+    if chars.iter().any(char::is_ascii_digit) {
+        size += 10.0;
+    }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+        size += 33.0;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        size += 100.0;
+    }
+    f64::max(size, 1.0)
+}
+
+/// Size of the class of `c` (lowercase, uppercase, digits or symbols).
+fn class_size(c: char) -> f64 {
+    cardinality(&[c])
+}
+
+/// The letter a look-alike character stands for, reading `1` as `one`.
+fn unsubstitute(c: char, one: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '8' => 'b',
+        '3' => 'e',
+        '9' => 'g',
+        '1' => one,
+        '!' | '|' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' | '+' => 't',
+        other => other,
+    }
+}
+
+/// Bits added by the capital letters of a dictionary word: none for all lowercase, one for
+/// a capitalized or all-uppercase word, otherwise the number of ways to place them.
+fn uppercase_entropy(token: &[char]) -> f64 {
+    let upper = token.iter().filter(|c| c.is_uppercase()).count();
+    let lower = token.iter().filter(|c| c.is_lowercase()).count();
+    if upper == 0 {
+        return 0.0;
+    }
+    if lower == 0 || (upper == 1 && token[0].is_uppercase()) {
+        return 1.0;
+    }
+    let letters = upper + lower;
+    let ways: f64 = (1..=upper.min(lower)).map(|k| binomial(letters, k)).sum();
+    ways.log2()
+}
+
+/// Number of ways to choose `k` of `n` items.
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Dictionary words and common passwords, also with capitals and look-alike substitutions.
+fn dictionary_patterns(chars: &[char], candidates: &mut Vec<Candidate>) {
+    let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    if lower.len() != chars.len() {
+        return;
+    }
+    for start in 0..chars.len() {
+        for end in (start + 3)..=chars.len().min(start + *LONGEST_WORD) {
+            let token = &chars[start..end];
+            let mut best: Option<Pattern> = None;
+            for one in ['i', 'l'] {
+                let plain: String = lower[start..end].iter().collect();
+                let word: String = lower[start..end].iter().map(|c| unsubstitute(*c, one)).collect();
+                let substitutions = plain.chars().zip(word.chars()).filter(|(a, b)| a != b).count();
+                for (text, substituted) in [(plain, 0), (word, substitutions)] {
+                    let Some((bits, common)) = DICTIONARY.get(&text) else { continue };
+                    if substituted == token.len() {
+                        continue;
+                    }
+                    let entropy = bits + uppercase_entropy(token) + substituted as f64;
+                    if best.as_ref().is_none_or(|b| entropy < b.entropy) {
+                        best = Some(Pattern {
+                            kind: if *common { PatternKind::CommonPassword } else { PatternKind::DictionaryWord },
+                            token: token.iter().collect(),
+                            substituted: substituted > 0,
+                            entropy,
+                        });
+                    }
+                }
+            }
+            if let Some(pattern) = best {
+                candidates.push(Candidate { start, end, pattern });
+            }
+        }
+    }
+}
+
+/// Walks of at least four neighbouring keys on a QWERTY keyboard.
+fn keyboard_patterns(chars: &[char], candidates: &mut Vec<Candidate>) {
+    let step = |a: char, b: char| -> Option<(i32, i32)> {
+        let (row_a, x_a, _) = KEYBOARD.get(&a)?;
+        let (row_b, x_b, _) = KEYBOARD.get(&b)?;
+        let direction = (row_b - row_a, x_b - x_a);
+        match direction {
+            (0, 2) | (0, -2) | (1, 1) | (1, -1) | (-1, 1) | (-1, -1) => Some(direction),
+            _ => None,
+        }
+    };
+    for start in 0..chars.len() {
+        let mut end = start + 1;
+        let mut turns = 0;
+        let mut direction = None;
+        while end < chars.len() {
+            let Some(next) = step(chars[end - 1], chars[end]) else { break };
+            if direction.is_some_and(|d| d != next) {
+                turns += 1;
+            }
+            direction = Some(next);
+            end += 1;
+            if end - start >= 4 {
+                let token = &chars[start..end];
+                let shifted = token.iter().filter(|c| KEYBOARD[c].2).count();
+                let shift_entropy = match shifted {
+                    0 => 0.0,
+                    s if s == token.len() => 1.0,
+                    _ => (token.len() as f64).log2(),
+                };
+                let entropy = KEYBOARD_STARTING_KEYS.log2()
+                    + ((token.len() - 1) as f64).log2()
+                    + turns as f64 * KEYBOARD_AVERAGE_DEGREE.log2()
+                    + shift_entropy;
+                candidates.push(Candidate {
+                    start,
+                    end,
+                    pattern: Pattern {
+                        kind: PatternKind::KeyboardWalk,
+                        token: token.iter().collect(),
+                        substituted: false,
+                        entropy,
+                    },
+                });
+            }
+        }
+    }
+}
+
+/// Runs of at least three letters or digits of one class spaced by the same step of 1 or 2,
+/// like `abcd`, `4321` or `acegi`.
+fn sequence_patterns(chars: &[char], candidates: &mut Vec<Candidate>) {
+    let class = |c: char| {
+        if c.is_ascii_lowercase() {
+            Some(0)
+        } else if c.is_ascii_uppercase() {
+            Some(1)
+        } else if c.is_ascii_digit() {
+            Some(2)
+        } else {
+            None
+        }
+    };
+    for start in 0..chars.len().saturating_sub(2) {
+        let Some(first_class) = class(chars[start]) else { continue };
+        let delta = chars[start + 1] as i32 - chars[start] as i32;
+        if !(1..=2).contains(&delta.abs()) {
+            continue;
+        }
+        let mut end = start + 1;
+        while end < chars.len()
+            && class(chars[end]) == Some(first_class)
+            && chars[end] as i32 - chars[end - 1] as i32 == delta
+        {
+            end += 1;
+        }
+        if end - start < 3 {
+            continue;
+        }
+        let first = chars[start];
+        let start_entropy = if "aAzZ019".contains(first) {
+            1.0
+        } else {
+            class_size(first).log2()
+        };
+        let entropy = start_entropy
+            + ((end - start) as f64).log2()
+            + if delta < 0 { 1.0 } else { 0.0 }
+            + if delta.abs() == 2 { 1.0 } else { 0.0 };
+        candidates.push(Candidate {
+            start,
+            end,
+            pattern: Pattern {
+                kind: PatternKind::Sequence,
+                token: chars[start..end].iter().collect(),
+                substituted: false,
+                entropy,
+            },
+        });
+    }
+}
+
+/// Runs of one character repeated at least three times, and chunks repeated at least twice.
+fn repeat_patterns(chars: &[char], cache: &mut HashMap<String, f64>, candidates: &mut Vec<Candidate>) {
+    for start in 0..chars.len() {
+        let mut entropy_and_end = None;
+        let run = chars[start..].iter().take_while(|c| **c == chars[start]).count();
+        if run >= 3 {
+            entropy_and_end = Some((class_size(chars[start]).log2() + (run as f64).log2(), start + run));
+        } else {
+            // The shortest chunk repeated from here, rated like a password of its own.
+            for size in 2..=(chars.len() - start) / 2 {
+                let chunk = &chars[start..start + size];
+                let repeats = chars[start..].chunks_exact(size).take_while(|c| *c == chunk).count();
+                if repeats >= 2 {
+                    let key: String = chunk.iter().collect();
+                    let chunk_entropy = match cache.get(&key) {
+                        Some(bits) => *bits,
+                        None => {
+                            let bits = minimum_entropy(chunk, cache).0;
+                            cache.insert(key, bits);
+                            bits
+                        }
+                    };
+                    entropy_and_end = Some((chunk_entropy + (repeats as f64).log2(), start + size * repeats));
+                    break;
+                }
+            }
+        }
+        if let Some((entropy, end)) = entropy_and_end {
+            candidates.push(Candidate {
+                start,
+                end,
+                pattern: Pattern {
+                    kind: PatternKind::Repeat,
+                    token: chars[start..end].iter().collect(),
+                    substituted: false,
+                    entropy,
+                },
+            });
+        }
+    }
+}
+
+/// Years from 1900 to 2099 and dates of day, month and year in any common order, with or
+/// without separators (`1987`, `120590`, `1990-05-12`, `5/12/90`).
+fn date_patterns(chars: &[char], candidates: &mut Vec<Candidate>) {
+    for start in 0..chars.len() {
+        for end in (start + 4)..=chars.len().min(start + 10) {
+            let token: String = chars[start..end].iter().collect();
+            let Some(entropy) = date_entropy(&token) else { continue };
+            candidates.push(Candidate {
+                start,
+                end,
+                pattern: Pattern {
+                    kind: PatternKind::Date,
+                    token,
+                    substituted: false,
+                    entropy,
+                },
+            });
+        }
+    }
+}
+
+/// Cost of `token` in bits if it is a year or a date.
+fn date_entropy(token: &str) -> Option<f64> {
+    if token.chars().all(|c| c.is_ascii_digit()) {
+        let splits: &[[usize; 2]] = match token.len() {
+            4 => {
+                let year: u32 = token.parse().ok()?;
+                return (1900..=2099).contains(&year).then(|| 200f64.log2());
+            }
+            6 => &[[2, 4]],
+            8 => &[[2, 4], [4, 6]],
+            _ => return None,
+        };
+        return splits
+            .iter()
+            .filter_map(|[a, b]| date_parts_entropy(&[&token[..*a], &token[*a..*b], &token[*b..]]))
+            .reduce(f64::min);
+    }
+
+    // Two identical separators between the parts cost two more bits.
+    let separators: Vec<char> = token.chars().filter(|c| !c.is_ascii_digit()).collect();
+    match separators.as_slice() {
+        [a, b] if a == b && "/-._ ".contains(*a) => {
+            let parts: Vec<&str> = token.split(*a).collect();
+            date_parts_entropy(&parts).map(|bits| bits + 2.0)
+        }
+        _ => None,
+    }
+}
+
+/// Cost in bits of a day, a month and a year in one of the orders day-month-year,
+/// month-day-year or year-month-day, or `None` if the parts are not a date.
+fn date_parts_entropy(parts: &[&str]) -> Option<f64> {
+    let [a, b, c] = parts else { return None };
+    let number = |part: &str| -> Option<u32> {
+        if part.is_empty() || part.len() > 4 || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        part.parse().ok()
+    };
+    let day = |part: &str| number(part).filter(|d| part.len() <= 2 && (1..=31).contains(d));
+    let month = |part: &str| number(part).filter(|m| part.len() <= 2 && (1..=12).contains(m));
+    let year = |part: &str| -> Option<f64> {
+        match part.len() {
+            2 => number(part).map(|_| 100f64.log2()),
+            4 => number(part).filter(|y| (1900..=2099).contains(y)).map(|_| 200f64.log2()),
+            _ => None,
+        }
+    };
+    let day_month = (31.0f64 * 12.0).log2();
+    let orders = [
+        day(a).and(month(b)).and(year(c)),
+        month(a).and(day(b)).and(year(c)),
+        year(a).filter(|_| month(b).is_some() && day(c).is_some()),
+    ];
+    orders.into_iter().flatten().reduce(f64::min).map(|bits| bits + day_month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(password: &str) -> Vec<PatternKind> {
+        estimate_strength(password).patterns.iter().map(|p| p.kind).collect()
+    }
+
+    #[test]
+    fn test_common_passwords_are_very_weak() {
+        for password in ["password", "123456", "qwerty", "P@ssw0rd", "letmein", "iloveyou"] {
+            let estimate = estimate_strength(password);
+            assert_eq!(estimate.strength, Strength::VeryWeak, "{}: {}", password, estimate);
+            assert!(!estimate.patterns.is_empty(), "{}", password);
+        }
+        assert!(estimate_strength("P@ssw0rd").patterns[0].substituted);
+    }
+
+    #[test]
+    fn test_patterns_are_recognized() {
+        assert_eq!(kinds("zxcvbnm,./"), vec![PatternKind::KeyboardWalk]);
+        assert_eq!(kinds("lmnopqrs"), vec![PatternKind::Sequence]);
+        assert_eq!(kinds("xxxxxxxx"), vec![PatternKind::Repeat]);
+        assert_eq!(kinds("k9Rk9Rk9R"), vec![PatternKind::Repeat]);
+        assert_eq!(kinds("12/05/1990"), vec![PatternKind::Date]);
+        assert_eq!(kinds("19900512"), vec![PatternKind::Date]);
+        assert!(kinds("Sunshine1987").contains(&PatternKind::Date));
+    }
+
+    #[test]
+    fn test_random_passwords_are_strong() {
+        let estimate = estimate_strength("vT8#qL2!zW9m$Kp4");
+        assert!(estimate.strength >= Strength::Strong, "{}", estimate);
+        let estimate = estimate_strength("k7Qx");
+        assert_eq!(estimate.strength, Strength::VeryWeak, "{}", estimate);
+        assert!(estimate_strength("").entropy == 0.0);
+    }
+
+    #[test]
+    fn test_long_passwords_rate_the_tail_as_random() {
+        let password = "k9Rk9Rk9R".repeat(500);
+        let head = estimate_strength(&password[..MAX_ANALYZED_LENGTH]).entropy;
+        let estimate = estimate_strength(&password);
+        let tail = (password.len() - MAX_ANALYZED_LENGTH) as f64 * cardinality(&['k', '9', 'R']).log2();
+        assert!((estimate.entropy - head - tail).abs() < 1e-6, "{}", estimate);
+        assert_eq!(estimate.strength, Strength::VeryStrong);
+    }
+
+    #[test]
+    fn test_patterns_cost_less_than_random_characters() {
+        let walk = estimate_strength("qwertyuiop").entropy;
+        let random = estimate_strength("qpwoeiruty").entropy;
+        assert!(walk < random / 2.0, "walk {} random {}", walk, random);
+    }
+
+    #[test]
+    fn test_strength_names() {
+        for name in Strength::NAMES {
+            assert_eq!(name.parse::<Strength>().unwrap().to_string(), name);
+        }
+        assert_eq!("Very Strong".parse::<Strength>(), Ok(Strength::VeryStrong));
+        assert!("great".parse::<Strength>().is_err());
+        assert!(Strength::Weak < Strength::Fair);
+        assert_eq!(Strength::from_entropy(59.9), Strength::Fair);
+    }
+}
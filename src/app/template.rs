@@ -0,0 +1,326 @@
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use std::fmt;
+use crate::app::error::{AppError, Result};
+use crate::app::policy::DEFAULT_SYMBOLS;
+use crate::app::rng::with_rng;
+
+/// Longest password a template may produce.
+pub const MAX_TEMPLATE_LENGTH: usize = 256;
+
+const LOWER_CONSONANTS: &str = "bcdfghjklmnpqrstvwxyz";
+const LOWER_VOWELS: &str = "aeiou";
+const LOWER_LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
+const DIGITS: &str = "0123456789";
+
+/// The characters a placeholder stands for, or `None` if `c` is not a placeholder.
+fn placeholder(c: char) -> Option<Vec<char>> {
+    let upper = |s: &str| s.to_uppercase();
+    let set = match c {
+        'c' => LOWER_CONSONANTS.to_string(),
+        'C' => upper(LOWER_CONSONANTS),
+        'v' => LOWER_VOWELS.to_string(),
+        'V' => upper(LOWER_VOWELS),
+        'l' => LOWER_LETTERS.to_string(),
+        'L' => upper(LOWER_LETTERS),
+        'a' => format!("{}{}", LOWER_LETTERS, upper(LOWER_LETTERS)),
+        '9' => DIGITS.to_string(),
+        '!' => DEFAULT_SYMBOLS.to_string(),
+        'x' => format!("{}{}{}", LOWER_LETTERS, upper(LOWER_LETTERS), DIGITS),
+        '*' => format!("{}{}{}{}", LOWER_LETTERS, upper(LOWER_LETTERS), DIGITS, DEFAULT_SYMBOLS),
+        _ => return None,
+    };
+    Some(set.chars().collect())
+}
+
+/// One character of a templated password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Slot {
+    /// Always this character.
+    Literal(char),
+    /// One character drawn from the set.
+    OneOf(Vec<char>),
+}
+
+/// A fixed password format, such as `Cvcc-9999-!!` or `x{5}(-x{5}){3}`.
+///
+/// * `c`/`C` lowercase/uppercase consonant, `v`/`V` vowel, `l`/`L` letter, `a` letter of
+///   either case, `9` digit, `!` symbol, `x` letter or digit, `*` letter, digit or symbol.
+/// * `[...]` one character of a set, with ranges: `[A-F0-9]`.
+/// * `{n}` repeats the previous item `n` times; `(...)` groups items: `(9{3}-){2}9{4}`.
+/// * `\` makes the next character literal. Other characters that are neither letters nor
+///   digits (`-`, `_`, `.`, `@`, spaces, ...) are literal as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    slots: Vec<Slot>,
+}
+
+impl Template {
+    /// Parses a template.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Template>` - The template, or `AppError::InvalidInput` naming the problem and
+    ///   its position if it is malformed, empty or produces more than `MAX_TEMPLATE_LENGTH` characters.
+    pub fn parse(source: &str) -> Result<Self> {
+        let invalid = |e: String| AppError::InvalidInput(format!("Invalid template '{}': {}", source, e));
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            position: 0,
+        };
+        let slots = parser.sequence(None).map_err(invalid)?;
+        if slots.is_empty() {
+            return Err(invalid("the template is empty".to_string()));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            slots,
+        })
+    }
+
+    /// Number of characters of the passwords it produces.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Always false: a template produces at least one character.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Entropy, in bits, of the passwords it produces. Literal characters add none.
+    pub fn entropy(&self) -> f64 {
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                Slot::Literal(_) => 0.0,
+                Slot::OneOf(set) => (set.len() as f64).log2(),
+            })
+            .sum()
+    }
+
+    /// Generates a password in this format.
+    pub fn generate(&self) -> String {
+        with_rng(|rng| self.generate_with(rng))
+    }
+
+    /// Generates a password like `generate`, drawing randomness from `rng`.
+    pub fn generate_with<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                Slot::Literal(c) => *c,
+                Slot::OneOf(set) => *set.choose(rng).expect("parsed sets are not empty"),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Recursive-descent parser over the characters of a template.
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.position).copied();
+        self.position += 1;
+        c
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Error message about the character just read.
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {}", message, self.position)
+    }
+
+    /// Parses items up to the end of the template, or up to `)` inside a group.
+    fn sequence(&mut self, group: Option<usize>) -> std::result::Result<Vec<Slot>, String> {
+        let mut slots = Vec::new();
+        loop {
+            let item = match self.next() {
+                None => match group {
+                    Some(start) => return Err(format!("unclosed '(' at position {}", start)),
+                    None => break,
+                },
+                Some(')') if group.is_some() => break,
+                Some('(') => {
+                    let start = self.position;
+                    let items = self.sequence(Some(start))?;
+                    if items.is_empty() {
+                        return Err(format!("empty group at position {}", start));
+                    }
+                    items
+                }
+                Some('[') => vec![Slot::OneOf(self.set()?)],
+                Some('\\') => match self.next() {
+                    Some(c) => vec![Slot::Literal(c)],
+                    None => return Err("'\\' at the end of the template".to_string()),
+                },
+                Some(c @ (')' | ']' | '{' | '}')) => {
+                    return Err(self.error(&format!("unexpected '{}' (write '\\{}' for the character)", c, c)))
+                }
+                Some(c) => match placeholder(c) {
+                    Some(set) => vec![Slot::OneOf(set)],
+                    None if c.is_alphanumeric() => {
+                        return Err(self.error(&format!(
+                            "unknown placeholder '{}' (write '\\{}' for the character)",
+                            c, c
+                        )))
+                    }
+                    None if c.is_control() => return Err(self.error("control character")),
+                    None => vec![Slot::Literal(c)],
+                },
+            };
+            let count = if self.peek() == Some('{') {
+                self.next();
+                self.count()?
+            } else {
+                1
+            };
+            if slots.len() + item.len() * count > MAX_TEMPLATE_LENGTH {
+                return Err(format!("the template produces more than {} characters", MAX_TEMPLATE_LENGTH));
+            }
+            for _ in 0..count {
+                slots.extend(item.iter().cloned());
+            }
+        }
+        Ok(slots)
+    }
+
+    /// Parses the repetition count after `{`, up to `}`.
+    fn count(&mut self) -> std::result::Result<usize, String> {
+        let start = self.position;
+        let mut digits = String::new();
+        loop {
+            match self.next() {
+                Some('}') => break,
+                Some(c) if c.is_ascii_digit() => digits.push(c),
+                Some(_) => return Err(self.error("a repetition count must be a number")),
+                None => return Err(format!("unclosed '{{' at position {}", start)),
+            }
+        }
+        match digits.parse::<usize>() {
+            Ok(count) if (1..=MAX_TEMPLATE_LENGTH).contains(&count) => Ok(count),
+            _ => Err(format!(
+                "the repetition count at position {} must be between 1 and {}",
+                start, MAX_TEMPLATE_LENGTH
+            )),
+        }
+    }
+
+    /// Parses a character set after `[`, up to `]`.
+    fn set(&mut self) -> std::result::Result<Vec<char>, String> {
+        let start = self.position;
+        let mut set: Vec<char> = Vec::new();
+        loop {
+            let c = match self.next() {
+                Some(']') => break,
+                Some('\\') => self.next().ok_or_else(|| "'\\' at the end of the template".to_string())?,
+                Some(c) if c.is_control() => return Err(self.error("control character")),
+                Some(c) => c,
+                None => return Err(format!("unclosed '[' at position {}", start)),
+            };
+            // `a-z` is a range; a `-` first or last in the set is literal.
+            if self.peek() == Some('-') && self.chars.get(self.position + 1).is_some_and(|c| *c != ']') {
+                self.next();
+                let end = match self.next() {
+                    Some('\\') => self.next(),
+                    other => other,
+                }
+                .ok_or_else(|| format!("unclosed '[' at position {}", start))?;
+                if end < c {
+                    return Err(self.error(&format!("reversed range '{}-{}'", c, end)));
+                }
+                set.extend(c..=end);
+            } else {
+                set.push(c);
+            }
+        }
+        set.sort_unstable();
+        set.dedup();
+        if set.is_empty() {
+            return Err(format!("empty set at position {}", start));
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_format() {
+        let template = Template::parse("Cvcc-9999-!!").unwrap();
+        assert_eq!(template.len(), 12);
+        for _ in 0..50 {
+            let password: Vec<char> = template.generate().chars().collect();
+            assert!(LOWER_CONSONANTS.to_uppercase().contains(password[0]));
+            assert!(LOWER_VOWELS.contains(password[1]));
+            assert!(LOWER_CONSONANTS.contains(password[2]) && LOWER_CONSONANTS.contains(password[3]));
+            assert_eq!(password[4], '-');
+            assert!(password[5..9].iter().all(|c| c.is_ascii_digit()));
+            assert_eq!(password[9], '-');
+            assert!(password[10..].iter().all(|c| DEFAULT_SYMBOLS.contains(*c)));
+        }
+        let expected = 21f64.log2() * 3.0 + 5f64.log2() + 10f64.log2() * 4.0 + 2.0 * (DEFAULT_SYMBOLS.len() as f64).log2();
+        assert!((template.entropy() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_groups_sets_and_escapes() {
+        let template = Template::parse("x{5}(-x{5}){3}").unwrap();
+        assert_eq!(template.len(), 23);
+        let password = template.generate();
+        let groups: Vec<&str> = password.split('-').collect();
+        assert_eq!(groups.len(), 4);
+        assert!(groups.iter().all(|g| g.len() == 5 && g.chars().all(|c| c.is_ascii_alphanumeric())));
+
+        let template = Template::parse("[A-F0-9]{8}").unwrap();
+        assert!(template.generate().chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_lowercase()));
+        assert!((template.entropy() - 32.0).abs() < 1e-9);
+
+        let template = Template::parse("\\I\\D-[-_]\\{9\\}").unwrap();
+        let password = template.generate();
+        assert!(password.starts_with("ID-"), "{}", password);
+        assert!(password.ends_with('}'), "{}", password);
+        assert_eq!(template.len(), 7);
+    }
+
+    #[test]
+    fn test_malformed_templates() {
+        for (source, message) in [
+            ("", "empty"),
+            ("Cvq", "unknown placeholder 'q' (write '\\q' for the character) at position 3"),
+            ("9{0}", "between 1 and"),
+            ("9{x}", "must be a number"),
+            ("9{4", "unclosed '{' at position 2"),
+            ("(99", "unclosed '(' at position 1"),
+            ("99)", "unexpected ')'"),
+            ("{3}", "unexpected '{'"),
+            ("()", "empty group"),
+            ("[]", "empty set"),
+            ("[z-a]", "reversed range"),
+            ("[abc", "unclosed '['"),
+            ("9\\", "at the end"),
+            ("x{200}x{57}", "more than 256"),
+        ] {
+            match Template::parse(source) {
+                Err(AppError::InvalidInput(e)) => assert!(e.contains(message), "{}: {}", source, e),
+                other => panic!("{}: {:?}", source, other),
+            }
+        }
+    }
+}
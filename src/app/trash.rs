@@ -0,0 +1,370 @@
+use keyring::Error as KeyringError;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::app::entry::EntryRecord;
+use crate::app::error::{AppError, Result};
+use crate::app::history::{current_user, get_history, set_history, HistoryEntry};
+use crate::app::index::load_index;
+use crate::app::keyring::{delete_from_keyring, get_entry_record, get_from_keyring, save_to_keyring,
+                          set_entry_record, set_password_type};
+use crate::app::store::store;
+use crate::app::config::{config, update_profile_config};
+use crate::app::profile::active_profile;
+use crate::app::TRASH_KEY;
+
+/// Number of days deleted entries are kept when no retention has been configured.
+pub const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+
+/// Serializes read-modify-write cycles on the trash within this process.
+static TRASH_LOCK: Mutex<()> = Mutex::new(());
+
+/// A deleted entry kept in the trash, with everything needed to restore it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashedEntry {
+    /// Name of the application.
+    pub name: String,
+    /// The password at the time of deletion.
+    pub password: String,
+    /// Password type: "auto", "pin" or "custom".
+    #[serde(rename = "type")]
+    pub password_type: String,
+    /// Username, URLs, notes and custom fields.
+    #[serde(default, skip_serializing_if = "EntryRecord::is_empty")]
+    pub record: EntryRecord,
+    /// Previous passwords, most recent first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<HistoryEntry>,
+    /// Unix timestamp of the deletion.
+    pub deleted_at: u64,
+    /// OS user that deleted the entry.
+    pub deleted_by: String,
+}
+
+/// Lists the entries in the trash, most recently deleted first.
+///
+/// # Returns
+///
+/// * `Result<Vec<TrashedEntry>>` - The trashed entries (empty if there are none), otherwise returns an `AppError`.
+pub fn list_trash() -> Result<Vec<TrashedEntry>> {
+    match store().get(TRASH_KEY) {
+        Ok(data) => serde_json::from_str(&data).map_err(|e| AppError::parse(TRASH_KEY, e)),
+        Err(KeyringError::NoEntry) => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes the trash back, deleting the record when it is empty.
+fn save_trash(trash: &[TrashedEntry]) -> Result<()> {
+    if trash.is_empty() {
+        return match store().delete(TRASH_KEY) {
+            Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        };
+    }
+    let data = serde_json::to_string(trash).map_err(|e| AppError::parse(TRASH_KEY, e))?;
+    Ok(store().set(TRASH_KEY, &data)?)
+}
+
+/// Applies `f` to the stored trash and writes the result back, holding the store lock.
+fn update_trash<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce(&mut Vec<TrashedEntry>) -> Result<T>,
+{
+    let _guard = TRASH_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = store();
+    let _store_lock = store.lock()?;
+    let mut trash = list_trash()?;
+    let result = f(&mut trash)?;
+    save_trash(&trash)?;
+    Ok(result)
+}
+
+/// Moves an application to the trash: its password, type, details and history are kept
+/// there and the entry itself is deleted.
+///
+/// # Arguments
+///
+/// * `app_name` - The name of the application.
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if successful, or `AppError::NotFound` if the application has no password.
+pub fn move_to_trash(app_name: &str) -> Result<()> {
+    let password = get_from_keyring(app_name)?;
+    let password_type = load_index()?
+        .get(app_name)
+        .map(|e| e.password_type.clone())
+        .unwrap_or_else(|| "auto".to_string());
+    let trashed = TrashedEntry {
+        name: app_name.to_string(),
+        password,
+        password_type,
+        record: get_entry_record(app_name)?,
+        history: get_history(app_name)?,
+        deleted_at: now(),
+        deleted_by: current_user(),
+    };
+    update_trash(|trash| {
+        trash.insert(0, trashed);
+        Ok(())
+    })?;
+    delete_from_keyring(app_name)
+}
+
+/// Restores the most recently deleted entry named `app_name` from the trash.
+///
+/// # Arguments
+///
+/// * `app_name` - The name of the application.
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if restored, `AppError::NotFound` if it is not
+///   in the trash, or `AppError::AlreadyExists` if an entry with the same name exists.
+pub fn restore_from_trash(app_name: &str) -> Result<()> {
+    match get_from_keyring(app_name) {
+        Ok(_) => return Err(AppError::AlreadyExists(app_name.to_string())),
+        Err(AppError::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+
+    let entry = list_trash()?
+        .into_iter()
+        .find(|e| e.name == app_name)
+        .ok_or_else(|| AppError::NotFound(app_name.to_string()))?;
+
+    // The entry only leaves the trash once it is fully restored; a partial restore is undone
+    // so it can be tried again
+    save_to_keyring(&entry.name, &entry.password)?;
+    let restored = set_password_type(&entry.name, &entry.password_type)
+        .and_then(|()| set_entry_record(&entry.name, &entry.record))
+        .and_then(|()| set_history(&entry.name, &entry.history));
+    if let Err(e) = restored {
+        let _ = delete_from_keyring(&entry.name);
+        return Err(e);
+    }
+
+    update_trash(|trash| {
+        if let Some(position) = trash.iter().position(|e| *e == entry) {
+            trash.remove(position);
+        }
+        Ok(())
+    })
+}
+
+/// Permanently deletes entries from the trash.
+///
+/// # Arguments
+///
+/// * `app_name` - Only purge entries with this name, or `None` to empty the trash.
+///
+/// # Returns
+///
+/// * `Result<usize>` - The number of entries purged, otherwise returns an `AppError`.
+pub fn purge_trash(app_name: Option<&str>) -> Result<usize> {
+    update_trash(|trash| {
+        let before = trash.len();
+        trash.retain(|e| app_name.is_some_and(|name| e.name != name));
+        Ok(before - trash.len())
+    })
+}
+
+/// Permanently deletes entries that have been in the trash longer than the retention period.
+/// Should be called at application startup.
+///
+/// # Returns
+///
+/// * `Result<usize>` - The number of entries purged, otherwise returns an `AppError`.
+pub fn purge_expired_trash() -> Result<usize> {
+    let retention_days = trash_retention_days();
+    if retention_days == 0 || list_trash()?.is_empty() {
+        return Ok(0);
+    }
+    let cutoff = now().saturating_sub(retention_days * 86_400);
+    update_trash(|trash| {
+        let before = trash.len();
+        trash.retain(|e| e.deleted_at > cutoff);
+        Ok(before - trash.len())
+    })
+}
+
+/// Returns how many days deleted entries are kept before being purged (0 keeps them forever),
+/// from `trash.retention_days` in the configuration.
+pub fn trash_retention_days() -> u64 {
+    config().trash.retention_days
+}
+
+/// Sets how many days deleted entries of the active profile are kept before being purged
+/// (0 keeps them forever), saving `trash.retention_days` in its section of the configuration file.
+pub fn set_trash_retention_days(days: u64) -> Result<()> {
+    update_profile_config(&active_profile(), |config| {
+        config.trash.retention_days = days;
+        Ok(())
+    })
+}
+
+/// Returns the current Unix timestamp in seconds.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::history::push_history;
+    use crate::app::index::list_entries;
+    use crate::app::store::{scope_store, MemoryStore, SecretStore};
+    use std::sync::Arc;
+
+    fn cleanup(app_name: &str) {
+        let _ = delete_from_keyring(app_name);
+        let _ = purge_trash(Some(app_name));
+    }
+
+    #[test]
+    fn test_trash_and_restore_keeps_everything() {
+        let app_name = "test_trash_restore_app";
+        cleanup(app_name);
+        save_to_keyring(app_name, "current").unwrap();
+        set_password_type(app_name, "custom").unwrap();
+        let record = EntryRecord {
+            username: Some("me".to_string()),
+            ..EntryRecord::default()
+        };
+        set_entry_record(app_name, &record).unwrap();
+        push_history(app_name, "older", "update").unwrap();
+
+        move_to_trash(app_name).unwrap();
+        assert!(get_from_keyring(app_name).is_err());
+        assert!(!list_entries().unwrap().iter().any(|e| e.name == app_name));
+        let trashed = list_trash().unwrap().into_iter().find(|e| e.name == app_name).unwrap();
+        assert_eq!(trashed.password, "current");
+        assert!(trashed.deleted_at > 0);
+
+        restore_from_trash(app_name).unwrap();
+        assert_eq!(get_from_keyring(app_name).unwrap(), "current");
+        assert_eq!(load_index().unwrap().get(app_name).unwrap().password_type, "custom");
+        assert_eq!(get_entry_record(app_name).unwrap(), record);
+        assert_eq!(get_history(app_name).unwrap()[0].password, "older");
+        assert!(!list_trash().unwrap().iter().any(|e| e.name == app_name));
+
+        cleanup(app_name);
+    }
+
+    #[test]
+    fn test_restore_refuses_to_overwrite_and_purge_removes() {
+        let app_name = "test_trash_conflict_app";
+        cleanup(app_name);
+        save_to_keyring(app_name, "first").unwrap();
+        move_to_trash(app_name).unwrap();
+        save_to_keyring(app_name, "second").unwrap();
+
+        assert!(matches!(restore_from_trash(app_name), Err(AppError::AlreadyExists(_))));
+        assert_eq!(get_from_keyring(app_name).unwrap(), "second");
+
+        assert_eq!(purge_trash(Some(app_name)).unwrap(), 1);
+        delete_from_keyring(app_name).unwrap();
+        assert!(matches!(restore_from_trash(app_name), Err(AppError::NotFound(_))));
+    }
+
+    /// Memory store whose writes fail for keys ending in `fail_suffix` while it is set.
+    #[derive(Default)]
+    struct FailingStore {
+        inner: MemoryStore,
+        fail_suffix: Mutex<Option<&'static str>>,
+    }
+
+    impl SecretStore for FailingStore {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn get(&self, key: &str) -> std::result::Result<String, KeyringError> {
+            self.inner.get(key)
+        }
+
+        fn set(&self, key: &str, value: &str) -> std::result::Result<(), KeyringError> {
+            match *self.fail_suffix.lock().unwrap() {
+                Some(suffix) if key.ends_with(suffix) => Err(KeyringError::NoStorageAccess("write failed".into())),
+                _ => self.inner.set(key, value),
+            }
+        }
+
+        fn delete(&self, key: &str) -> std::result::Result<(), KeyringError> {
+            self.inner.delete(key)
+        }
+
+        fn list(&self) -> std::result::Result<Vec<String>, KeyringError> {
+            self.inner.list()
+        }
+    }
+
+    #[test]
+    fn test_failed_restore_keeps_entry_in_trash() {
+        let failing = Arc::new(FailingStore::default());
+        let _store = scope_store(failing.clone());
+        let app_name = "test_trash_failed_restore";
+        save_to_keyring(app_name, "pw").unwrap();
+        let record = EntryRecord {
+            notes: Some("keep me".to_string()),
+            ..EntryRecord::default()
+        };
+        set_entry_record(app_name, &record).unwrap();
+        move_to_trash(app_name).unwrap();
+
+        *failing.fail_suffix.lock().unwrap() = Some(crate::app::ENTRY_RECORD_SUFFIX);
+        assert!(matches!(restore_from_trash(app_name), Err(AppError::Backend(_))));
+        assert!(matches!(get_from_keyring(app_name), Err(AppError::NotFound(_))));
+        assert!(list_trash().unwrap().iter().any(|e| e.name == app_name));
+
+        *failing.fail_suffix.lock().unwrap() = None;
+        restore_from_trash(app_name).unwrap();
+        assert_eq!(get_from_keyring(app_name).unwrap(), "pw");
+        assert_eq!(get_entry_record(app_name).unwrap(), record);
+        assert!(list_trash().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_purge_expired_keeps_recent_entries() {
+        let app_name = "test_trash_expired_app";
+        cleanup(app_name);
+        save_to_keyring(app_name, "pw").unwrap();
+        move_to_trash(app_name).unwrap();
+        update_trash(|trash| {
+            let mut old = trash.iter().find(|e| e.name == app_name).unwrap().clone();
+            old.deleted_at = 1;
+            trash.push(old);
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(purge_expired_trash().unwrap() >= 1);
+        let remaining: Vec<_> = list_trash().unwrap().into_iter().filter(|e| e.name == app_name).collect();
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].deleted_at > 1);
+
+        cleanup(app_name);
+    }
+
+    #[test]
+    fn test_trash_of_many_entries_fits_a_keyring_sized_store() {
+        let _store = crate::app::store::scoped_keyring_sized_store();
+        let names: Vec<String> = (0..40).map(|i| format!("test_trash_chunked_{:02}", i)).collect();
+        for name in &names {
+            save_to_keyring(name, "a-secret-long-enough-to-matter").unwrap();
+            move_to_trash(name).unwrap();
+        }
+        assert_eq!(list_trash().unwrap().len(), names.len());
+        restore_from_trash(&names[0]).unwrap();
+        assert_eq!(get_from_keyring(&names[0]).unwrap(), "a-secret-long-enough-to-matter");
+    }
+
+    #[test]
+    fn test_move_missing_app_is_no_entry() {
+        assert!(matches!(move_to_trash("test_trash_missing_app"), Err(AppError::NotFound(_))));
+    }
+}
@@ -0,0 +1,550 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use keyring::Error as KeyringError;
+use once_cell::sync::Lazy;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use crate::app::error::AppError;
+use crate::app::filelock::FileLock;
+use crate::app::store::SecretStore;
+use crate::app::{APP_SERVICE, MASTER_PASSWORD_ENV_VAR, VAULT_PATH_ENV_VAR};
+
+/// Current on-disk format version of the vault file.
+const VAULT_FORMAT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Explicit vault path set from the command line (`--vault`).
+static VAULT_PATH_OVERRIDE: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+/// Argon2id cost parameters used to derive the vault key from the master password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory cost in KiB.
+    pub m_cost: u32,
+    /// Number of iterations.
+    pub t_cost: u32,
+    /// Degree of parallelism.
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// OWASP recommended minimum for Argon2id (19 MiB, 2 iterations, 1 lane).
+    fn default() -> Self {
+        Self {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+/// The vault file as stored on disk. Everything but the KDF parameters is encrypted.
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Secret store that keeps every entry and its metadata in a single file encrypted with
+/// XChaCha20-Poly1305, using a key derived from a master password with Argon2id.
+///
+/// The whole vault is held decrypted in memory while the store is open and re-encrypted
+/// with a fresh nonce on every write. A missing file is treated as a new, empty vault
+/// that is created on the first write.
+///
+/// Several processes may have the same vault open. Writes hold an exclusive lock on
+/// `<vault>.lock` and first reload the file if another process replaced it, so no
+/// process overwrites changes it has not seen; reads also pick up such changes.
+pub struct VaultStore {
+    path: PathBuf,
+    lock_path: PathBuf,
+    /// Master password, kept only until the vault file exists. Another process may
+    /// create the file first with its own salt, which then has to be used instead.
+    pending_password: Mutex<Option<String>>,
+    state: Mutex<VaultState>,
+}
+
+/// The decrypted contents of the vault and the keys needed to write it back.
+struct VaultState {
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    key: Key,
+    /// Nonce of the file version `entries` was read from (`None` if no file exists yet).
+    /// Every write uses a fresh nonce, so a different one means the file changed.
+    nonce: Option<String>,
+    entries: BTreeMap<String, String>,
+}
+
+impl VaultStore {
+    /// Opens (or prepares to create) the vault at `path` with the default KDF parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Location of the vault file.
+    /// * `master_password` - The master password used to derive the encryption key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<VaultStore, KeyringError>` - `NoStorageAccess` if the password is wrong or the file is corrupted.
+    pub fn open(path: &Path, master_password: &str) -> Result<Self, KeyringError> {
+        Self::open_with_params(path, master_password, KdfParams::default())
+    }
+
+    /// Opens the vault at `path`. `params` are only used when a new vault is created;
+    /// existing vaults always use the parameters recorded in the file.
+    pub fn open_with_params(path: &Path, master_password: &str, params: KdfParams) -> Result<Self, KeyringError> {
+        if master_password.is_empty() {
+            return Err(KeyringError::Invalid(
+                "master password".to_string(),
+                "cannot be empty".to_string(),
+            ));
+        }
+
+        let (state, pending_password) = match read_vault_file(path)? {
+            Some(file) => (decrypt(&file, master_password)?, None),
+            None => {
+                let mut salt = [0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                let state = VaultState {
+                    kdf: params,
+                    salt,
+                    key: derive_key(master_password, &salt, params)?,
+                    nonce: None,
+                    entries: BTreeMap::new(),
+                };
+                (state, Some(master_password.to_string()))
+            }
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            lock_path: path.with_extension("lock"),
+            pending_password: Mutex::new(pending_password),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Reloads `state` from disk if another process has written the vault since it was read.
+    fn refresh(&self, state: &mut VaultState) -> Result<(), KeyringError> {
+        let file = match read_vault_file(&self.path)? {
+            Some(file) => file,
+            None => {
+                if state.nonce.is_some() {
+                    // The vault was removed (e.g. its profile was deleted)
+                    state.nonce = None;
+                    state.entries.clear();
+                }
+                return Ok(());
+            }
+        };
+        if state.nonce.as_deref() == Some(file.nonce.as_str()) {
+            return Ok(());
+        }
+
+        let same_key = file.kdf == state.kdf && file.salt == encode_hex(&state.salt);
+        *state = if same_key {
+            decrypt_with_key(&file, state.key)?
+        } else {
+            // Another process created the vault with its own salt
+            let mut pending = self.pending_password.lock().unwrap_or_else(|e| e.into_inner());
+            let password = pending.as_deref().ok_or_else(|| {
+                KeyringError::NoStorageAccess("the vault was replaced by another process".into())
+            })?;
+            let refreshed = decrypt(&file, password)?;
+            *pending = None;
+            refreshed
+        };
+        Ok(())
+    }
+
+    /// Applies `f` to the latest entries and writes them back, holding the vault lock.
+    fn modify<F>(&self, f: F) -> Result<(), KeyringError>
+    where
+        F: FnOnce(&mut BTreeMap<String, String>) -> Result<(), KeyringError>,
+    {
+        let _lock = FileLock::acquire(&self.lock_path).map_err(platform_failure)?;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.refresh(&mut state)?;
+        let mut updated = state.entries.clone();
+        f(&mut updated)?;
+        let nonce = self.persist(&state, &updated)?;
+        state.entries = updated;
+        state.nonce = Some(nonce);
+        *self.pending_password.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        Ok(())
+    }
+
+    /// Encrypts `entries` and atomically replaces the vault file. Returns the new nonce.
+    fn persist(&self, state: &VaultState, entries: &BTreeMap<String, String>) -> Result<String, KeyringError> {
+        let plaintext = serde_json::to_vec(entries).map_err(platform_failure)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let cipher = XChaCha20Poly1305::new(&state.key);
+        let aad = associated_data(VAULT_FORMAT_VERSION, &state.kdf, &state.salt);
+        let ciphertext = cipher
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &aad })
+            .map_err(|_| KeyringError::PlatformFailure("failed to encrypt vault".into()))?;
+
+        let file = VaultFile {
+            version: VAULT_FORMAT_VERSION,
+            kdf: state.kdf,
+            salt: encode_hex(&state.salt),
+            nonce: encode_hex(&nonce),
+            ciphertext: encode_hex(&ciphertext),
+        };
+        let data = serde_json::to_string_pretty(&file).map_err(platform_failure)?;
+
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(platform_failure)?;
+            }
+        }
+        let tmp_path = self.path.with_extension("tmp");
+        write_private(&tmp_path, data.as_bytes()).map_err(platform_failure)?;
+        fs::rename(&tmp_path, &self.path).map_err(platform_failure)?;
+        Ok(file.nonce)
+    }
+}
+
+impl SecretStore for VaultStore {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    fn get(&self, key: &str) -> Result<String, KeyringError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.refresh(&mut state)?;
+        state.entries.get(key).cloned().ok_or(KeyringError::NoEntry)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), KeyringError> {
+        self.modify(|entries| {
+            entries.insert(key.to_string(), value.to_string());
+            Ok(())
+        })
+    }
+
+    fn delete(&self, key: &str) -> Result<(), KeyringError> {
+        self.modify(|entries| entries.remove(key).map(|_| ()).ok_or(KeyringError::NoEntry))
+    }
+
+    fn list(&self) -> Result<Vec<String>, KeyringError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.refresh(&mut state)?;
+        Ok(state.entries.keys().cloned().collect())
+    }
+
+    fn lock(&self) -> Result<Option<FileLock>, KeyringError> {
+        FileLock::acquire(&self.lock_path).map(Some).map_err(platform_failure)
+    }
+}
+
+/// Reads the vault file at `path`. Returns `None` if it does not exist.
+fn read_vault_file(path: &Path) -> Result<Option<VaultFile>, KeyringError> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(platform_failure(e)),
+    };
+    let file: VaultFile = serde_json::from_str(&data).map_err(|_| corrupted())?;
+    if file.version > VAULT_FORMAT_VERSION {
+        return Err(KeyringError::NoStorageAccess(
+            format!("vault format version {} is newer than supported", file.version).into(),
+        ));
+    }
+    Ok(Some(file))
+}
+
+/// Derives the key of `file` from the master password and decrypts it.
+fn decrypt(file: &VaultFile, master_password: &str) -> Result<VaultState, KeyringError> {
+    let salt = decode_salt(&file.salt)?;
+    decrypt_with_key(file, derive_key(master_password, &salt, file.kdf)?)
+}
+
+/// Decrypts `file` with an already derived key.
+fn decrypt_with_key(file: &VaultFile, key: Key) -> Result<VaultState, KeyringError> {
+    let salt = decode_salt(&file.salt)?;
+    let nonce = decode_hex(&file.nonce)
+        .filter(|n| n.len() == NONCE_LEN)
+        .ok_or_else(corrupted)?;
+    let ciphertext = decode_hex(&file.ciphertext).ok_or_else(corrupted)?;
+
+    let cipher = XChaCha20Poly1305::new(&key);
+    let aad = associated_data(file.version, &file.kdf, &salt);
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+        .map_err(|_| {
+            KeyringError::NoStorageAccess("wrong master password or corrupted vault".into())
+        })?;
+    let entries: BTreeMap<String, String> =
+        serde_json::from_slice(&plaintext).map_err(|_| corrupted())?;
+
+    Ok(VaultState {
+        kdf: file.kdf,
+        salt,
+        key,
+        nonce: Some(file.nonce.clone()),
+        entries,
+    })
+}
+
+fn decode_salt(salt: &str) -> Result<[u8; SALT_LEN], KeyringError> {
+    decode_hex(salt)
+        .and_then(|s| s.try_into().ok())
+        .ok_or_else(corrupted)
+}
+
+/// Sets the vault path explicitly, taking precedence over `APPPASS_VAULT`.
+pub fn set_vault_path(path: &Path) {
+    *VAULT_PATH_OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = Some(path.to_path_buf());
+}
+
+/// Returns the vault file used for the given service namespace.
+///
+/// Resolution order: `--vault`, then `APPPASS_VAULT`, then `<data dir>/apppass/<service>.vault`.
+/// An explicit path names the vault of the default `apppass` namespace; other namespaces
+/// (profiles) use a sibling file named `<stem>-<service>.vault`.
+pub fn vault_path(service: &str) -> PathBuf {
+    let explicit = VAULT_PATH_OVERRIDE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .or_else(|| std::env::var(VAULT_PATH_ENV_VAR).ok().map(PathBuf::from));
+    match explicit {
+        Some(path) if service == APP_SERVICE => path,
+        Some(path) => {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("apppass");
+            path.with_file_name(format!("{}-{}.vault", stem, service))
+        }
+        None => dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("apppass")
+            .join(format!("{}.vault", service)),
+    }
+}
+
+/// Reads the master password from `APPPASS_MASTER_PASSWORD`, or prompts for it on the terminal.
+///
+/// When the vault file does not exist yet the password is asked for twice, since a typo
+/// would leave the new vault impossible to open.
+///
+/// # Returns
+///
+/// * `Result<String>` - The master password, `AppError::InvalidInput` if the two entries of a
+///   new master password differ, or `AppError::Backend` if the terminal cannot be read.
+pub fn read_master_password(path: &Path) -> Result<String, AppError> {
+    if let Ok(password) = std::env::var(MASTER_PASSWORD_ENV_VAR) {
+        return Ok(password);
+    }
+    let prompt = |message: String| {
+        rpassword::prompt_password(message).map_err(|e| AppError::Backend(platform_failure(e)))
+    };
+    if path.exists() {
+        return prompt(format!("Master password for '{}': ", path.display()));
+    }
+    let password = prompt(format!("New master password for '{}': ", path.display()))?;
+    if prompt("Repeat the master password: ".to_string())? != password {
+        return Err(AppError::InvalidInput("The master passwords do not match".to_string()));
+    }
+    Ok(password)
+}
+
+/// Derives the 256-bit vault key from the master password with Argon2id.
+fn derive_key(master_password: &str, salt: &[u8], params: KdfParams) -> Result<Key, KeyringError> {
+    let params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+        .map_err(|e| KeyringError::Invalid("kdf parameters".to_string(), e.to_string()))?;
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(master_password.as_bytes(), salt, &mut key)
+        .map_err(|e| KeyringError::PlatformFailure(e.to_string().into()))?;
+    Ok(key)
+}
+
+/// Binds the unencrypted header to the ciphertext so it cannot be tampered with.
+fn associated_data(version: u32, kdf: &KdfParams, salt: &[u8]) -> Vec<u8> {
+    format!(
+        "apppass-vault:v{}:{}:{}:{}:{}",
+        version,
+        kdf.m_cost,
+        kdf.t_cost,
+        kdf.p_cost,
+        encode_hex(salt)
+    )
+    .into_bytes()
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    fs::write(path, data)
+}
+
+fn platform_failure<E: std::error::Error + Send + Sync + 'static>(e: E) -> KeyringError {
+    KeyringError::PlatformFailure(Box::new(e))
+}
+
+fn corrupted() -> KeyringError {
+    KeyringError::NoStorageAccess("vault file is corrupted".into())
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap parameters so tests don't spend seconds in the KDF.
+    const TEST_PARAMS: KdfParams = KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+    fn temp_vault(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!("apppass_test_{}_{}.vault", name, nanos))
+    }
+
+    fn cleanup(path: &Path) {
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(path.with_extension("lock"));
+    }
+
+    #[test]
+    fn test_vault_roundtrip_across_reopen() {
+        let path = temp_vault("roundtrip");
+        let vault = VaultStore::open_with_params(&path, "correct horse", TEST_PARAMS).unwrap();
+        vault.set("github", "s3cret").unwrap();
+        vault.set("github_type", "custom").unwrap();
+        drop(vault);
+
+        let reopened = VaultStore::open_with_params(&path, "correct horse", TEST_PARAMS).unwrap();
+        assert_eq!(reopened.get("github").unwrap(), "s3cret");
+        assert_eq!(reopened.list().unwrap(), vec!["github", "github_type"]);
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_vault_wrong_password_is_rejected() {
+        let path = temp_vault("wrong_pw");
+        let vault = VaultStore::open_with_params(&path, "right", TEST_PARAMS).unwrap();
+        vault.set("app", "value").unwrap();
+
+        let result = VaultStore::open_with_params(&path, "wrong", TEST_PARAMS);
+        assert!(matches!(result, Err(KeyringError::NoStorageAccess(_))));
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_vault_file_does_not_contain_plaintext() {
+        let path = temp_vault("plaintext");
+        let vault = VaultStore::open_with_params(&path, "pw", TEST_PARAMS).unwrap();
+        vault.set("my_bank", "hunter2hunter2").unwrap();
+
+        let data = fs::read_to_string(&path).unwrap();
+        assert!(!data.contains("my_bank"));
+        assert!(!data.contains("hunter2hunter2"));
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_vault_tampered_header_is_rejected() {
+        let path = temp_vault("tamper");
+        let vault = VaultStore::open_with_params(&path, "pw", TEST_PARAMS).unwrap();
+        vault.set("app", "value").unwrap();
+
+        let data = fs::read_to_string(&path).unwrap();
+        fs::write(&path, data.replace("\"t_cost\": 1", "\"t_cost\": 2")).unwrap();
+
+        let result = VaultStore::open_with_params(&path, "pw", TEST_PARAMS);
+        assert!(result.is_err());
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_vault_delete_missing_is_no_entry() {
+        let path = temp_vault("delete");
+        let vault = VaultStore::open_with_params(&path, "pw", TEST_PARAMS).unwrap();
+        assert!(matches!(vault.delete("missing"), Err(KeyringError::NoEntry)));
+        assert!(!path.exists(), "nothing should be written for a failed delete");
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_vault_concurrent_writers_keep_every_entry() {
+        let path = temp_vault("concurrent");
+        // Every writer opens the vault before it exists, like separate processes would
+        let vaults: Vec<_> = (0..6)
+            .map(|_| VaultStore::open_with_params(&path, "pw", TEST_PARAMS).unwrap())
+            .collect();
+        std::thread::scope(|scope| {
+            for (t, vault) in vaults.iter().enumerate() {
+                scope.spawn(move || {
+                    for i in 0..10 {
+                        vault.set(&format!("app_{}_{}", t, i), "secret").unwrap();
+                    }
+                });
+            }
+        });
+
+        let reopened = VaultStore::open_with_params(&path, "pw", TEST_PARAMS).unwrap();
+        assert_eq!(reopened.list().unwrap().len(), 60);
+        // Instances see writes made through the others
+        assert_eq!(vaults[0].get("app_5_9").unwrap(), "secret");
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_vault_rejects_empty_master_password() {
+        let path = temp_vault("empty_pw");
+        assert!(VaultStore::open_with_params(&path, "", TEST_PARAMS).is_err());
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let bytes = [0u8, 1, 127, 128, 255];
+        assert_eq!(decode_hex(&encode_hex(&bytes)).unwrap(), bytes);
+        assert!(decode_hex("abc").is_none());
+        assert!(decode_hex("zz").is_none());
+    }
+}
@@ -0,0 +1,105 @@
+//! Generate, store and manage application passwords.
+//!
+//! `apppass` is both a command-line tool and a library. The `apppass` binary is a thin layer
+//! over this crate: the `console` feature adds the command line and interactive console,
+//! the `tui` feature adds the terminal UI ([`ui::run_tui`]). With both features disabled
+//! only the library is built.
+//!
+//! # Stable API
+//!
+//! The items re-exported at the crate root are the supported API:
+//!
+//! * **Store handle** - [`init_store`] opens a [`Backend`] (OS keyring, encrypted vault or
+//!   in-memory) for the active profile, [`set_store`] installs any [`SecretStore`], and
+//!   [`store`] returns the handle every other function uses. [`migrate_store`] upgrades data
+//!   written by older releases to [`STORE_SCHEMA_VERSION`].
+//! * **Entries** - [`save_to_keyring`], [`get_from_keyring`], [`update_password`],
+//!   [`delete_password`], [`list_entries`], plus details ([`edit_entry`], [`get_entry_record`])
+//!   and history ([`get_history`], [`restore_password`]).
+//! * **Strength** - [`estimate_strength`] rates a password chosen by a person, looking for
+//!   common passwords, words, keyboard walks, sequences, repeats and dates;
+//!   [`save_custom_password`] and [`update_password`] reject one weaker than the configured
+//!   minimum ([`check_password_strength`]), which also rates a password found in a local
+//!   breached-password corpus ([`BreachCorpus`], [`breach_count`]) as very weak.
+//! * **Generators** - [`generate_password`] and [`generate_memorizable`] (a [`Passphrase`]
+//!   from a [`Wordlist`], see [`generate_passphrase`]) return a password without saving it;
+//!   [`generate_save_safety_password`] and [`generate_memorizable_password`] generate and
+//!   save one. Random passwords follow a [`PasswordPolicy`] (character classes, minimum
+//!   counts, excluded characters) or a [`Template`] (a fixed format such as `Cvcc-9999-!!`),
+//!   which is stored with the entry ([`entry_policy`], [`set_entry_policy`]) and reused by
+//!   [`update_password_regenerate`].
+//! * **PINs** - [`generate_pin`] returns a numeric PIN that is not a sequence, repeat, common
+//!   PIN or date ([`pin_weakness`]); [`generate_save_pin`] saves one and records the entry as a
+//!   PIN ([`is_pin_entry`]), so [`update_password_regenerate`] keeps producing PINs.
+//! * **Derivation** - [`derive_password`] computes a site password from a master secret and
+//!   [`DeriveParams`] (site, username, counter, length and policy) through Argon2id, so it can
+//!   be reproduced anywhere without being stored; [`save_derived_site`] keeps the parameters.
+//! * **Randomness** - every generator draws from the operating system's CSPRNG through
+//!   [`with_rng`]; [`with_seed`] replaces it with a seeded generator on the current thread,
+//!   so tests can reproduce what is generated.
+//! * **Audit** - [`audit`] scans every entry for weak, breached, reused and old passwords,
+//!   missing details and expired OTPs, and returns an [`AuditReport`] that renders as text or
+//!   Markdown and serializes to JSON.
+//! * **Import/export** - [`export_passwords`] and [`import_passwords`] (CSV).
+//! * **One-time passwords** - [`generate_otp`] and [`cleanup_expired_otps`].
+//! * **Configuration** - [`init_config`] loads the user's `config.toml` (default length,
+//!   password policy, OTP lifetime, ...); without it the generators use [`Config::default`].
+//!
+//! Every fallible function returns [`Result`], whose error is [`AppError`]. Only the
+//! [`SecretStore`] backends themselves ([`VaultStore::open`], [`Backend::purge`] and the trait
+//! methods) speak `keyring::Error`, which [`AppError::Backend`] wraps. Application names are
+//! checked with [`validate_app_name`] whenever a password is saved.
+//!
+//! The [`app`] module holds the implementation, including the pieces only the binary uses
+//! (profiles, `doctor`, auto-lock). Its layout may change between releases.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::Arc;
+//! use apppass::{generate_password, get_from_keyring, list_entries, save_to_keyring, set_store, MemoryStore};
+//!
+//! set_store(Arc::new(MemoryStore::new()));
+//!
+//! let password = generate_password(24)?;
+//! save_to_keyring("github", &password)?;
+//!
+//! assert_eq!(get_from_keyring("github")?, password);
+//! assert_eq!(list_entries()?[0].name, "github");
+//! # Ok::<(), apppass::AppError>(())
+//! ```
+
+pub mod app;
+
+#[cfg(feature = "tui")]
+pub mod ui;
+
+pub use app::audit::{audit, audit_at, AuditOptions, AuditReport, EntryBreakdown, Finding, FindingKind,
+                     DEFAULT_MAX_AGE_DAYS};
+pub use app::breach::{breach_count, check_entries, BreachCorpus};
+pub use app::config::{config, init_config, set_config, Config, DEFAULT_OTP_TTL, DEFAULT_PASSWORD_LENGTH};
+pub use app::derive::{derive_password, forget_derived_site, get_derived_site, list_derived_sites, save_derived_site,
+                      DeriveParams, DEFAULT_DERIVE_LENGTH, DERIVE_KDF};
+pub use app::entry::{EntryChanges, EntryRecord};
+pub use app::error::{AppError, Result};
+pub use app::history::{get_history, HistoryEntry};
+pub use app::index::{list_entries, IndexEntry};
+pub use app::keys::{validate_app_name, MAX_APP_NAME_LEN};
+pub use app::migrate::{migrate_store, MigrationReport, STORE_SCHEMA_VERSION};
+pub use app::keyring::{delete_from_keyring, get_entry_record, get_from_keyring, save_to_keyring, set_entry_record};
+pub use app::otp::{cleanup_expired_otps, generate_otp};
+pub use app::password::{delete_password, edit_entry, entry_entropy, entry_policy, export_passwords,
+                        generate_memorizable, generate_memorizable_password, generate_password, generate_save_pin,
+                        generate_save_safety_password, import_passwords, is_pin_entry, restore_password,
+                        save_custom_password, set_entry_policy, update_password, update_password_regenerate,
+                        update_pin_regenerate};
+pub use app::passphrase::{generate_passphrase, Capitalization, Passphrase, PassphraseSettings, Wordlist};
+pub use app::pin::{generate_pin, pin_entropy, pin_weakness, DEFAULT_PIN_LENGTH, PIN_LENGTH_RANGE};
+pub use app::rng::{with_rng, with_seed};
+pub use app::policy::{CharClass, EntryPolicy, PasswordPolicy};
+pub use app::strength::{check_password_strength, estimate_strength, Strength, StrengthEstimate};
+pub use app::template::{Template, MAX_TEMPLATE_LENGTH};
+pub use app::store::{init_store, set_store, store, Backend, ChunkedStore, KeyringStore, MemoryStore, SecretStore,
+                     KEYRING_MAX_VALUE_LEN};
+pub use app::trash::{list_trash, restore_from_trash, TrashedEntry};
+pub use app::vault::VaultStore;
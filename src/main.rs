@@ -1,25 +1,64 @@
-mod app;
-
 #[cfg(feature = "tui")]
-mod ui;
+use apppass::ui;
 
 #[cfg(feature = "console")]
-use clap::{Arg, ArgAction, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 
 #[cfg(feature = "console")]
-use crate::app::keyring::show_list_applications;
+use apppass::app::keyring::show_list_applications;
+#[cfg(feature = "console")]
+use apppass::app::keyring::cleanup_orphaned_index;
+#[cfg(feature = "console")]
+use apppass::app::lock::start_auto_lock;
+#[cfg(feature = "console")]
+use apppass::generate_otp;
+#[cfg(feature = "console")]
+use apppass::cleanup_expired_otps;
+#[cfg(any(feature = "console", feature = "tui"))]
+use apppass::app::profile::{active_profile, resolve_profile, set_active_profile};
+#[cfg(any(feature = "console", feature = "tui"))]
+use apppass::app::config::config_path;
+#[cfg(any(feature = "console", feature = "tui"))]
+use apppass::app::migrate::migrate_store;
+#[cfg(any(feature = "console", feature = "tui"))]
+use apppass::{init_config, init_store, store, Backend};
+#[cfg(feature = "console")]
+use apppass::{config, Config};
+#[cfg(feature = "console")]
+use apppass::app::entry::{parse_field, EntryChanges};
+#[cfg(feature = "console")]
+use apppass::app::policy::{CharClass, EntryPolicy, PasswordPolicy};
+#[cfg(feature = "console")]
+use apppass::app::passphrase::{Capitalization, PassphraseSettings};
+#[cfg(feature = "console")]
+use apppass::AppError;
+#[cfg(feature = "console")]
+use apppass::app::history::{format_timestamp, get_history};
+#[cfg(feature = "console")]
+use apppass::get_entry_record;
+#[cfg(feature = "console")]
+use apppass::app::search::{closest_names, search_entries, MatchField, SearchMode};
 #[cfg(feature = "console")]
-use crate::app::keyring::cleanup_orphaned_index;
+use apppass::app::trash::{list_trash, purge_expired_trash, restore_from_trash, TrashedEntry};
 #[cfg(feature = "console")]
-use crate::app::lock::start_auto_lock;
+use apppass::app::password::{delete_password, edit_entry, entry_entropy, export_passwords,
+                             generate_memorizable_password, entry_policy, generate_save_pin,
+                             generate_save_safety_password, get_password_for_specify_app, import_passwords,
+                             is_pin_entry, restore_password, save_custom_password, set_entry_policy,
+                             update_password, update_password_regenerate, update_pin_regenerate};
 #[cfg(feature = "console")]
-use crate::app::otp::generate_otp;
+use apppass::app::audit::{audit, AuditOptions, FindingKind};
 #[cfg(feature = "console")]
-use crate::app::otp::cleanup_expired_otps;
+use apppass::app::breach::{check_entries, BreachCorpus};
 #[cfg(feature = "console")]
-use crate::app::password::{delete_password, export_passwords, generate_memorizable_password,
-                           generate_save_safety_password, get_password_for_specify_app,
-                           import_passwords, update_password, update_password_regenerate};
+use apppass::app::derive::{derive_password, forget_derived_site, get_derived_site, list_derived_sites,
+                           read_derive_secret, save_derived_site, DeriveParams};
+#[cfg(feature = "console")]
+use apppass::app::pin::pin_entropy;
+#[cfg(feature = "console")]
+use apppass::app::clipboard::{copy_to_clipboard, wait_for_clipboard_clear};
+#[cfg(feature = "console")]
+use apppass::app::strength::{Strength, StrengthEstimate};
 
 fn main() {
     #[cfg(feature = "console")]
@@ -27,6 +66,10 @@ fn main() {
     
     #[cfg(all(not(feature = "console"), feature = "tui"))]
     {
+        if let Err(e) = load_config().and_then(|_| select_profile(None)).and_then(|_| select_backend(None)) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
         if let Err(e) = ui::run_tui() {
             eprintln!("Error running UI: {}", e);
             std::process::exit(1);
@@ -40,12 +83,56 @@ fn main() {
     }
 }
 
+/// Loads the configuration file (see `apppass::Config`).
+#[cfg(any(feature = "console", feature = "tui"))]
+fn load_config() -> Result<(), String> {
+    init_config().map_err(|e| format!("Failed to load configuration: {}", e))
+}
+
+/// Returns the storage backend requested by the `--backend` flag or the `APPPASS_BACKEND`
+/// environment variable, falling back to the OS keyring.
+#[cfg(any(feature = "console", feature = "tui"))]
+fn requested_backend(flag: Option<&str>) -> Result<Backend, String> {
+    match flag {
+        Some(name) => name.parse(),
+        None => Ok(Backend::from_env().transpose()?.unwrap_or(Backend::Keyring)),
+    }
+}
+
+/// Opens the requested storage backend (see `requested_backend`) for the active profile
+/// and migrates its data to the current schema.
+#[cfg(any(feature = "console", feature = "tui"))]
+fn select_backend(flag: Option<&str>) -> Result<Backend, String> {
+    let backend = requested_backend(flag)?;
+    init_store(backend).map_err(|e| format!("Failed to open {:?} backend: {}", backend, e))?;
+    let report = migrate_store(&*store(), &config_path(), &active_profile()).map_err(|e| format!("Failed to migrate the store: {}", e))?;
+    if let Some(report) = report {
+        eprintln!(
+            "Migrated the store from schema version {} to {}:",
+            report.from_version, report.to_version
+        );
+        for step in report.applied {
+            eprintln!("  - {}", step);
+        }
+        // Migrations may have moved settings into the configuration file
+        load_config()?;
+    }
+    Ok(backend)
+}
+
+/// Selects the active profile from the `--profile` flag, the `APPPASS_PROFILE`
+/// environment variable or the default profile.
+#[cfg(any(feature = "console", feature = "tui"))]
+fn select_profile(flag: Option<&str>) -> Result<String, String> {
+    let profile = resolve_profile(flag)?;
+    set_active_profile(&profile);
+    // Apply the settings of the profile
+    load_config()?;
+    Ok(profile)
+}
+
 #[cfg(feature = "console")]
 fn run_cli() {
-    // Cleanup at startup
-    cleanup_orphaned_index();
-    cleanup_expired_otps();
-    
     let apppass = Command::new("apppass")
         .version(env!("CARGO_PKG_VERSION"))
         .author("Sergio Triana Escobedo")
@@ -62,7 +149,17 @@ fn run_cli() {
                 .short('n')
                 .long("length")
                 .action(ArgAction::Set)
-                .help("Password length"),
+                .help("Password length (number of digits with --pin)"),
+        )
+        .arg(template_arg())
+        .args(policy_args())
+        .arg(
+            Arg::new("pin")
+                .long("pin")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("template")
+                .conflicts_with_all(POLICY_ARG_IDS)
+                .help("Generate a numeric PIN with --app or --update, avoiding sequences, repeats, common PINs and dates"),
         )
         .arg(
             Arg::new("list")
@@ -78,6 +175,14 @@ fn run_cli() {
                 .action(ArgAction::Set)
                 .help("Get password for an application"),
         )
+        .arg(
+            Arg::new("copy")
+                .short('c')
+                .long("copy")
+                .action(ArgAction::SetTrue)
+                .requires("get")
+                .help("With --get, copy the password to the clipboard instead of printing it (cleared after clipboard.timeout seconds)"),
+        )
         .arg(
             Arg::new("delete")
                 .short('d')
@@ -105,6 +210,79 @@ fn run_cli() {
                 .action(ArgAction::Set)
                 .help("Custom password to use with --update-custom"),
         )
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .action(ArgAction::Set)
+                .help("Show the previous passwords of an application"),
+        )
+        .arg(
+            Arg::new("restore")
+                .long("restore")
+                .action(ArgAction::Set)
+                .help("Restore a previous password of an application (see --history, --revision)"),
+        )
+        .arg(
+            Arg::new("revision")
+                .long("revision")
+                .action(ArgAction::Set)
+                .help("History entry to restore with --restore (default: 1, the most recent)"),
+        )
+        .arg(
+            Arg::new("edit")
+                .short('e')
+                .long("edit")
+                .action(ArgAction::Set)
+                .help("Edit the details of an application (use with --username, --url, --notes, --field, --tag)"),
+        )
+        .arg(
+            Arg::new("username")
+                .long("username")
+                .action(ArgAction::Set)
+                .help("Username or login to store with --app or --edit (empty to clear)"),
+        )
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .action(ArgAction::Append)
+                .help("URL to add with --app or --edit (repeatable)"),
+        )
+        .arg(
+            Arg::new("remove-url")
+                .long("remove-url")
+                .action(ArgAction::Append)
+                .help("URL to remove with --edit (repeatable)"),
+        )
+        .arg(
+            Arg::new("notes")
+                .long("notes")
+                .action(ArgAction::Set)
+                .help("Notes to store with --app or --edit (empty to clear)"),
+        )
+        .arg(
+            Arg::new("field")
+                .long("field")
+                .action(ArgAction::Append)
+                .help("Custom field KEY=VALUE to set with --app or --edit (repeatable)"),
+        )
+        .arg(
+            Arg::new("remove-field")
+                .long("remove-field")
+                .action(ArgAction::Append)
+                .help("Custom field KEY to remove with --edit (repeatable)"),
+        )
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .action(ArgAction::Append)
+                .help("Tag to add with --app or --edit (repeatable)"),
+        )
+        .arg(
+            Arg::new("remove-tag")
+                .long("remove-tag")
+                .action(ArgAction::Append)
+                .help("Tag to remove with --edit (repeatable)"),
+        )
         .arg(
             Arg::new("export")
                 .long("export")
@@ -127,26 +305,275 @@ fn run_cli() {
             Arg::new("ttl")
                 .long("ttl")
                 .action(ArgAction::Set)
-                .help("Time-to-live for OTP in seconds (default: 300)"),
+                .help("Time-to-live for OTP in seconds (default: otp.ttl from the configuration, 300)"),
         )
         .arg(
             Arg::new("memorizable")
                 .long("memorizable")
+                .visible_alias("passphrase")
                 .action(ArgAction::Set)
-                .help("Generate a memorizable password for an application"),
+                .help("Generate a memorizable passphrase for an application"),
         )
+        .args(passphrase_args())
         .arg(
             Arg::new("lock")
                 .long("lock")
                 .action(ArgAction::Set)
                 .help("Set auto-lock timeout in seconds"),
         )
+        .arg(
+            Arg::new("backend")
+                .long("backend")
+                .action(ArgAction::Set)
+                .value_parser(Backend::NAMES)
+                .help("Secret storage backend (default: keyring, or $APPPASS_BACKEND)"),
+        )
+        .arg(
+            Arg::new("vault")
+                .long("vault")
+                .action(ArgAction::Set)
+                .help("Path of the encrypted vault file used by the vault backend (or $APPPASS_VAULT)"),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .action(ArgAction::Set)
+                .global(true)
+                .help("Profile whose entries and settings to use (default: the default profile, or $APPPASS_PROFILE)"),
+        )
         .arg(
             Arg::new("interactive")
                 .short('i')
                 .long("interactive")
                 .action(ArgAction::SetTrue)
                 .help("Launch interactive console menu mode"),
+        )
+        .subcommand(
+            Command::new("profile")
+                .about("Manage profiles (separate sets of entries and settings)")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("List profiles"))
+                .subcommand(
+                    Command::new("create")
+                        .about("Create a profile")
+                        .arg(Arg::new("name").required(true)),
+                )
+                .subcommand(
+                    Command::new("default")
+                        .about("Set the profile used when --profile is not given")
+                        .arg(Arg::new("name").required(true)),
+                )
+                .subcommand(
+                    Command::new("delete")
+                        .about("Delete a profile and every entry stored in it")
+                        .arg(Arg::new("name").required(true)),
+                ),
+        )
+        .subcommand(
+            Command::new("trash")
+                .about("List, restore or purge deleted entries")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("List deleted entries, most recent first"))
+                .subcommand(
+                    Command::new("restore")
+                        .about("Restore the most recently deleted entry with this name")
+                        .arg(Arg::new("name").required(true)),
+                )
+                .subcommand(
+                    Command::new("purge")
+                        .about("Permanently delete entries from the trash")
+                        .arg(Arg::new("name").required_unless_present("all"))
+                        .arg(
+                            Arg::new("all")
+                                .long("all")
+                                .action(ArgAction::SetTrue)
+                                .conflicts_with("name")
+                                .help("Empty the whole trash"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("retention")
+                        .about("Show or set how many days deleted entries are kept (0 keeps them forever)")
+                        .arg(Arg::new("days").value_parser(clap::value_parser!(u64))),
+                ),
+        )
+        .subcommand(
+            Command::new("policy")
+                .about("Show or change the length and password policy an entry is regenerated with")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("show")
+                        .about("Print the entry's length and policy")
+                        .arg(Arg::new("name").required(true)),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about("Change the entry's length, policy or template; options not given keep their value")
+                        .arg(Arg::new("name").required(true))
+                        .arg(
+                            Arg::new("length")
+                                .short('n')
+                                .long("length")
+                                .value_parser(clap::value_parser!(usize))
+                                .help("Password length"),
+                        )
+                        .arg(template_arg())
+                        .args(policy_args()),
+                )
+                .subcommand(
+                    Command::new("reset")
+                        .about("Forget the entry's policy so the configured defaults apply")
+                        .arg(Arg::new("name").required(true)),
+                ),
+        )
+        .subcommand(
+            Command::new("derive")
+                .about("Compute a site password from a master secret, so it can be re-derived on any machine")
+                .arg(
+                    Arg::new("site")
+                        .required_unless_present("list")
+                        .help("Site the password is for (case does not matter)"),
+                )
+                .arg(
+                    Arg::new("username")
+                        .long("username")
+                        .action(ArgAction::Set)
+                        .help("Account on the site, if you have several (empty to clear a saved one)"),
+                )
+                .arg(
+                    Arg::new("counter")
+                        .long("counter")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .help("Increase to get a new password for the site (default: 1)"),
+                )
+                .arg(
+                    Arg::new("length")
+                        .short('n')
+                        .long("length")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Password length (default: 20)"),
+                )
+                .arg(template_arg())
+                .args(policy_args())
+                .arg(
+                    Arg::new("save")
+                        .long("save")
+                        .action(ArgAction::SetTrue)
+                        .help("Save the parameters, never the password, so later derivations reuse them"),
+                )
+                .arg(
+                    Arg::new("forget")
+                        .long("forget")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("save")
+                        .help("Remove the saved parameters of the site"),
+                )
+                .arg(
+                    Arg::new("list")
+                        .long("list")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["site", "save", "forget"])
+                        .help("List the sites with saved parameters"),
+                ),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Look up passwords in a local breached-password corpus")
+                .arg(
+                    Arg::new("names")
+                        .num_args(0..)
+                        .help("Entries to check (default: every custom password)"),
+                )
+                .arg(
+                    Arg::new("breach-file")
+                        .long("breach-file")
+                        .action(ArgAction::Set)
+                        .help("Sorted SHA1HASH:COUNT file to search (default: password.breach_file)"),
+                ),
+        )
+        .subcommand(
+            Command::new("audit")
+                .about("Report weak, breached, reused and old passwords, missing details and expired OTPs")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .action(ArgAction::Set)
+                        .value_parser(["text", "json", "markdown"])
+                        .default_value("text")
+                        .help("Output format"),
+                )
+                .arg(
+                    Arg::new("max-age")
+                        .long("max-age")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .help("Report passwords not changed for more than this many days (default: 365)"),
+                )
+                .arg(
+                    Arg::new("min-strength")
+                        .long("min-strength")
+                        .action(ArgAction::Set)
+                        .value_parser(Strength::NAMES)
+                        .help("Report passwords weaker than this (default: password.min_strength, or fair)"),
+                )
+                .arg(
+                    Arg::new("fail-on")
+                        .long("fail-on")
+                        .action(ArgAction::Set)
+                        .value_delimiter(',')
+                        .value_parser(FindingKind::NAMES)
+                        .help("Findings that make the exit status 1, comma-separated (default: all)"),
+                ),
+        )
+        .subcommand(
+            Command::new("search")
+                .about("Find entries by name, username, URL or tag")
+                .arg(Arg::new("query").required(true).help("Text to look for; may use * and ? wildcards"))
+                .arg(
+                    Arg::new("mode")
+                        .long("mode")
+                        .action(ArgAction::Set)
+                        .value_parser(SearchMode::NAMES)
+                        .help("How to match (default: glob if the query has * or ?, fuzzy otherwise)"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .action(ArgAction::Set)
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Show at most this many entries"),
+                ),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Show or change settings in the configuration file")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("get")
+                        .about("Print a setting, or every setting when no key is given")
+                        .arg(Arg::new("key").value_parser(Config::KEYS)),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about("Change a setting")
+                        .arg(Arg::new("key").required(true).value_parser(Config::KEYS))
+                        .arg(Arg::new("value").required(true)),
+                ),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("Check the store for orphaned or unindexed entries and expired OTPs")
+                .arg(
+                    Arg::new("fix")
+                        .long("fix")
+                        .action(ArgAction::SetTrue)
+                        .help("Repair the problems found"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the report as JSON"),
+                ),
         );
     
     #[cfg(feature = "tui")]
@@ -159,6 +586,93 @@ fn run_cli() {
     
     let apppass = apppass.get_matches();
 
+    if let Err(e) = load_config() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    if let Some(path) = apppass.get_one::<String>("vault") {
+        apppass::app::vault::set_vault_path(std::path::Path::new(path));
+    }
+    let backend_flag = apppass.get_one::<String>("backend").map(String::as_str);
+
+    if let Some(("config", command)) = apppass.subcommand() {
+        if let Err(e) = run_config_command(command, apppass.get_one::<String>("profile").map(String::as_str)) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(("profile", command)) = apppass.subcommand() {
+        if let Err(e) = run_profile_command(command, backend_flag) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Runs before the startup cleanup so that it can report what the cleanup would hide
+    if let Some(("doctor", command)) = apppass.subcommand() {
+        let profile_flag = apppass.get_one::<String>("profile").map(String::as_str);
+        std::process::exit(run_doctor_command(command, profile_flag, backend_flag));
+    }
+
+    if let Err(e) = select_profile(apppass.get_one::<String>("profile").map(String::as_str))
+        .and_then(|_| select_backend(backend_flag))
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    // Runs before the startup cleanup so that it can report expired OTPs
+    if let Some(("audit", command)) = apppass.subcommand() {
+        std::process::exit(run_audit_command(command));
+    }
+
+    // Cleanup at startup
+    cleanup_orphaned_index();
+    cleanup_expired_otps();
+    if let Err(e) = purge_expired_trash() {
+        eprintln!("Warning: Failed to purge expired trash: {}", e);
+    }
+
+    if let Some(("trash", command)) = apppass.subcommand() {
+        if let Err(e) = run_trash_command(command) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(("policy", command)) = apppass.subcommand() {
+        if let Err(e) = run_policy_command(command) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(("derive", command)) = apppass.subcommand() {
+        if let Err(e) = run_derive_command(command) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(("check", command)) = apppass.subcommand() {
+        std::process::exit(run_check_command(command));
+    }
+
+    if let Some(("search", command)) = apppass.subcommand() {
+        if let Err(e) = run_search_command(command) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // If interactive flag is set, launch the interactive console menu
     if *apppass.get_one::<bool>("interactive").unwrap_or(&false) {
         run_interactive_console();
@@ -175,13 +689,58 @@ fn run_cli() {
         return;
     }
 
+    let changes = match entry_changes(&apppass) {
+        Ok(changes) => changes,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let policy = match generation_policy(&apppass, &config().password.policy) {
+        Ok(policy) => policy,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     if let Some(name) = apppass.get_one::<String>("app") {
         let length = apppass
             .get_one::<String>("length")
             .and_then(|l| l.parse::<usize>().ok());
-        match generate_save_safety_password(name, length) {
-            Ok(_) => println!("Password saved securely for '{}'.", name),
-            Err(_) => eprintln!("Password already exists for '{}'. Use update to change it.", name),
+        let template = apppass.get_one::<String>("template").map(String::as_str);
+        let result = if *apppass.get_one::<bool>("pin").unwrap_or(&false) {
+            generate_save_pin(name, length).map(|_| "PIN")
+        } else {
+            generate_save_safety_password(name, length, policy.as_ref(), template).map(|_| "Password")
+        };
+        match result {
+            Ok(kind) => {
+                println!("{} saved securely for '{}'.", kind, name);
+                if let Ok(entropy) = entry_entropy(name) {
+                    print_entropy(entropy);
+                }
+                if !changes.is_empty() {
+                    if let Err(e) = edit_entry(name, &changes) {
+                        eprintln!("Failed to save details for '{}': {}", name, e);
+                    }
+                }
+            }
+            Err(e @ AppError::AlreadyExists(_)) => eprintln!("{}. Use update to change it.", e),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+
+    if let Some(name) = apppass.get_one::<String>("edit") {
+        if changes.is_empty() {
+            eprintln!("Error: --edit requires at least one of --username, --url, --remove-url, --notes, --field, --remove-field, --tag or --remove-tag.");
+        } else {
+            match edit_entry(name, &changes) {
+                Ok(_) => println!("Details updated successfully for '{}'.", name),
+                Err(e @ AppError::NotFound(_)) => eprintln!("{}. Use -a/--app to create a new password.", e),
+                Err(e) => eprintln!("Error: {}", e),
+            }
         }
     }
 
@@ -191,18 +750,35 @@ fn run_cli() {
 
     if let Some(name) = apppass.get_one::<String>("get") {
         match get_password_for_specify_app(name) {
+            Ok(password) if apppass.get_flag("copy") => {
+                println!("Application Name: {}", name);
+                print_entry_details(name);
+                match copy_to_clipboard(&password) {
+                    Ok(0) => println!("Password copied to the clipboard."),
+                    Ok(timeout) => {
+                        println!("Password copied to the clipboard, it will be cleared in {} seconds.", timeout);
+                        wait_for_clipboard_clear();
+                    }
+                    Err(e) => println!("{}.", e),
+                }
+            }
             Ok(password) => {
                 println!("Application Name: {}", name);
                 println!("Password: {}", password);
+                print_entry_details(name);
+            }
+            Err(e @ AppError::NotFound(_)) => {
+                println!("{}.", e);
+                print_suggestions(name);
             }
-            Err(_) => println!("No password found for '{}'.", name),
+            Err(e) => println!("{}.", e),
         }
     }
 
     if let Some(name) = apppass.get_one::<String>("delete") {
         match delete_password(name) {
-            Ok(_) => println!("Password for '{}' deleted successfully.", name),
-            Err(_) => println!("No password found for '{}'.", name),
+            Ok(_) => println!("Password for '{}' deleted successfully (restore it with: apppass trash restore {}).", name, name),
+            Err(e) => println!("{}.", e),
         }
     }
 
@@ -211,12 +787,22 @@ fn run_cli() {
         let length = apppass
             .get_one::<String>("length")
             .and_then(|l| l.parse::<usize>().ok());
-        match update_password_regenerate(name, length) {
+        let template = apppass.get_one::<String>("template").map(String::as_str);
+        let result = if *apppass.get_one::<bool>("pin").unwrap_or(&false) {
+            update_pin_regenerate(name, length)
+        } else {
+            update_password_regenerate(name, length, policy.as_ref(), template)
+        };
+        match result {
             Ok(new_password) => {
                 println!("Password updated successfully for '{}'.", name);
                 println!("New Password: {}", new_password);
+                if let Ok(entropy) = entry_entropy(name) {
+                    print_entropy(entropy);
+                }
             }
-            Err(_) => eprintln!("No password found for '{}'. Use -a/--app to create a new password.", name),
+            Err(e @ AppError::NotFound(_)) => eprintln!("{}. Use -a/--app to create a new password.", e),
+            Err(e) => eprintln!("Error: {}", e),
         }
     }
 
@@ -224,25 +810,51 @@ fn run_cli() {
     if let Some(name) = apppass.get_one::<String>("update-custom") {
         if let Some(new_pass) = apppass.get_one::<String>("password") {
             match update_password(name, new_pass) {
-                Ok(_) => println!("Password updated successfully for '{}'.", name),
-                Err(_) => eprintln!("No password found for '{}'. Use -a/--app to create a new password.", name),
+                Ok(estimate) => {
+                    println!("Password updated successfully for '{}'.", name);
+                    print_strength(&estimate);
+                }
+                Err(e @ AppError::NotFound(_)) => eprintln!("{}. Use -a/--app to create a new password.", e),
+                Err(e) => eprintln!("Error: {}", e),
             }
         } else {
             eprintln!("Error: --update-custom requires --password/-p to specify the new password.");
         }
     }
 
+    if let Some(name) = apppass.get_one::<String>("history") {
+        print_history(name);
+    }
+
+    if let Some(name) = apppass.get_one::<String>("restore") {
+        let revision = apppass
+            .get_one::<String>("revision")
+            .and_then(|r| r.parse::<usize>().ok())
+            .unwrap_or(1);
+        match restore_password(name, revision) {
+            Ok(password) => {
+                println!("Password restored successfully for '{}' (revision {}).", name, revision);
+                println!("Password: {}", password);
+            }
+            Err(e @ AppError::InvalidInput(_)) => eprintln!("{}. Use --history to list it.", e),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+
     if let Some(path) = apppass.get_one::<String>("export") {
         match export_passwords(path) {
             Ok(_) => println!("Passwords exported to '{}'.", path),
-            Err(_) => eprintln!("Failed to export passwords to '{}'.", path),
+            Err(e) => eprintln!("Failed to export passwords: {}", e),
         }
     }
 
     if let Some(path) = apppass.get_one::<String>("import") {
         match import_passwords(path) {
-            Ok(_) => println!("Passwords imported from '{}'.", path),
-            Err(_) => eprintln!("Failed to import passwords from '{}'.", path),
+            Ok(breached) => {
+                println!("Passwords imported from '{}'.", path);
+                print_breached(&breached);
+            }
+            Err(e) => eprintln!("Failed to import passwords: {}", e),
         }
     }
 
@@ -250,18 +862,14 @@ fn run_cli() {
         let ttl = apppass
             .get_one::<String>("ttl")
             .and_then(|t| t.parse::<u64>().ok())
-            .unwrap_or(300);
-        
-        // Load password length from keyring, default to 30
-        let password_length = crate::app::keyring::get_from_keyring(crate::app::PASSWORD_LENGTH_KEY)
-            .ok()
-            .and_then(|v| v.parse::<usize>().ok())
-            .unwrap_or(30);
+            .unwrap_or(config().otp.ttl);
         
-        match generate_otp(name, ttl, password_length) {
+        let length = config().password.length;
+        match generate_otp(name, ttl, length, policy.as_ref()) {
             Ok(otp) => {
                 println!("OTP generated and saved for '{}'", name);
                 println!("Temporary Password: {}", otp);
+                print_entropy(policy.as_ref().unwrap_or(&config().password.policy).entropy(length));
                 println!("Expires in: {} seconds", ttl);
                 println!("\nThis password will be automatically deleted from the keyring after {} seconds.", ttl);
             }
@@ -270,9 +878,20 @@ fn run_cli() {
     }
 
     if let Some(name) = apppass.get_one::<String>("memorizable") {
-        match generate_memorizable_password(name) {
-            Ok(_) => println!("Memorizable password saved for '{}'.", name),
-            Err(_) => eprintln!("Password already exists for '{}'. Use update to change it.", name),
+        let settings = match passphrase_settings(&apppass) {
+            Ok(settings) => settings,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        match generate_memorizable_password(name, settings.as_ref()) {
+            Ok(passphrase) => {
+                println!("Memorizable password saved for '{}'.", name);
+                print_entropy(passphrase.entropy);
+            }
+            Err(e @ AppError::AlreadyExists(_)) => eprintln!("{}. Use update to change it.", e),
+            Err(e) => eprintln!("Error: {}", e),
         }
     }
 
@@ -282,6 +901,741 @@ fn run_cli() {
     }
 }
 
+/// Ids of the arguments returned by `policy_args`.
+#[cfg(feature = "console")]
+const POLICY_ARG_IDS: [&str; 9] = [
+    "classes", "min-lower", "min-upper", "min-digits", "min-symbols", "symbols", "exclude", "no-ambiguous",
+    "no-repeats",
+];
+
+/// The `--template` argument, shared by the top-level generators and `policy set`. A template
+/// sets the length and characters itself, so it conflicts with `--length` and `policy_args`.
+#[cfg(feature = "console")]
+fn template_arg() -> Arg {
+    Arg::new("template")
+        .long("template")
+        .value_name("TEMPLATE")
+        .conflicts_with("length")
+        .conflicts_with_all(POLICY_ARG_IDS)
+        .help(
+            "Generate the password in a fixed format, e.g. 'Cvcc-9999-!!': c/C consonant, v/V vowel, \
+             l/L letter, a any letter, 9 digit, ! symbol, x letter or digit, * any, [A-F] set, \
+             {3} repeat, (...) group, \\ literal",
+        )
+}
+
+/// Arguments that shape generated passwords, shared by the top-level generators and `policy set`.
+#[cfg(feature = "console")]
+fn policy_args() -> [Arg; 9] {
+    [
+        Arg::new("classes")
+            .long("classes")
+            .value_name("LIST")
+            .help("Character classes of generated passwords: comma-separated lower, upper, digits, symbols"),
+        Arg::new("min-lower")
+            .long("min-lower")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Minimum number of lowercase letters in generated passwords"),
+        Arg::new("min-upper")
+            .long("min-upper")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Minimum number of uppercase letters in generated passwords"),
+        Arg::new("min-digits")
+            .long("min-digits")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Minimum number of digits in generated passwords"),
+        Arg::new("min-symbols")
+            .long("min-symbols")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Minimum number of symbols in generated passwords"),
+        Arg::new("symbols")
+            .long("symbols")
+            .value_name("CHARS")
+            .help("Symbols generated passwords may use (enables the symbols class)"),
+        Arg::new("exclude")
+            .long("exclude")
+            .value_name("CHARS")
+            .help("Characters generated passwords must not contain"),
+        Arg::new("no-ambiguous")
+            .long("no-ambiguous")
+            .action(ArgAction::SetTrue)
+            .help("Leave easily confused characters (0O1lI) out of generated passwords"),
+        Arg::new("no-repeats")
+            .long("no-repeats")
+            .action(ArgAction::SetTrue)
+            .help("Never repeat a character twice in a row in generated passwords"),
+    ]
+}
+
+/// Arguments that shape passphrases generated with `--memorizable`.
+#[cfg(feature = "console")]
+fn passphrase_args() -> [Arg; 6] {
+    [
+        Arg::new("words")
+            .long("words")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Number of words in the passphrase"),
+        Arg::new("separator")
+            .long("separator")
+            .value_name("TEXT")
+            .help("Text between the words of the passphrase"),
+        Arg::new("capitalize")
+            .long("capitalize")
+            .value_name("MODE")
+            .value_parser(Capitalization::NAMES)
+            .help("Capitalization of the passphrase words"),
+        Arg::new("add-digit")
+            .long("add-digit")
+            .action(ArgAction::SetTrue)
+            .help("Append a random digit to a random word of the passphrase"),
+        Arg::new("add-symbol")
+            .long("add-symbol")
+            .action(ArgAction::SetTrue)
+            .help("Append a random symbol to a random word of the passphrase"),
+        Arg::new("wordlist")
+            .long("wordlist")
+            .value_name("FILE")
+            .help("Wordlist file to draw the passphrase from (one word per line, or diceware format)"),
+    ]
+}
+
+/// Builds the passphrase settings from the passphrase flags, starting from the configured ones.
+/// Returns `None` when no flag is given.
+#[cfg(feature = "console")]
+fn passphrase_settings(matches: &ArgMatches) -> Result<Option<PassphraseSettings>, String> {
+    const FLAGS: [&str; 6] = ["words", "separator", "capitalize", "add-digit", "add-symbol", "wordlist"];
+    if !FLAGS.iter().any(|id| matches.value_source(id) == Some(clap::parser::ValueSource::CommandLine)) {
+        return Ok(None);
+    }
+
+    let mut settings = config().passphrase;
+    if let Some(&words) = matches.get_one::<usize>("words") {
+        settings.words = words;
+    }
+    if let Some(separator) = matches.get_one::<String>("separator") {
+        settings.separator = separator.clone();
+    }
+    if let Some(capitalize) = matches.get_one::<String>("capitalize") {
+        settings.capitalize = capitalize.parse()?;
+    }
+    settings.digit |= matches.get_flag("add-digit");
+    settings.symbol |= matches.get_flag("add-symbol");
+    if let Some(path) = matches.get_one::<String>("wordlist") {
+        settings.wordlist = Some(path.into());
+    }
+    Ok(Some(settings))
+}
+
+/// Runs a `config` subcommand. With `--profile`, reads or changes the settings of that
+/// profile (its `[profiles.<name>]` section); otherwise the top-level settings, which apply
+/// to every profile.
+#[cfg(feature = "console")]
+fn run_config_command(command: &ArgMatches, profile_flag: Option<&str>) -> Result<(), String> {
+    use apppass::app::config::{config_path, update_config, update_profile_config};
+    use apppass::app::profile::{registry_path, ProfileRegistry};
+
+    let registry = ProfileRegistry::load(&registry_path())?;
+    if let Some(profile) = profile_flag {
+        if !registry.contains(profile) {
+            return Err(format!("Profile '{}' does not exist", profile));
+        }
+    }
+    let settings = || -> Result<Config, String> {
+        let config = Config::load(&config_path()).map_err(|e| e.to_string())?;
+        match profile_flag {
+            Some(profile) => config.for_profile(profile).map_err(|e| e.to_string()),
+            None => Ok(config),
+        }
+    };
+
+    match command.subcommand() {
+        Some(("get", m)) => {
+            let config = settings()?;
+            match m.get_one::<String>("key") {
+                Some(key) => println!("{}", config.get(key).map_err(|e| e.to_string())?),
+                None => {
+                    match profile_flag {
+                        Some(profile) => println!("# {} [profiles.{}]", config_path().display(), profile),
+                        None => println!("# {}", config_path().display()),
+                    }
+                    for key in Config::KEYS {
+                        println!("{} = {}", key, config.get(key).map_err(|e| e.to_string())?);
+                    }
+                }
+            }
+        }
+        Some(("set", m)) => {
+            let key = m.get_one::<String>("key").cloned().unwrap_or_default();
+            let value = m.get_one::<String>("value").cloned().unwrap_or_default();
+            if key == "default_profile" && !value.trim().is_empty() && !registry.contains(value.trim()) {
+                return Err(format!("Profile '{}' does not exist", value.trim()));
+            }
+            match profile_flag {
+                // The default profile is chosen before any profile's settings are read
+                Some(profile) if key != "default_profile" => {
+                    update_profile_config(profile, |config| config.set(&key, &value))
+                }
+                _ => update_config(|config| config.set(&key, &value)),
+            }
+            .map_err(|e| e.to_string())?;
+            println!("{} = {}", key, settings()?.get(&key).map_err(|e| e.to_string())?);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Runs a `profile` subcommand.
+#[cfg(feature = "console")]
+fn run_profile_command(command: &ArgMatches, backend_flag: Option<&str>) -> Result<(), String> {
+    use apppass::app::profile::{create_profile, list_profiles, remove_profile, service_name,
+                              set_default_profile, validate_profile_name};
+
+    let name = |m: &ArgMatches| m.get_one::<String>("name").cloned().unwrap_or_default();
+    match command.subcommand() {
+        Some(("list", _)) => {
+            for (profile, is_default) in list_profiles()? {
+                if is_default {
+                    println!("* {} (default)", profile);
+                } else {
+                    println!("  {}", profile);
+                }
+            }
+        }
+        Some(("create", m)) => {
+            let profile = name(m);
+            create_profile(&profile)?;
+            println!("Profile '{}' created. Use it with --profile {}.", profile, profile);
+        }
+        Some(("default", m)) => {
+            let profile = name(m);
+            set_default_profile(&profile)?;
+            println!("Default profile set to '{}'.", profile);
+        }
+        Some(("delete", m)) => {
+            let profile = name(m);
+            validate_profile_name(&profile)?;
+            let service = service_name(&profile);
+            // Entries may have been saved with any persistent backend, not only the selected one
+            let mut backends = vec![requested_backend(backend_flag)?];
+            for other in [Backend::Keyring, Backend::Vault] {
+                if backends.contains(&other) {
+                    continue;
+                }
+                match other.has_data(&service) {
+                    Ok(true) => backends.push(other),
+                    Ok(false) => {}
+                    Err(e) => eprintln!(
+                        "Warning: cannot check the {} backend for entries of '{}', they are kept: {}",
+                        other.name(), profile, e
+                    ),
+                }
+            }
+            remove_profile(&profile)?;
+            for backend in backends {
+                backend.purge(&service).map_err(|e| {
+                    format!("Profile removed, but failed to delete its {} entries: {}", backend.name(), e)
+                })?;
+            }
+            println!("Profile '{}' and its entries deleted.", profile);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Runs a `trash` subcommand.
+#[cfg(feature = "console")]
+fn run_trash_command(command: &ArgMatches) -> Result<(), String> {
+    use apppass::app::trash::{purge_trash, set_trash_retention_days, trash_retention_days};
+
+    let name = |m: &ArgMatches| m.get_one::<String>("name").cloned().unwrap_or_default();
+    match command.subcommand() {
+        Some(("list", _)) => {
+            let trash = list_trash().map_err(|e| e.to_string())?;
+            if trash.is_empty() {
+                println!("The trash is empty.");
+            } else {
+                print_trash(&trash);
+            }
+        }
+        Some(("restore", m)) => {
+            let app_name = name(m);
+            match restore_from_trash(&app_name) {
+                Ok(()) => println!("Password for '{}' restored.", app_name),
+                Err(AppError::NotFound(_)) => {
+                    return Err(format!("'{}' is not in the trash", app_name))
+                }
+                Err(e) => return Err(format!("Cannot restore '{}': {}", app_name, e)),
+            }
+        }
+        Some(("purge", m)) => {
+            let app_name = m.get_one::<String>("name").map(String::as_str);
+            let purged = purge_trash(app_name).map_err(|e| e.to_string())?;
+            println!("{} entries permanently deleted.", purged);
+        }
+        Some(("retention", m)) => match m.get_one::<u64>("days") {
+            Some(&days) => {
+                set_trash_retention_days(days).map_err(|e| e.to_string())?;
+                println!("Retention set: {}.", describe_retention(days));
+            }
+            None => println!("Retention: {}.", describe_retention(trash_retention_days())),
+        },
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Describes a trash retention period for display.
+#[cfg(feature = "console")]
+fn describe_retention(days: u64) -> String {
+    if days == 0 {
+        "deleted entries are kept until purged".to_string()
+    } else {
+        format!("deleted entries are kept for {} days", days)
+    }
+}
+
+/// Prints the entries in the trash, most recently deleted first.
+#[cfg(feature = "console")]
+fn print_trash(trash: &[TrashedEntry]) {
+    for entry in trash {
+        println!(
+            "{}  deleted {} UTC by {}",
+            entry.name,
+            format_timestamp(entry.deleted_at),
+            entry.deleted_by
+        );
+    }
+}
+
+/// Runs a `policy` subcommand.
+#[cfg(feature = "console")]
+fn run_policy_command(command: &ArgMatches) -> Result<(), String> {
+    let (action, m) = command.subcommand().ok_or("Missing policy command")?;
+    let name = m.get_one::<String>("name").map(String::as_str).unwrap_or_default();
+    get_password_for_specify_app(name).map_err(|e| e.to_string())?;
+    match action {
+        "show" if is_pin_entry(name) => {
+            let digits = get_password_for_specify_app(name).map_err(|e| e.to_string())?.len();
+            println!("# '{}' holds a PIN; updates generate a new PIN", name);
+            println!("pin_length = {}", digits);
+        }
+        "show" => {
+            let stored = get_entry_record(name).map_err(|e| e.to_string())?.policy;
+            if stored.is_some() {
+                println!("# Policy stored with '{}'", name);
+            } else {
+                println!("# '{}' has no stored policy; the configured defaults apply", name);
+            }
+            let policy = entry_policy(name, None, None).map_err(|e| e.to_string())?;
+            for (key, value) in policy.settings() {
+                println!("{} = {}", key, value);
+            }
+        }
+        "set" => {
+            let current = entry_policy(name, None, None).map_err(|e| e.to_string())?;
+            let length = m.get_one::<usize>("length").copied();
+            let policy = generation_policy(m, &current.policy)?;
+            let updated = match m.get_one::<String>("template") {
+                Some(template) => EntryPolicy::from_template(template, current.policy).map_err(|e| e.to_string())?,
+                None if length.is_none() && policy.is_none() => {
+                    return Err("policy set requires --length, --template or at least one policy option".to_string());
+                }
+                // A new length or policy replaces the template
+                None => EntryPolicy {
+                    length: length.unwrap_or(current.length),
+                    policy: policy.unwrap_or(current.policy),
+                    template: None,
+                },
+            };
+            set_entry_policy(name, Some(updated)).map_err(|e| e.to_string())?;
+            println!("Policy updated for '{}'.", name);
+        }
+        "reset" => {
+            set_entry_policy(name, None).map_err(|e| e.to_string())?;
+            println!("Policy removed from '{}'; the configured defaults apply.", name);
+        }
+        _ => unreachable!("subcommand_required"),
+    }
+    Ok(())
+}
+
+/// Runs the `derive` subcommand. Saved parameters of the site are the defaults of the options.
+#[cfg(feature = "console")]
+fn run_derive_command(command: &ArgMatches) -> Result<(), String> {
+    if command.get_flag("list") {
+        let sites = list_derived_sites().map_err(|e| e.to_string())?;
+        if sites.is_empty() {
+            println!("No saved derivation parameters.");
+        }
+        for params in sites {
+            let account = params.username.as_deref().map(|u| format!(", user {}", u)).unwrap_or_default();
+            let generation = match &params.generation.template {
+                Some(template) => format!("template {}", template),
+                None => format!("{} chars, {}", params.generation.length, CharClass::join(&params.generation.policy.classes)),
+            };
+            println!("{} (counter {}{}): {}", params.site, params.counter, account, generation);
+        }
+        return Ok(());
+    }
+
+    let site = command.get_one::<String>("site").map(String::as_str).unwrap_or_default();
+    if command.get_flag("forget") {
+        forget_derived_site(site).map_err(|e| e.to_string())?;
+        println!("Saved parameters of '{}' removed.", site);
+        return Ok(());
+    }
+
+    let mut params = get_derived_site(site)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| DeriveParams::new(site));
+    if let Some(username) = command.get_one::<String>("username") {
+        params.username = Some(username.clone()).filter(|u| !u.is_empty());
+    }
+    if let Some(counter) = command.get_one::<u32>("counter") {
+        params.counter = *counter;
+    }
+    let length = command.get_one::<usize>("length").copied();
+    let policy = generation_policy(command, &params.generation.policy)?;
+    params.generation = match command.get_one::<String>("template") {
+        Some(template) => {
+            EntryPolicy::from_template(template, params.generation.policy.clone()).map_err(|e| e.to_string())?
+        }
+        None if length.is_none() && policy.is_none() => params.generation,
+        // A new length or policy replaces the template
+        None => EntryPolicy {
+            length: length.unwrap_or(params.generation.length),
+            policy: policy.unwrap_or(params.generation.policy),
+            template: None,
+        },
+    };
+
+    let secret = read_derive_secret().map_err(|e| e.to_string())?;
+    let password = derive_password(&secret, &params).map_err(|e| e.to_string())?;
+    println!("Site: {} (counter {})", params.site, params.counter);
+    println!("Password: {}", password);
+    print_entropy(params.generation.entropy());
+    if command.get_flag("save") {
+        save_derived_site(&params).map_err(|e| e.to_string())?;
+        println!("Parameters saved for '{}' (the password is not stored).", params.site);
+    }
+    Ok(())
+}
+
+/// Runs the `search` subcommand.
+#[cfg(feature = "console")]
+fn run_search_command(command: &ArgMatches) -> Result<(), String> {
+    let query = command.get_one::<String>("query").cloned().unwrap_or_default();
+    let mode = match command.get_one::<String>("mode") {
+        Some(mode) => mode.parse()?,
+        None => SearchMode::detect(&query),
+    };
+    let mut hits = search_entries(&query, mode).map_err(|e| e.to_string())?;
+    if let Some(&limit) = command.get_one::<usize>("limit") {
+        hits.truncate(limit);
+    }
+    if hits.is_empty() {
+        println!("No entries match '{}'.", query);
+        return Ok(());
+    }
+    let width = hits.iter().map(|hit| hit.name.chars().count()).max().unwrap_or(0);
+    for hit in &hits {
+        if hit.field == MatchField::Name {
+            println!("{}", hit.name);
+        } else {
+            println!("{:<width$}  ({}: {})", hit.name, hit.field, hit.value, width = width);
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `audit` subcommand and returns the process exit code: 0 if there is no finding
+/// of the kinds given to `--fail-on` (every kind by default), 1 if there is, 2 if the audit failed.
+#[cfg(feature = "console")]
+fn run_audit_command(command: &ArgMatches) -> i32 {
+    let mut options = AuditOptions::default();
+    if let Some(strength) = command.get_one::<String>("min-strength") {
+        options.min_strength = strength.parse().unwrap_or(options.min_strength);
+    } else if let Some(strength) = config().password.min_strength {
+        options.min_strength = strength;
+    }
+    if let Some(&days) = command.get_one::<u64>("max-age") {
+        options.max_age_days = days;
+    }
+    let fail_on: Vec<FindingKind> = match command.get_many::<String>("fail-on") {
+        Some(kinds) => kinds.filter_map(|kind| kind.parse().ok()).collect(),
+        None => FindingKind::ALL.to_vec(),
+    };
+
+    let report = match audit(&options) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 2;
+        }
+    };
+    match command.get_one::<String>("format").map(String::as_str) {
+        Some("json") => match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize report: {}", e);
+                return 2;
+            }
+        },
+        Some("markdown") => print!("{}", report.to_markdown()),
+        _ => print!("{}", report.to_text()),
+    }
+
+    if report.has_findings(&fail_on) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Runs the `check` subcommand and returns the process exit code: 0 if no password checked
+/// is in the corpus, 1 if one is or the check failed.
+#[cfg(feature = "console")]
+fn run_check_command(command: &ArgMatches) -> i32 {
+    let corpus = match command.get_one::<String>("breach-file") {
+        Some(path) => BreachCorpus::open(path.as_ref()).map(Some),
+        None => BreachCorpus::configured(),
+    };
+    let mut corpus = match corpus {
+        Ok(Some(corpus)) => corpus,
+        Ok(None) => {
+            eprintln!("Error: No breach file. Set one with 'apppass config set password.breach_file PATH' or pass --breach-file.");
+            return 1;
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 1;
+        }
+    };
+    let names: Vec<String> = command.get_many::<String>("names").unwrap_or_default().cloned().collect();
+    let results = match check_entries(&mut corpus, &names) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 1;
+        }
+    };
+
+    if results.is_empty() {
+        println!("No custom passwords to check.");
+        return 0;
+    }
+    for (name, count) in &results {
+        match count {
+            0 => println!("{}: not found", name),
+            count => println!("{}: found {} times in known breaches", name, count),
+        }
+    }
+    let breached = results.iter().filter(|(_, count)| *count > 0).count();
+    println!("{} of {} passwords found in known breaches.", breached, results.len());
+    if breached > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Prints the entries whose names are closest to `name`, if any.
+#[cfg(feature = "console")]
+fn print_suggestions(name: &str) {
+    let suggestions = closest_names(name, 5);
+    if !suggestions.is_empty() {
+        println!("Did you mean: {}?", suggestions.join(", "));
+    }
+}
+
+/// Runs the `doctor` subcommand and returns the process exit code: 0 if the store is
+/// healthy (or every problem was fixed), 1 otherwise.
+#[cfg(feature = "console")]
+fn run_doctor_command(command: &ArgMatches, profile_flag: Option<&str>, backend_flag: Option<&str>) -> i32 {
+    use apppass::app::doctor::{check_store, DoctorReport};
+
+    let fix = command.get_flag("fix");
+    let profile = match select_profile(profile_flag) {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let backend = match requested_backend(backend_flag) {
+        Ok(backend) => backend,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut report = DoctorReport::new(&profile, backend.name());
+    match init_store(backend) {
+        Ok(()) => check_store(&*apppass::app::store::store(), fix, &mut report),
+        Err(e) => report.unreadable_backend(&e.to_string()),
+    }
+
+    if command.get_flag("json") {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Failed to serialize report: {}", e),
+        }
+    } else {
+        println!("Checking profile '{}' ({} backend)", report.profile, report.backend);
+        for issue in &report.issues {
+            let status = if issue.fixed {
+                "fixed"
+            } else if fix && !issue.kind.is_fixable() {
+                "cannot fix"
+            } else {
+                "problem"
+            };
+            println!("[{}] {}", status, issue.message);
+        }
+        let fixed = report.issues.iter().filter(|i| i.fixed).count();
+        println!(
+            "{} healthy entries, {} problems found, {} fixed.",
+            report.entries,
+            report.issues.len(),
+            fixed
+        );
+        if !fix && report.has_unfixed_issues() {
+            println!("Run 'apppass doctor --fix' to repair them.");
+        }
+    }
+
+    if report.has_unfixed_issues() {
+        1
+    } else {
+        0
+    }
+}
+
+/// Collects the entry detail flags (`--username`, `--url`, `--notes`, `--field`, `--tag`, ...) into a set of changes.
+#[cfg(feature = "console")]
+fn entry_changes(matches: &ArgMatches) -> Result<EntryChanges, String> {
+    let many = |id: &str| -> Vec<String> {
+        matches
+            .get_many::<String>(id)
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default()
+    };
+    Ok(EntryChanges {
+        username: matches.get_one::<String>("username").cloned(),
+        add_urls: many("url"),
+        remove_urls: many("remove-url"),
+        notes: matches.get_one::<String>("notes").cloned(),
+        set_fields: many("field")
+            .iter()
+            .map(|f| parse_field(f))
+            .collect::<Result<_, _>>()?,
+        remove_fields: many("remove-field"),
+        add_tags: many("tag"),
+        remove_tags: many("remove-tag"),
+    })
+}
+
+/// Builds the password policy from the generation flags (`--classes`, `--min-*`, `--symbols`,
+/// `--exclude`, `--no-ambiguous`, `--no-repeats`), starting from `base`.
+/// Returns `None` when no flag is given.
+#[cfg(feature = "console")]
+fn generation_policy(matches: &ArgMatches, base: &PasswordPolicy) -> Result<Option<PasswordPolicy>, String> {
+    if !POLICY_ARG_IDS.iter().any(|id| matches.value_source(id) == Some(clap::parser::ValueSource::CommandLine)) {
+        return Ok(None);
+    }
+
+    let mut policy = base.clone();
+    if let Some(classes) = matches.get_one::<String>("classes") {
+        policy.classes = CharClass::parse_list(classes)?;
+    }
+    if let Some(symbols) = matches.get_one::<String>("symbols") {
+        policy.symbols = symbols.clone();
+        policy.enable(CharClass::Symbols);
+    }
+    for (id, class) in [
+        ("min-lower", CharClass::Lower),
+        ("min-upper", CharClass::Upper),
+        ("min-digits", CharClass::Digits),
+        ("min-symbols", CharClass::Symbols),
+    ] {
+        if let Some(&min) = matches.get_one::<usize>(id) {
+            policy.require(class, min);
+        }
+    }
+    if let Some(exclude) = matches.get_one::<String>("exclude") {
+        policy.exclude = exclude.clone();
+    }
+    policy.exclude_ambiguous |= matches.get_flag("no-ambiguous");
+    policy.no_repeats |= matches.get_flag("no-repeats");
+    Ok(Some(policy))
+}
+
+/// Prints the estimated entropy of a generated password and its strength.
+#[cfg(feature = "console")]
+fn print_entropy(entropy: f64) {
+    println!("Entropy: {:.1} bits ({})", entropy, Strength::from_entropy(entropy));
+}
+
+/// Prints the estimated strength of a custom password and the guessable patterns found in it.
+#[cfg(feature = "console")]
+fn print_strength(estimate: &StrengthEstimate) {
+    println!("Strength: {}", estimate);
+    for pattern in &estimate.patterns {
+        println!("  - {}", pattern);
+    }
+}
+
+/// Warns about imported passwords found in the breach corpus, if any.
+#[cfg(feature = "console")]
+fn print_breached(breached: &[(String, u64)]) {
+    if !breached.is_empty() {
+        println!("Warning: {} imported passwords appear in known breaches; change them:", breached.len());
+        for (name, count) in breached {
+            println!("  - {} (found {} times)", name, count);
+        }
+    }
+}
+
+/// Prints the username, URLs, notes, custom fields and tags of an application, if any.
+#[cfg(feature = "console")]
+fn print_entry_details(app_name: &str) {
+    if let Ok(record) = get_entry_record(app_name) {
+        for (label, value) in record.display_lines() {
+            println!("{}: {}", label, value);
+        }
+    }
+}
+
+/// Prints the previous passwords of an application, most recent first.
+#[cfg(feature = "console")]
+fn print_history(app_name: &str) {
+    match get_history(app_name) {
+        Ok(history) if history.is_empty() => println!("No password history for '{}'.", app_name),
+        Ok(history) => {
+            println!("Password history for '{}' (most recent first):", app_name);
+            for (i, entry) in history.iter().enumerate() {
+                println!(
+                    "  {}. {} UTC  {} by {}  Password: {}",
+                    i + 1,
+                    format_timestamp(entry.changed_at),
+                    entry.source,
+                    entry.changed_by,
+                    entry.password
+                );
+            }
+        }
+        Err(e) => eprintln!("Failed to read history for '{}': {}", app_name, e),
+    }
+}
+
 /// Helper function to read a line from stdin
 #[cfg(feature = "console")]
 fn read_line() -> String {
@@ -316,9 +1670,9 @@ fn run_interactive_console() {
     
     loop {
         // Check password availability for menu display
-        let has_passwords = crate::app::keyring::has_any_passwords();
-        let has_auto = crate::app::keyring::has_auto_passwords();
-        let has_custom = crate::app::keyring::has_custom_passwords();
+        let has_passwords = apppass::app::keyring::has_any_passwords();
+        let has_auto = apppass::app::keyring::has_auto_passwords();
+        let has_custom = apppass::app::keyring::has_custom_passwords();
         
         println!("\n┌──────────────────────────────────────────┐");
         println!("│              MAIN MENU                   │");
@@ -366,6 +1720,13 @@ fn run_interactive_console() {
             println!("│ 10. Export to CSV (No passwords)        │");
         }
         println!("│ 11. Import from CSV                      │");
+        if has_passwords {
+            println!("│ 12. Password History / Restore           │");
+        } else {
+            println!("│ 12. Password History (No passwords)      │");
+        }
+        println!("│ 13. Trash (Restore Deleted Passwords)    │");
+        println!("│ 14. Generate PIN                         │");
         println!("│  0. Exit                                 │");
         println!("└──────────────────────────────────────────┘");
         
@@ -379,16 +1740,21 @@ fn run_interactive_console() {
                     continue;
                 }
                 
-                let length_str = prompt("Password length [30]: ");
+                let length_str = prompt(&format!("Password length [{}]: ", config().password.length));
                 let length: Option<usize> = if length_str.is_empty() {
                     None
                 } else {
                     length_str.parse().ok()
                 };
                 
-                match generate_save_safety_password(&app_name, length) {
-                    Ok(_) => println!("✓ Password saved for '{}'", app_name),
-                    Err(_) => println!("✗ Password already exists for '{}'", app_name),
+                match generate_save_safety_password(&app_name, length, None, None) {
+                    Ok(_) => {
+                        println!("✓ Password saved for '{}'", app_name);
+                        if let Ok(entropy) = entry_entropy(&app_name) {
+                            print_entropy(entropy);
+                        }
+                    }
+                    Err(e) => println!("✗ {}", e),
                 }
             }
             "2" => {
@@ -404,16 +1770,16 @@ fn run_interactive_console() {
                     continue;
                 }
                 
-                match crate::app::keyring::save_to_keyring(&app_name, &password) {
-                    Ok(_) => {
-                        let _ = crate::app::keyring::set_password_type(&app_name, "custom");
+                match save_custom_password(&app_name, &password) {
+                    Ok(estimate) => {
                         println!("✓ Custom password saved for '{}'", app_name);
+                        print_strength(&estimate);
                     }
-                    Err(e) => println!("✗ Error: {}", e),
+                    Err(e) => println!("✗ {}", e),
                 }
             }
             "3" => {
-                if !crate::app::keyring::has_any_passwords() {
+                if !apppass::app::keyring::has_any_passwords() {
                     println!("✗ No passwords to list");
                     continue;
                 }
@@ -421,7 +1787,7 @@ fn run_interactive_console() {
                 show_list_applications();
             }
             "4" => {
-                if !crate::app::keyring::has_any_passwords() {
+                if !apppass::app::keyring::has_any_passwords() {
                     println!("✗ No passwords to get");
                     continue;
                 }
@@ -430,33 +1796,49 @@ fn run_interactive_console() {
                     Ok(password) => {
                         println!("Application: {}", app_name);
                         println!("Password: {}", password);
+                        print_entry_details(&app_name);
+                    }
+                    Err(e @ AppError::NotFound(_)) => {
+                        println!("✗ {}", e);
+                        print_suggestions(&app_name);
                     }
-                    Err(_) => println!("✗ No password found for '{}'", app_name),
+                    Err(e) => println!("✗ {}", e),
                 }
             }
             "5" => {
-                if !crate::app::keyring::has_auto_passwords() {
+                if !apppass::app::keyring::has_auto_passwords() {
                     println!("✗ No auto-generated passwords to update");
                     continue;
                 }
                 let app_name = prompt("Application name: ");
-                let length_str = prompt("New password length [30]: ");
+                // Default to the length stored with the entry; its policy is reused as well
+                let current_length = if is_pin_entry(&app_name) {
+                    get_password_for_specify_app(&app_name).map(|pin| pin.len()).unwrap_or(config().pin.length)
+                } else {
+                    entry_policy(&app_name, None, None)
+                        .map(|policy| policy.length)
+                        .unwrap_or(config().password.length)
+                };
+                let length_str = prompt(&format!("New password length [{}]: ", current_length));
                 let length: Option<usize> = if length_str.is_empty() {
                     None
                 } else {
                     length_str.parse().ok()
                 };
                 
-                match update_password_regenerate(&app_name, length) {
+                match update_password_regenerate(&app_name, length, None, None) {
                     Ok(new_password) => {
                         println!("✓ Password updated for '{}'", app_name);
                         println!("New Password: {}", new_password);
+                        if let Ok(entropy) = entry_entropy(&app_name) {
+                            print_entropy(entropy);
+                        }
                     }
-                    Err(_) => println!("✗ No password found for '{}'", app_name),
+                    Err(e) => println!("✗ {}", e),
                 }
             }
             "6" => {
-                if !crate::app::keyring::has_custom_passwords() {
+                if !apppass::app::keyring::has_custom_passwords() {
                     println!("✗ No custom passwords to update");
                     continue;
                 }
@@ -468,12 +1850,15 @@ fn run_interactive_console() {
                 }
                 
                 match update_password(&app_name, &password) {
-                    Ok(_) => println!("✓ Password updated for '{}'", app_name),
-                    Err(_) => println!("✗ No password found for '{}'", app_name),
+                    Ok(estimate) => {
+                        println!("✓ Password updated for '{}'", app_name);
+                        print_strength(&estimate);
+                    }
+                    Err(e) => println!("✗ {}", e),
                 }
             }
             "7" => {
-                if !crate::app::keyring::has_any_passwords() {
+                if !apppass::app::keyring::has_any_passwords() {
                     println!("✗ No passwords to delete");
                     continue;
                 }
@@ -482,67 +1867,131 @@ fn run_interactive_console() {
                 
                 if confirm.to_lowercase() == "y" {
                     match delete_password(&app_name) {
-                        Ok(_) => println!("✓ Password deleted for '{}'", app_name),
-                        Err(_) => println!("✗ No password found for '{}'", app_name),
+                        Ok(_) => println!("✓ Password for '{}' moved to the trash", app_name),
+                        Err(e) => println!("✗ {}", e),
                     }
                 } else {
                     println!("Cancelled.");
                 }
             }
             "8" => {
-                if !crate::app::keyring::has_any_passwords() {
+                if !apppass::app::keyring::has_any_passwords() {
                     println!("✗ No passwords to generate OTP for");
                     continue;
                 }
                 let app_name = prompt("OTP application name: ");
-                let ttl_str = prompt("TTL in seconds [300]: ");
+                let default_ttl = config().otp.ttl;
+                let ttl_str = prompt(&format!("TTL in seconds [{}]: ", default_ttl));
                 let ttl: u64 = if ttl_str.is_empty() {
-                    300
+                    default_ttl
                 } else {
-                    ttl_str.parse().unwrap_or(300)
+                    ttl_str.parse().unwrap_or(default_ttl)
                 };
                 
-                let password_length = crate::app::keyring::get_from_keyring(crate::app::PASSWORD_LENGTH_KEY)
-                    .ok()
-                    .and_then(|v| v.parse::<usize>().ok())
-                    .unwrap_or(30);
-                
-                match generate_otp(&app_name, ttl, password_length) {
+                match generate_otp(&app_name, ttl, config().password.length, None) {
                     Ok(otp) => {
                         println!("✓ OTP generated for '{}'", app_name);
                         println!("Password: {}", otp);
+                        print_entropy(config().password.policy.entropy(config().password.length));
                         println!("Expires in: {} seconds", ttl);
                     }
-                    Err(e) => println!("✗ Error: {}", e),
+                    Err(e) => println!("✗ {}", e),
                 }
             }
             "9" => {
-                if !crate::app::keyring::has_any_passwords() {
+                if !apppass::app::keyring::has_any_passwords() {
                     println!("✗ No passwords to generate memorizable for");
                     continue;
                 }
                 let app_name = prompt("Application name: ");
-                match generate_memorizable_password(&app_name) {
-                    Ok(_) => println!("✓ Memorizable password saved for '{}'", app_name),
-                    Err(_) => println!("✗ Password already exists for '{}'", app_name),
+                match generate_memorizable_password(&app_name, None) {
+                    Ok(passphrase) => {
+                        println!("✓ Memorizable password saved for '{}'", app_name);
+                        print_entropy(passphrase.entropy);
+                    }
+                    Err(e) => println!("✗ {}", e),
                 }
             }
             "10" => {
-                if !crate::app::keyring::has_any_passwords() {
+                if !apppass::app::keyring::has_any_passwords() {
                     println!("✗ No passwords to export");
                     continue;
                 }
                 let path = prompt("Export file path: ");
                 match export_passwords(&path) {
                     Ok(_) => println!("✓ Exported to '{}'", path),
-                    Err(_) => println!("✗ Export failed"),
+                    Err(e) => println!("✗ Export failed: {}", e),
                 }
             }
             "11" => {
                 let path = prompt("Import file path: ");
                 match import_passwords(&path) {
-                    Ok(_) => println!("✓ Imported from '{}'", path),
-                    Err(_) => println!("✗ Import failed"),
+                    Ok(breached) => {
+                        println!("✓ Imported from '{}'", path);
+                        print_breached(&breached);
+                    }
+                    Err(e) => println!("✗ Import failed: {}", e),
+                }
+            }
+            "12" => {
+                if !apppass::app::keyring::has_any_passwords() {
+                    println!("✗ No passwords with history");
+                    continue;
+                }
+                let app_name = prompt("Application name: ");
+                print_history(&app_name);
+                if get_history(&app_name).map(|h| h.is_empty()).unwrap_or(true) {
+                    continue;
+                }
+
+                let revision = prompt("Revision to restore (Enter to skip): ");
+                if revision.is_empty() {
+                    continue;
+                }
+                match revision.parse::<usize>() {
+                    Ok(revision) => match restore_password(&app_name, revision) {
+                        Ok(_) => println!("✓ Password restored for '{}'", app_name),
+                        Err(e) => println!("✗ {}", e),
+                    },
+                    Err(_) => println!("✗ Invalid revision '{}'", revision),
+                }
+            }
+            "13" => {
+                let trash = list_trash().unwrap_or_default();
+                if trash.is_empty() {
+                    println!("✗ The trash is empty");
+                    continue;
+                }
+                print_trash(&trash);
+                let app_name = prompt("Application to restore (Enter to skip): ");
+                if app_name.is_empty() {
+                    continue;
+                }
+                match restore_from_trash(&app_name) {
+                    Ok(_) => println!("✓ Password restored for '{}'", app_name),
+                    Err(e) => println!("✗ Failed to restore '{}': {}", app_name, e),
+                }
+            }
+            "14" => {
+                let app_name = prompt("Application name: ");
+                if app_name.is_empty() {
+                    println!("✗ Application name cannot be empty");
+                    continue;
+                }
+
+                let length_str = prompt(&format!("Number of digits [{}]: ", config().pin.length));
+                let length: Option<usize> = if length_str.is_empty() {
+                    None
+                } else {
+                    length_str.parse().ok()
+                };
+
+                match generate_save_pin(&app_name, length) {
+                    Ok(pin) => {
+                        println!("✓ PIN saved for '{}'", app_name);
+                        print_entropy(pin_entropy(pin.len()));
+                    }
+                    Err(e) => println!("✗ {}", e),
                 }
             }
             "0" | "q" | "exit" => {
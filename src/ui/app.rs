@@ -1,6 +1,18 @@
-use crate::app::keyring::{delete_from_keyring, get_from_keyring, save_to_keyring, set_password_type, has_auto_passwords, has_custom_passwords, get_password_type, cleanup_orphaned_index};
-use crossterm::event::{KeyCode, KeyEvent};
-use keyring::Entry;
+use crate::app::clipboard::copy_to_clipboard;
+use crate::app::config::{config, update_profile_config};
+use crate::app::entry::EntryRecord;
+use crate::app::error::AppError;
+use crate::app::history::{get_history, push_history, set_history, HistoryEntry};
+use crate::app::password::{entry_policy, generate_save_pin, restore_password, save_custom_password, set_entry_policy};
+use crate::app::pin::{generate_pin, pin_entropy};
+use crate::app::profile::active_profile;
+use crate::app::strength::{check_password_strength, Strength};
+use crate::app::trash::{list_trash, move_to_trash, purge_expired_trash, purge_trash, restore_from_trash, TrashedEntry};
+use crate::app::index::load_index;
+use crate::app::keys::validate_app_name;
+use crate::app::keyring::{delete_from_keyring, get_entry_record, set_entry_record, get_from_keyring, replace_password, save_to_keyring, set_password_type, has_any_passwords, has_auto_passwords, has_custom_passwords, cleanup_orphaned_index};
+use crate::app::search::{match_entry, SearchMode};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::io;
 
 /// Application modes
@@ -30,8 +42,34 @@ pub enum Mode {
     Export,
     /// Import passwords
     Import,
-    /// Settings (password length configuration)
+    /// Settings (password length and policy)
     Settings,
+    /// Password history of the viewed entry
+    History,
+    /// Deleted entries that can be restored
+    Trash,
+}
+
+/// Rows of the settings form: the label shown and the configuration key each row edits.
+pub const SETTINGS_ROWS: [(&str, &str); 13] = [
+    ("Default length (8-128)", "password.length"),
+    ("Character classes", "password.classes"),
+    ("Minimum lowercase letters", "password.min_lower"),
+    ("Minimum uppercase letters", "password.min_upper"),
+    ("Minimum digits", "password.min_digits"),
+    ("Minimum symbols", "password.min_symbols"),
+    ("Symbol set", "password.symbols"),
+    ("Excluded characters", "password.exclude"),
+    ("Avoid ambiguous characters (0O1lI)", "password.exclude_ambiguous"),
+    ("No repeated characters", "password.no_repeats"),
+    ("Minimum strength of custom passwords", "password.min_strength"),
+    ("Breached-password file (empty: none)", "password.breach_file"),
+    ("Default PIN digits (4-12)", "pin.length"),
+];
+
+/// Whether the settings row for `key` is a yes/no toggle rather than a text field.
+pub fn is_toggle_setting(key: &str) -> bool {
+    matches!(key, "password.exclude_ambiguous" | "password.no_repeats")
 }
 
 /// Input field for forms
@@ -84,8 +122,9 @@ impl InputField {
 pub struct PasswordEntry {
     pub app_name: String,
     pub password: String,
-    #[allow(dead_code)]
-    pub password_type: String, // "auto" or "custom"
+    pub password_type: String, // "auto", "pin" or "custom"
+    /// Username, URLs, notes and custom fields
+    pub record: EntryRecord,
 }
 
 /// Main application state
@@ -100,10 +139,16 @@ pub struct App {
     pub app_name_input: InputField,
     /// Input field for password
     pub password_input: InputField,
-    /// Input field for password length
+    /// Input field for a length: PIN digits on the create screen, OTP lifetime on the OTP screen
     pub length_input: InputField,
-    /// List of passwords
+    /// Input field for the optional template of the create screen
+    pub template_input: InputField,
+    /// List of passwords (in List mode, the entries matching `list_filter`)
     pub password_list: Vec<PasswordEntry>,
+    /// Every entry loaded for List mode, before filtering
+    pub all_passwords: Vec<PasswordEntry>,
+    /// Filter typed in List mode
+    pub list_filter: InputField,
     /// Selected item in list
     pub selected_list_item: usize,
     /// Status message
@@ -114,6 +159,18 @@ pub struct App {
     pub default_password_length: usize,
     /// Whether we are in editing mode (for Update screens)
     pub is_editing: bool,
+    /// Password history of the viewed entry, most recent first
+    pub history_list: Vec<HistoryEntry>,
+    /// Selected item in the history list
+    pub selected_history_item: usize,
+    /// Deleted entries, most recently deleted first
+    pub trash_list: Vec<TrashedEntry>,
+    /// Selected item in the trash list
+    pub selected_trash_item: usize,
+    /// Values of the settings form, one per `SETTINGS_ROWS` entry
+    pub settings_inputs: Vec<InputField>,
+    /// Selected row of the settings form
+    pub selected_setting: usize,
 }
 
 impl App {
@@ -124,9 +181,12 @@ impl App {
         
         // Clean up expired OTPs at startup
         crate::app::otp::cleanup_expired_otps();
+
+        // Permanently delete entries kept in the trash past the retention period
+        let _ = purge_expired_trash();
         
-        // Load default password length from keyring (persistent setting)
-        let default_password_length = Self::load_password_length_setting().unwrap_or(30);
+        // Default password length from the configuration file
+        let default_password_length = config().password.length;
 
         Self {
             mode: Mode::Menu,
@@ -135,41 +195,48 @@ impl App {
             app_name_input: InputField::new(),
             password_input: InputField::new(),
             length_input: InputField::new(),
+            template_input: InputField::new(),
             password_list: Vec::new(),
+            all_passwords: Vec::new(),
+            list_filter: InputField::new(),
             selected_list_item: 0,
             default_password_length,
             status_message: String::new(),
             active_input: 0,
             is_editing: false,
+            history_list: Vec::new(),
+            selected_history_item: 0,
+            trash_list: Vec::new(),
+            selected_trash_item: 0,
+            settings_inputs: vec![InputField::new(); SETTINGS_ROWS.len()],
+            selected_setting: 0,
         }
     }
 
-    /// Load password length setting from keyring
-    fn load_password_length_setting() -> Option<usize> {
-        match get_from_keyring(crate::app::PASSWORD_LENGTH_KEY) {
-            Ok(value) => {
-                value.parse::<usize>().ok().filter(|&len| len >= 8 && len <= 128)
-            }
-            Err(_) => {
-                // No saved setting or keyring access failed - use default
-                None
-            }
-        }
-    }
-
-    /// Save password length setting to keyring
-    fn save_password_length_setting(length: usize) -> Result<(), String> {
-        save_to_keyring(crate::app::PASSWORD_LENGTH_KEY, &length.to_string())
-            .map_err(|e| format!("Failed to save setting: {}", e))
+    /// Fills the settings form with the current configuration
+    fn load_settings(&mut self) {
+        let config = config();
+        self.settings_inputs = SETTINGS_ROWS
+            .iter()
+            .map(|(_, key)| {
+                let value = config.get(key).unwrap_or_default();
+                InputField {
+                    cursor_position: value.len(),
+                    value,
+                }
+            })
+            .collect();
+        self.selected_setting = 0;
     }
 
-    /// Delete password length setting from keyring (resets to default)
-    /// Note: Currently unused - user can change value instead of deleting.
-    /// Kept for potential future use (e.g., explicit "Reset to Default" option).
-    #[allow(dead_code)]
-    fn delete_password_length_setting() -> Result<(), String> {
-        delete_from_keyring(crate::app::PASSWORD_LENGTH_KEY)
-            .map_err(|e| format!("Failed to delete setting: {}", e))
+    /// Saves every value of the settings form as the settings of the active profile
+    fn save_settings(&self) -> Result<(), AppError> {
+        let values: Vec<(&str, &str)> = SETTINGS_ROWS
+            .iter()
+            .zip(&self.settings_inputs)
+            .map(|((_, key), input)| (*key, input.value.as_str()))
+            .collect();
+        update_profile_config(&active_profile(), |config| config.set_all(&values))
     }
 
     /// Handles keyboard input
@@ -188,6 +255,8 @@ impl App {
             Mode::Export => self.handle_export_key(key),
             Mode::Import => self.handle_import_key(key),
             Mode::Settings => self.handle_settings_key(key),
+            Mode::History => self.handle_history_key(key),
+            Mode::Trash => self.handle_trash_key(key),
         }
     }
 
@@ -197,15 +266,11 @@ impl App {
             KeyCode::Char('q') | KeyCode::Esc => {
                 self.should_quit = true;
             }
-            KeyCode::Up => {
-                if self.selected_menu > 0 {
-                    self.selected_menu -= 1;
-                }
+            KeyCode::Up if self.selected_menu > 0 => {
+                self.selected_menu -= 1;
             }
-            KeyCode::Down => {
-                if self.selected_menu < 12 {  // Updated for 13 menu items (0-12)
-                    self.selected_menu += 1;
-                }
+            KeyCode::Down if self.selected_menu < 13 => {  // Updated for 14 menu items (0-13)
+                self.selected_menu += 1;
             }
             KeyCode::Enter => {
                 self.status_message.clear();
@@ -219,6 +284,7 @@ impl App {
                         self.mode = Mode::Create;
                         self.app_name_input.clear();
                         self.length_input.clear();
+                        self.template_input.clear();
                         self.active_input = 0;
                     }
                     1 => {
@@ -235,6 +301,7 @@ impl App {
                             return Ok(());
                         }
                         self.mode = Mode::List;
+                        self.list_filter.clear();
                         self.load_passwords();
                     }
                     3 => {
@@ -246,7 +313,7 @@ impl App {
                         self.mode = Mode::UpdateAuto;
                         self.app_name_input.clear();
                         self.is_editing = false;
-                        self.load_passwords_by_type("auto");  // Load only auto passwords
+                        self.load_passwords_by_type(&["auto", "pin"]);  // Load only auto passwords
                         self.selected_list_item = 0;
                     }
                     4 => {
@@ -259,7 +326,7 @@ impl App {
                         self.app_name_input.clear();
                         self.password_input.clear();
                         self.is_editing = false;
-                        self.load_passwords_by_type("custom");  // Load only custom passwords
+                        self.load_passwords_by_type(&["custom"]);  // Load only custom passwords
                         self.selected_list_item = 0;
                         self.active_input = 0;
                     }
@@ -309,17 +376,20 @@ impl App {
                         self.app_name_input.clear();  // Use for file path
                     }
                     10 => {
-                        // Settings (Password Length)
+                        // Settings (Password Length and Policy)
                         self.mode = Mode::Settings;
-                        self.length_input.clear();
-                        self.length_input.value = self.default_password_length.to_string();
-                        self.length_input.cursor_position = self.length_input.value.len();
+                        self.load_settings();
                     }
                     11 => {
+                        // Trash (restore deleted passwords)
+                        self.mode = Mode::Trash;
+                        self.load_trash();
+                    }
+                    12 => {
                         // Set Auto-Lock
                         self.status_message = "Auto-lock not implemented in UI yet".to_string();
                     }
-                    12 => {
+                    13 => {
                         // Exit
                         self.should_quit = true;
                     }
@@ -337,44 +407,97 @@ impl App {
             KeyCode::Esc => {
                 self.mode = Mode::Menu;
             }
-            KeyCode::Enter => {
-                if !self.app_name_input.value.is_empty() {
-                    // Auto-generate password with configured default length
-                    match crate::app::password::generate_save_safety_password(
-                        &self.app_name_input.value,
-                        Some(self.default_password_length),
-                    ) {
-                        Ok(_) => {
-                            self.status_message = format!(
-                                "✓ Password auto-generated ({} chars) for '{}'",
-                                self.default_password_length,
-                                self.app_name_input.value
-                            );
-                            self.app_name_input.clear();
-                        }
-                        Err(e) => {
-                            self.status_message = format!("✗ Error: {}", e);
-                        }
+            KeyCode::Tab => {
+                self.active_input = (self.active_input + 1) % 3;
+            }
+            KeyCode::Enter if !self.app_name_input.value.is_empty() && !self.length_input.value.is_empty() => {
+                // Generate a PIN with the number of digits given
+                if !self.template_input.value.is_empty() {
+                    self.status_message = "✗ Give either a template or a number of PIN digits".to_string();
+                    return Ok(());
+                }
+                let digits = match self.length_input.value.trim().parse::<usize>() {
+                    Ok(digits) => digits,
+                    Err(_) => {
+                        self.status_message = format!("✗ Invalid number of PIN digits '{}'", self.length_input.value);
+                        return Ok(());
+                    }
+                };
+                match generate_save_pin(&self.app_name_input.value, Some(digits)) {
+                    Ok(pin) => {
+                        let entropy = pin_entropy(pin.len());
+                        self.status_message = format!(
+                            "✓ PIN generated ({} digits, {:.1} bits, {}) for '{}'",
+                            pin.len(),
+                            entropy,
+                            Strength::from_entropy(entropy),
+                            self.app_name_input.value
+                        );
+                        self.app_name_input.clear();
+                        self.active_input = 0;
+                    }
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
+                    }
+                }
+            }
+            KeyCode::Enter if !self.app_name_input.value.is_empty() => {
+                // Auto-generate password from the template, or with configured default length
+                let template = Some(self.template_input.value.as_str()).filter(|t| !t.is_empty());
+                let length = if template.is_some() { None } else { Some(self.default_password_length) };
+                match crate::app::password::generate_save_safety_password(
+                    &self.app_name_input.value,
+                    length,
+                    None,
+                    template,
+                ) {
+                    Ok(_) => {
+                        let (length, entropy) = match entry_policy(&self.app_name_input.value, None, None) {
+                            Ok(generation) => (generation.length, generation.entropy()),
+                            Err(_) => (self.default_password_length, 0.0),
+                        };
+                        self.status_message = format!(
+                            "✓ Password auto-generated ({} chars, {:.1} bits, {}) for '{}'",
+                            length,
+                            entropy,
+                            Strength::from_entropy(entropy),
+                            self.app_name_input.value
+                        );
+                        self.app_name_input.clear();
+                        self.active_input = 0;
+                    }
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
                     }
                 }
             }
             KeyCode::Char(c) => {
-                self.app_name_input.insert_char(c);
+                self.active_create_input().insert_char(c);
             }
             KeyCode::Backspace => {
-                self.app_name_input.delete_char();
+                self.active_create_input().delete_char();
             }
             KeyCode::Left => {
-                self.app_name_input.move_cursor_left();
+                self.active_create_input().move_cursor_left();
             }
             KeyCode::Right => {
-                self.app_name_input.move_cursor_right();
+                self.active_create_input().move_cursor_right();
             }
             _ => {}
         }
         Ok(())
     }
 
+    /// The field of the create screen being edited: the application name, the template or
+    /// the number of PIN digits
+    fn active_create_input(&mut self) -> &mut InputField {
+        match self.active_input {
+            0 => &mut self.app_name_input,
+            1 => &mut self.template_input,
+            _ => &mut self.length_input,
+        }
+    }
+
     /// Handles keys in create custom mode (user-specified password)
     fn handle_create_custom_key(&mut self, key: KeyEvent) -> io::Result<()> {
         match key.code {
@@ -384,29 +507,23 @@ impl App {
             KeyCode::Tab => {
                 self.active_input = (self.active_input + 1) % 2;
             }
-            KeyCode::Enter => {
-                if !self.app_name_input.value.is_empty() && !self.password_input.value.is_empty() {
-                    match save_to_keyring(
-                        &self.app_name_input.value,
-                        &self.password_input.value,
-                    ) {
-                        Ok(_) => {
-                            // Mark as custom password
-                            if let Err(e) = set_password_type(&self.app_name_input.value, "custom") {
-                                self.status_message = format!("✗ Error setting type: {}", e);
-                            } else {
-                                self.status_message = format!(
-                                    "✓ Custom password saved for '{}'",
-                                    self.app_name_input.value
-                                );
-                                self.app_name_input.clear();
-                                self.password_input.clear();
-                                self.active_input = 0;
-                            }
-                        }
-                        Err(e) => {
-                            self.status_message = format!("✗ Error: {}", e);
-                        }
+            KeyCode::Enter if !self.app_name_input.value.is_empty() && !self.password_input.value.is_empty() => {
+                match save_custom_password(
+                    &self.app_name_input.value,
+                    &self.password_input.value,
+                ) {
+                    Ok(estimate) => {
+                        self.status_message = format!(
+                            "✓ Custom password saved for '{}' (strength: {})",
+                            self.app_name_input.value,
+                            estimate
+                        );
+                        self.app_name_input.clear();
+                        self.password_input.clear();
+                        self.active_input = 0;
+                    }
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
                     }
                 }
             }
@@ -443,118 +560,141 @@ impl App {
         Ok(())
     }
 
-    /// Loads all passwords from keyring
+    /// Loads all passwords from keyring, keeping those matching `list_filter`
     fn load_passwords(&mut self) {
         self.password_list.clear();
+        self.all_passwords.clear();
         self.selected_list_item = 0;
 
-        if let Ok(entry) = Entry::new(crate::app::APP_SERVICE, crate::app::APP_INDEX) {
-            if let Ok(data) = entry.get_password() {
-                let app_names: Vec<&str> = data.split(',').filter(|s| !s.is_empty()).collect();
-                for app_name in app_names {
-                    // Skip password_length, _type metadata, and internal index - they're settings/metadata, not real passwords
-                    if app_name == crate::app::PASSWORD_LENGTH_KEY 
-                        || app_name.ends_with(crate::app::PASSWORD_TYPE_SUFFIX)
-                        || app_name == crate::app::APP_INDEX {
-                        continue;
-                    }
-                    if let Ok(password) = get_from_keyring(app_name) {
-                        let pw_type = get_password_type(app_name).unwrap_or_else(|| "auto".to_string());
-                        self.password_list.push(PasswordEntry {
-                            app_name: app_name.to_string(),
+        match load_index() {
+            Ok(index) if index.is_empty() => {
+                self.status_message = "No passwords found in keyring".to_string();
+            }
+            Ok(index) => {
+                for entry in index.entries.into_values() {
+                    if let Ok(password) = get_from_keyring(&entry.name) {
+                        let record = get_entry_record(&entry.name).unwrap_or_default();
+                        self.all_passwords.push(PasswordEntry {
+                            app_name: entry.name,
                             password,
-                            password_type: pw_type,
+                            password_type: entry.password_type,
+                            record,
                         });
                     }
                 }
-            } else {
-                self.status_message = "No passwords found in keyring".to_string();
+                self.apply_list_filter();
             }
-        } else {
-            self.status_message = "Failed to access keyring".to_string();
+            Err(e) => {
+                self.status_message = format!("✗ {}", e);
+            }
+        }
+    }
+
+    /// Shows the loaded entries matching `list_filter` (names, usernames, URLs and tags),
+    /// best match first, or all of them when the filter is empty
+    fn apply_list_filter(&mut self) {
+        let query = self.list_filter.value.trim();
+        self.selected_list_item = 0;
+        if query.is_empty() {
+            self.password_list = self.all_passwords.clone();
+            return;
         }
+        let mode = SearchMode::detect(query);
+        let mut matches: Vec<(u32, &PasswordEntry)> = self
+            .all_passwords
+            .iter()
+            .filter_map(|entry| {
+                match_entry(query, mode, &entry.app_name, &entry.record).map(|(_, _, score)| (score, entry))
+            })
+            .collect();
+        matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.app_name.cmp(&b.1.app_name)));
+        self.password_list = matches.into_iter().map(|(_, entry)| entry.clone()).collect();
     }
 
-    /// Loads passwords filtered by type ("auto" or "custom")
-    fn load_passwords_by_type(&mut self, filter_type: &str) {
+    /// Loads passwords filtered by type ("auto", "pin" or "custom")
+    fn load_passwords_by_type(&mut self, filter_types: &[&str]) {
         self.password_list.clear();
         self.selected_list_item = 0;
 
-        if let Ok(entry) = Entry::new(crate::app::APP_SERVICE, crate::app::APP_INDEX) {
-            if let Ok(data) = entry.get_password() {
-                let app_names: Vec<&str> = data.split(',').filter(|s| !s.is_empty()).collect();
-                for app_name in app_names {
-                    // Skip password_length, _type metadata, and internal index
-                    if app_name == crate::app::PASSWORD_LENGTH_KEY 
-                        || app_name.ends_with(crate::app::PASSWORD_TYPE_SUFFIX)
-                        || app_name == crate::app::APP_INDEX {
+        match load_index() {
+            Ok(index) if index.is_empty() => {
+                self.status_message = format!("No {} passwords found", filter_types[0]);
+            }
+            Ok(index) => {
+                for entry in index.entries.into_values() {
+                    // Filter by type
+                    if !filter_types.contains(&entry.password_type.as_str()) {
                         continue;
                     }
-                    if let Ok(password) = get_from_keyring(app_name) {
-                        let pw_type = get_password_type(app_name).unwrap_or_else(|| "auto".to_string());
-                        // Filter by type
-                        if pw_type == filter_type {
-                            self.password_list.push(PasswordEntry {
-                                app_name: app_name.to_string(),
-                                password,
-                                password_type: pw_type,
-                            });
-                        }
+                    if let Ok(password) = get_from_keyring(&entry.name) {
+                        let record = get_entry_record(&entry.name).unwrap_or_default();
+                        self.password_list.push(PasswordEntry {
+                            app_name: entry.name,
+                            password,
+                            password_type: entry.password_type,
+                            record,
+                        });
                     }
                 }
-            } else {
-                self.status_message = format!("No {} passwords found", filter_type);
             }
-        } else {
-            self.status_message = "Failed to access keyring".to_string();
+            Err(e) => {
+                self.status_message = format!("✗ {}", e);
+            }
         }
     }
 
-    /// Check if there are any real passwords (excluding password_length setting, _type metadata, and internal index)
+    /// Check if there are any real passwords (excluding settings and metadata, which are never indexed)
     pub fn has_passwords(&self) -> bool {
-        if let Ok(entry) = Entry::new(crate::app::APP_SERVICE, crate::app::APP_INDEX) {
-            if let Ok(data) = entry.get_password() {
-                let app_names: Vec<&str> = data.split(',')
-                    .filter(|s| !s.is_empty() 
-                        && *s != crate::app::PASSWORD_LENGTH_KEY
-                        && !s.ends_with(crate::app::PASSWORD_TYPE_SUFFIX)
-                        && *s != crate::app::APP_INDEX)
-                    .collect();
-                return !app_names.is_empty();
-            }
-        }
-        false
+        has_any_passwords()
     }
 
-    /// Handles keys in list mode
+    /// Handles keys in list mode. Typed characters go to the filter box.
     fn handle_list_key(&mut self, key: KeyEvent) -> io::Result<()> {
         match key.code {
+            KeyCode::Esc if !self.list_filter.value.is_empty() => {
+                self.list_filter.clear();
+                self.apply_list_filter();
+            }
             KeyCode::Esc => {
                 self.mode = Mode::Menu;
             }
-            KeyCode::Up => {
-                if self.selected_list_item > 0 {
-                    self.selected_list_item -= 1;
-                }
+            KeyCode::Up if self.selected_list_item > 0 => {
+                self.selected_list_item -= 1;
             }
-            KeyCode::Down => {
+            KeyCode::Down
                 if !self.password_list.is_empty()
                     && self.selected_list_item < self.password_list.len() - 1
-                {
+                => {
                     self.selected_list_item += 1;
                 }
-            }
-            KeyCode::Enter => {
+            KeyCode::Enter
                 if !self.password_list.is_empty()
                     && self.selected_list_item < self.password_list.len()
-                {
+                => {
                     self.mode = Mode::View;
                 }
+            KeyCode::F(5) => {
+                self.load_passwords();
+                self.status_message = "✓ List refreshed".to_string();
             }
-            KeyCode::Char('r') => {
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.load_passwords();
                 self.status_message = "✓ List refreshed".to_string();
             }
+            KeyCode::Char(c) => {
+                self.list_filter.insert_char(c);
+                self.apply_list_filter();
+            }
+            KeyCode::Backspace => {
+                self.list_filter.delete_char();
+                self.apply_list_filter();
+            }
+            KeyCode::Left => {
+                self.list_filter.move_cursor_left();
+            }
+            KeyCode::Right => {
+                self.list_filter.move_cursor_right();
+            }
             _ => {}
         }
         Ok(())
@@ -566,11 +706,110 @@ impl App {
             KeyCode::Esc | KeyCode::Enter => {
                 self.mode = Mode::List;
             }
+            KeyCode::Char('h') => {
+                self.status_message.clear();
+                self.load_history();
+                self.mode = Mode::History;
+            }
+            KeyCode::Char('c') => {
+                if let Some(entry) = self.password_list.get(self.selected_list_item) {
+                    self.status_message = match copy_to_clipboard(&entry.password) {
+                        Ok(0) => format!("✓ Password for '{}' copied to the clipboard", entry.app_name),
+                        Ok(timeout) => format!(
+                            "✓ Password for '{}' copied to the clipboard, cleared in {} seconds",
+                            entry.app_name, timeout
+                        ),
+                        Err(e) => format!("✗ {}", e),
+                    };
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Loads the password history of the selected entry
+    fn load_history(&mut self) {
+        self.history_list.clear();
+        self.selected_history_item = 0;
+        if let Some(entry) = self.password_list.get(self.selected_list_item) {
+            match get_history(&entry.app_name) {
+                Ok(history) => self.history_list = history,
+                Err(e) => self.status_message = format!("✗ Failed to load history: {}", e),
+            }
+        }
+    }
+
+    /// Handles keys in history mode
+    fn handle_history_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::View;
+            }
+            KeyCode::Up if self.selected_history_item > 0 => {
+                self.selected_history_item -= 1;
+            }
+            KeyCode::Down
+                if !self.history_list.is_empty()
+                    && self.selected_history_item < self.history_list.len() - 1
+                => {
+                    self.selected_history_item += 1;
+                }
+            KeyCode::Enter
+                if !self.history_list.is_empty()
+                    && self.selected_list_item < self.password_list.len()
+                => {
+                    let app_name = self.password_list[self.selected_list_item].app_name.clone();
+                    match restore_password(&app_name, self.selected_history_item + 1) {
+                        Ok(password) => {
+                            if let Some(entry) = self.all_passwords.iter_mut().find(|e| e.app_name == app_name) {
+                                entry.password = password.clone();
+                            }
+                            self.password_list[self.selected_list_item].password = password;
+                            self.status_message = format!("✓ Password restored for '{}'", app_name);
+                            self.load_history();
+                        }
+                        Err(e) => {
+                            self.status_message = format!("✗ {}", e);
+                        }
+                    }
+                }
             _ => {}
         }
         Ok(())
     }
 
+    /// Saves the selected entry under `new_app_name` with `new_password`.
+    ///
+    /// The previous password goes to the history. If the entry was renamed, its details
+    /// and history move to the new name and the old entry is deleted once the new one is
+    /// fully written. Renaming onto an existing entry fails with `AppError::AlreadyExists`.
+    fn save_updated_entry(&self, new_app_name: &str, new_password: &str) -> Result<(), AppError> {
+        let entry = &self.password_list[self.selected_list_item];
+        if new_app_name != entry.app_name {
+            validate_app_name(new_app_name)?;
+            match get_from_keyring(new_app_name) {
+                Ok(_) => return Err(AppError::AlreadyExists(new_app_name.to_string())),
+                Err(AppError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+            let history = get_history(&entry.app_name)?;
+            save_to_keyring(new_app_name, new_password)?;
+            let moved = set_password_type(new_app_name, &entry.password_type)
+                .and_then(|()| set_history(new_app_name, &history))
+                .and_then(|()| push_history(new_app_name, &entry.password, "tui"))
+                .and_then(|()| set_entry_record(new_app_name, &entry.record));
+            if let Err(e) = moved {
+                // Leave the old entry untouched rather than half of it under the new name
+                let _ = delete_from_keyring(new_app_name);
+                return Err(e);
+            }
+            delete_from_keyring(&entry.app_name)
+        } else {
+            replace_password(new_app_name, new_password, "tui")
+        }
+    }
+
     /// Handles keys in update mode
     /// Handles keys in update auto-generated password mode (list selection + name change only)
     fn handle_update_auto_key(&mut self, key: KeyEvent) -> io::Result<()> {
@@ -585,98 +824,121 @@ impl App {
                     self.mode = Mode::Menu;
                 }
             }
-            KeyCode::Up => {
-                if !self.is_editing && self.selected_list_item > 0 {
-                    self.selected_list_item -= 1;
-                }
+            KeyCode::Up if !self.is_editing && self.selected_list_item > 0 => {
+                self.selected_list_item -= 1;
             }
-            KeyCode::Down => {
-                if !self.is_editing && self.selected_list_item < self.password_list.len().saturating_sub(1) {
-                    self.selected_list_item += 1;
-                }
+            KeyCode::Down if !self.is_editing && self.selected_list_item < self.password_list.len().saturating_sub(1) => {
+                self.selected_list_item += 1;
             }
-            KeyCode::Enter => {
-                if !self.password_list.is_empty() && self.selected_list_item < self.password_list.len() {
-                    let old_app_name = self.password_list[self.selected_list_item].app_name.clone();
+            KeyCode::Enter if !self.password_list.is_empty() && self.selected_list_item < self.password_list.len() => {
+                let old_app_name = self.password_list[self.selected_list_item].app_name.clone();
+                
+                if !self.is_editing {
+                    // First Enter: start editing app name
+                    self.is_editing = true;
+                    self.app_name_input.value = old_app_name.clone();
+                    self.app_name_input.cursor_position = self.app_name_input.value.len();
+                    self.status_message = "Edit app name and press Enter to save (or Esc to cancel)".to_string();
+                } else {
+                    // Second Enter: save with new app name
+                    let new_app_name = self.app_name_input.value.clone();
                     
-                    if !self.is_editing {
-                        // First Enter: start editing app name
-                        self.is_editing = true;
-                        self.app_name_input.value = old_app_name.clone();
-                        self.app_name_input.cursor_position = self.app_name_input.value.len();
-                        self.status_message = "Edit app name and press Enter to save (or Esc to cancel)".to_string();
-                    } else {
-                        // Second Enter: save with new app name
-                        let new_app_name = self.app_name_input.value.clone();
-                        
-                        if new_app_name.is_empty() {
-                            self.status_message = "✗ App name cannot be empty".to_string();
-                            return Ok(());
-                        }
-                        
-                        // Generate new password with current default length
-                        use rand::distributions::Alphanumeric;
-                        use rand::{thread_rng, Rng};
-                        let new_password: String = thread_rng()
-                            .sample_iter(&Alphanumeric)
-                            .take(self.default_password_length)
-                            .map(char::from)
-                            .collect();
-                        
-                        // If name changed, delete old entry
-                        if new_app_name != old_app_name {
-                            let _ = delete_from_keyring(&old_app_name);
-                        }
-                        
-                        // Save with new name and new password
-                        match save_to_keyring(&new_app_name, &new_password) {
+                    if new_app_name.is_empty() {
+                        self.status_message = "✗ App name cannot be empty".to_string();
+                        return Ok(());
+                    }
+                    
+                    // A PIN entry gets a new PIN with as many digits
+                    let old_entry = &self.password_list[self.selected_list_item];
+                    if old_entry.password_type == "pin" {
+                        let new_pin = match generate_pin(old_entry.password.len()) {
+                            Ok(pin) => pin,
+                            Err(e) => {
+                                self.status_message = format!("✗ {}", e);
+                                return Ok(());
+                            }
+                        };
+                        match self.save_updated_entry(&new_app_name, &new_pin) {
                             Ok(_) => {
-                                // Mark as auto-generated
-                                let _ = set_password_type(&new_app_name, "auto");
+                                let _ = set_password_type(&new_app_name, "pin");
                                 self.status_message = format!(
-                                    "✓ Password updated for '{}' (regenerated with {} chars)",
+                                    "✓ PIN updated for '{}' (regenerated with {} digits, {:.1} bits)",
                                     new_app_name,
-                                    self.default_password_length
+                                    new_pin.len(),
+                                    pin_entropy(new_pin.len())
                                 );
                                 self.is_editing = false;
                                 self.app_name_input.clear();
-                                self.load_passwords_by_type("auto");  // Reload only auto passwords
+                                self.load_passwords_by_type(&["auto", "pin"]);
                                 self.selected_list_item = 0;
                             }
                             Err(e) => {
-                                self.status_message = format!("✗ Error: {}", e);
+                                self.status_message = format!("✗ {}", e);
                             }
                         }
+                        return Ok(());
+                    }
+
+                    // Generate new password with the entry's stored length and policy
+                    let generation = match entry_policy(&old_app_name, None, None) {
+                        Ok(generation) => generation,
+                        Err(e) => {
+                            self.status_message = format!("✗ {}", e);
+                            return Ok(());
+                        }
+                    };
+                    let new_password = match generation.generate() {
+                        Ok(password) => password,
+                        Err(e) => {
+                            self.status_message = format!("✗ {}", e);
+                            return Ok(());
+                        }
+                    };
+                    
+                    // Save with new name and new password (details and history move with a renamed entry)
+                    match self.save_updated_entry(&new_app_name, &new_password) {
+                        Ok(_) => {
+                            // Mark as auto-generated and remember the policy for the next rotation
+                            let _ = set_password_type(&new_app_name, "auto");
+                            let length = generation.length;
+                            let entropy = generation.entropy();
+                            let _ = set_entry_policy(&new_app_name, Some(generation));
+                            self.status_message = format!(
+                                "✓ Password updated for '{}' (regenerated with {} chars, {:.1} bits)",
+                                new_app_name,
+                                length,
+                                entropy
+                            );
+                            self.is_editing = false;
+                            self.app_name_input.clear();
+                            self.load_passwords_by_type(&["auto", "pin"]);  // Reload only auto passwords
+                            self.selected_list_item = 0;
+                        }
+                        Err(e) => {
+                            self.status_message = format!("✗ {}", e);
+                        }
                     }
                 }
             }
             KeyCode::Char('r') => {
                 if !self.is_editing {
-                    self.load_passwords_by_type("auto");
+                    self.load_passwords_by_type(&["auto", "pin"]);
                     self.status_message = "✓ Auto passwords list refreshed".to_string();
                 } else {
                     self.app_name_input.insert_char('r');
                 }
             }
-            KeyCode::Char(c) => {
-                if self.is_editing {
-                    self.app_name_input.insert_char(c);
-                }
+            KeyCode::Char(c) if self.is_editing => {
+                self.app_name_input.insert_char(c);
             }
-            KeyCode::Backspace => {
-                if self.is_editing {
-                    self.app_name_input.delete_char();
-                }
+            KeyCode::Backspace if self.is_editing => {
+                self.app_name_input.delete_char();
             }
-            KeyCode::Left => {
-                if self.is_editing {
-                    self.app_name_input.move_cursor_left();
-                }
+            KeyCode::Left if self.is_editing => {
+                self.app_name_input.move_cursor_left();
             }
-            KeyCode::Right => {
-                if self.is_editing {
-                    self.app_name_input.move_cursor_right();
-                }
+            KeyCode::Right if self.is_editing => {
+                self.app_name_input.move_cursor_right();
             }
             _ => {}
         }
@@ -698,20 +960,14 @@ impl App {
                     self.mode = Mode::Menu;
                 }
             }
-            KeyCode::Up => {
-                if !self.is_editing && self.selected_list_item > 0 {
-                    self.selected_list_item -= 1;
-                }
+            KeyCode::Up if !self.is_editing && self.selected_list_item > 0 => {
+                self.selected_list_item -= 1;
             }
-            KeyCode::Down => {
-                if !self.is_editing && self.selected_list_item < self.password_list.len().saturating_sub(1) {
-                    self.selected_list_item += 1;
-                }
+            KeyCode::Down if !self.is_editing && self.selected_list_item < self.password_list.len().saturating_sub(1) => {
+                self.selected_list_item += 1;
             }
-            KeyCode::Tab => {
-                if self.is_editing {
-                    self.active_input = (self.active_input + 1) % 2;
-                }
+            KeyCode::Tab if self.is_editing => {
+                self.active_input = (self.active_input + 1) % 2;
             }
             KeyCode::Enter => {
                 if !self.is_editing {
@@ -738,37 +994,50 @@ impl App {
                     }
                     
                     // Save changes
-                    let old_app_name = self.password_list[self.selected_list_item].app_name.clone();
                     let new_app_name = self.app_name_input.value.clone();
                     let new_password = self.password_input.value.clone();
+                    // A rename keeps the password, which was already checked when it was saved
+                    let estimate = if new_password != self.password_list[self.selected_list_item].password {
+                        match check_password_strength(&new_password) {
+                            Ok(estimate) => Some(estimate),
+                            Err(e) => {
+                                self.status_message = format!("✗ {}", e);
+                                return Ok(());
+                            }
+                        }
+                    } else {
+                        None
+                    };
                     
-                    // If name changed, delete old entry
-                    if new_app_name != old_app_name {
-                        let _ = delete_from_keyring(&old_app_name);
-                    }
-                    
-                    // Save with new values
-                    match save_to_keyring(&new_app_name, &new_password) {
+                    // Save with new values (details and history move with a renamed entry)
+                    match self.save_updated_entry(&new_app_name, &new_password) {
                         Ok(_) => {
                             // Mark as custom password
                             let _ = set_password_type(&new_app_name, "custom");
-                            self.status_message = format!("✓ Custom password updated for '{}'", new_app_name);
+                            self.status_message = match estimate {
+                                Some(estimate) => format!(
+                                    "✓ Custom password updated for '{}' (strength: {})",
+                                    new_app_name,
+                                    estimate
+                                ),
+                                None => format!("✓ Custom password entry updated for '{}'", new_app_name),
+                            };
                             self.is_editing = false;
                             self.app_name_input.clear();
                             self.password_input.clear();
                             self.active_input = 0;
-                            self.load_passwords_by_type("custom");  // Reload only custom passwords
+                            self.load_passwords_by_type(&["custom"]);  // Reload only custom passwords
                             self.selected_list_item = 0;
                         }
                         Err(e) => {
-                            self.status_message = format!("✗ Error: {}", e);
+                            self.status_message = format!("✗ {}", e);
                         }
                     }
                 }
             }
             KeyCode::Char('r') => {
                 if !self.is_editing {
-                    self.load_passwords_by_type("custom");
+                    self.load_passwords_by_type(&["custom"]);
                     self.status_message = "✓ Custom passwords list refreshed".to_string();
                 } else {
                     if self.active_input == 0 {
@@ -778,40 +1047,32 @@ impl App {
                     }
                 }
             }
-            KeyCode::Char(c) => {
-                if self.is_editing {
-                    if self.active_input == 0 {
-                        self.app_name_input.insert_char(c);
-                    } else {
-                        self.password_input.insert_char(c);
-                    }
+            KeyCode::Char(c) if self.is_editing => {
+                if self.active_input == 0 {
+                    self.app_name_input.insert_char(c);
+                } else {
+                    self.password_input.insert_char(c);
                 }
             }
-            KeyCode::Backspace => {
-                if self.is_editing {
-                    if self.active_input == 0 {
-                        self.app_name_input.delete_char();
-                    } else {
-                        self.password_input.delete_char();
-                    }
+            KeyCode::Backspace if self.is_editing => {
+                if self.active_input == 0 {
+                    self.app_name_input.delete_char();
+                } else {
+                    self.password_input.delete_char();
                 }
             }
-            KeyCode::Left => {
-                if self.is_editing {
-                    if self.active_input == 0 {
-                        self.app_name_input.move_cursor_left();
-                    } else {
-                        self.password_input.move_cursor_left();
-                    }
+            KeyCode::Left if self.is_editing => {
+                if self.active_input == 0 {
+                    self.app_name_input.move_cursor_left();
+                } else {
+                    self.password_input.move_cursor_left();
                 }
             }
-            KeyCode::Right => {
-                if self.is_editing {
-                    if self.active_input == 0 {
-                        self.app_name_input.move_cursor_right();
-                    } else {
-                        self.password_input.move_cursor_right();
-                    }
+            KeyCode::Right if self.is_editing => {
+                if self.active_input == 0 {
+                    self.app_name_input.move_cursor_right();
+                } else {
+                    self.password_input.move_cursor_right();
                 }
             }
             _ => {}
@@ -825,31 +1086,25 @@ impl App {
             KeyCode::Esc => {
                 self.mode = Mode::Menu;
             }
-            KeyCode::Up => {
-                if self.selected_list_item > 0 {
-                    self.selected_list_item -= 1;
-                }
+            KeyCode::Up if self.selected_list_item > 0 => {
+                self.selected_list_item -= 1;
             }
-            KeyCode::Down => {
-                if self.selected_list_item < self.password_list.len().saturating_sub(1) {
-                    self.selected_list_item += 1;
-                }
+            KeyCode::Down if self.selected_list_item < self.password_list.len().saturating_sub(1) => {
+                self.selected_list_item += 1;
             }
-            KeyCode::Enter => {
-                if !self.password_list.is_empty() && self.selected_list_item < self.password_list.len() {
-                    let app_name = self.password_list[self.selected_list_item].app_name.clone();
-                    match delete_from_keyring(&app_name) {
-                        Ok(_) => {
-                            self.status_message = format!("✓ Password deleted for '{}'", app_name);
-                            self.load_passwords();  // Reload the list
-                            if self.selected_list_item >= self.password_list.len() && self.selected_list_item > 0 {
-                                self.selected_list_item -= 1;
-                            }
-                        }
-                        Err(e) => {
-                            self.status_message = format!("✗ Error: {}", e);
+            KeyCode::Enter if !self.password_list.is_empty() && self.selected_list_item < self.password_list.len() => {
+                let app_name = self.password_list[self.selected_list_item].app_name.clone();
+                match move_to_trash(&app_name) {
+                    Ok(_) => {
+                        self.status_message = format!("✓ Password for '{}' moved to the trash", app_name);
+                        self.load_passwords();  // Reload the list
+                        if self.selected_list_item >= self.password_list.len() && self.selected_list_item > 0 {
+                            self.selected_list_item -= 1;
                         }
                     }
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
+                    }
                 }
             }
             KeyCode::Char('r') => {
@@ -861,6 +1116,53 @@ impl App {
         Ok(())
     }
 
+    /// Loads the deleted entries from the trash
+    fn load_trash(&mut self) {
+        match list_trash() {
+            Ok(trash) => self.trash_list = trash,
+            Err(e) => {
+                self.trash_list.clear();
+                self.status_message = format!("✗ Failed to load trash: {}", e);
+            }
+        }
+        if self.selected_trash_item >= self.trash_list.len() {
+            self.selected_trash_item = self.trash_list.len().saturating_sub(1);
+        }
+    }
+
+    /// Handles keys in trash mode
+    fn handle_trash_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Menu;
+            }
+            KeyCode::Up if self.selected_trash_item > 0 => {
+                self.selected_trash_item -= 1;
+            }
+            KeyCode::Down if self.selected_trash_item < self.trash_list.len().saturating_sub(1) => {
+                self.selected_trash_item += 1;
+            }
+            KeyCode::Enter if self.selected_trash_item < self.trash_list.len() => {
+                let app_name = self.trash_list[self.selected_trash_item].name.clone();
+                match restore_from_trash(&app_name) {
+                    Ok(_) => self.status_message = format!("✓ Password restored for '{}'", app_name),
+                    Err(e) => self.status_message = format!("✗ Failed to restore '{}': {}", app_name, e),
+                }
+                self.load_trash();
+            }
+            KeyCode::Char('p') if self.selected_trash_item < self.trash_list.len() => {
+                let app_name = self.trash_list[self.selected_trash_item].name.clone();
+                match purge_trash(Some(&app_name)) {
+                    Ok(_) => self.status_message = format!("✓ '{}' permanently deleted", app_name),
+                    Err(e) => self.status_message = format!("✗ {}", e),
+                }
+                self.load_trash();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     /// Handles keys for OTP generation
     fn handle_generate_otp_key(&mut self, key: KeyEvent) -> io::Result<()> {
         match key.code {
@@ -870,31 +1172,33 @@ impl App {
             KeyCode::Tab => {
                 self.active_input = (self.active_input + 1) % 2;
             }
-            KeyCode::Enter => {
-                if !self.app_name_input.value.is_empty() {
-                    // Parse TTL in seconds, default to 300 seconds (5 minutes) if not provided
-                    let ttl = if !self.length_input.value.is_empty() {
-                        self.length_input.value.parse::<u64>().unwrap_or(300)
-                    } else {
-                        300
-                    };
-                    
-                    // Use the configured default password length for OTP
-                    match crate::app::otp::generate_otp(&self.app_name_input.value, ttl, self.default_password_length) {
-                        Ok(otp) => {
-                            self.status_message = format!(
-                                "✓ OTP saved for '{}' (expires in {} seconds): {}",
-                                self.app_name_input.value, ttl, otp
-                            );
-                        }
-                        Err(e) => {
-                            self.status_message = format!("✗ Error: {}", e);
-                        }
+            KeyCode::Enter if !self.app_name_input.value.is_empty() => {
+                // Parse TTL in seconds, default to otp.ttl from the configuration if not provided
+                let default_ttl = config().otp.ttl;
+                let ttl = if !self.length_input.value.is_empty() {
+                    self.length_input.value.parse::<u64>().unwrap_or(default_ttl)
+                } else {
+                    default_ttl
+                };
+                
+                // Use the configured default password length for OTP
+                match crate::app::otp::generate_otp(&self.app_name_input.value, ttl, self.default_password_length, None) {
+                    Ok(otp) => {
+                        self.status_message = format!(
+                            "✓ OTP saved for '{}' (expires in {} seconds, {:.1} bits): {}",
+                            self.app_name_input.value,
+                            ttl,
+                            config().password.policy.entropy(self.default_password_length),
+                            otp
+                        );
+                    }
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
                     }
-                    self.app_name_input.clear();
-                    self.length_input.clear();
-                    self.active_input = 0;
                 }
+                self.app_name_input.clear();
+                self.length_input.clear();
+                self.active_input = 0;
             }
             KeyCode::Char(c) => {
                 if self.active_input == 0 {
@@ -935,19 +1239,18 @@ impl App {
             KeyCode::Esc => {
                 self.mode = Mode::Menu;
             }
-            KeyCode::Enter => {
-                if !self.app_name_input.value.is_empty() {
-                    match crate::app::password::generate_memorizable_password(&self.app_name_input.value) {
-                        Ok(_) => {
-                            self.status_message = format!(
-                                "✓ Memorizable password generated for '{}'",
-                                self.app_name_input.value
-                            );
-                            self.app_name_input.clear();
-                        }
-                        Err(e) => {
-                            self.status_message = format!("✗ Error: {}", e);
-                        }
+            KeyCode::Enter if !self.app_name_input.value.is_empty() => {
+                match crate::app::password::generate_memorizable_password(&self.app_name_input.value, None) {
+                    Ok(passphrase) => {
+                        self.status_message = format!(
+                            "✓ Memorizable password generated for '{}' ({:.1} bits of entropy)",
+                            self.app_name_input.value,
+                            passphrase.entropy
+                        );
+                        self.app_name_input.clear();
+                    }
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
                     }
                 }
             }
@@ -974,19 +1277,17 @@ impl App {
             KeyCode::Esc => {
                 self.mode = Mode::Menu;
             }
-            KeyCode::Enter => {
-                if !self.app_name_input.value.is_empty() {
-                    match crate::app::password::export_passwords(&self.app_name_input.value) {
-                        Ok(_) => {
-                            self.status_message = format!(
-                                "✓ Passwords exported to '{}'",
-                                self.app_name_input.value
-                            );
-                            self.app_name_input.clear();
-                        }
-                        Err(e) => {
-                            self.status_message = format!("✗ Error: {}", e);
-                        }
+            KeyCode::Enter if !self.app_name_input.value.is_empty() => {
+                match crate::app::password::export_passwords(&self.app_name_input.value) {
+                    Ok(_) => {
+                        self.status_message = format!(
+                            "✓ Passwords exported to '{}'",
+                            self.app_name_input.value
+                        );
+                        self.app_name_input.clear();
+                    }
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
                     }
                 }
             }
@@ -1013,19 +1314,26 @@ impl App {
             KeyCode::Esc => {
                 self.mode = Mode::Menu;
             }
-            KeyCode::Enter => {
-                if !self.app_name_input.value.is_empty() {
-                    match crate::app::password::import_passwords(&self.app_name_input.value) {
-                        Ok(_) => {
-                            self.status_message = format!(
-                                "✓ Passwords imported from '{}'",
-                                self.app_name_input.value
-                            );
-                            self.app_name_input.clear();
-                        }
-                        Err(e) => {
-                            self.status_message = format!("✗ Error: {}", e);
-                        }
+            KeyCode::Enter if !self.app_name_input.value.is_empty() => {
+                match crate::app::password::import_passwords(&self.app_name_input.value) {
+                    Ok(breached) if !breached.is_empty() => {
+                        let names: Vec<&str> = breached.iter().map(|(name, _)| name.as_str()).collect();
+                        self.status_message = format!(
+                            "✓ Passwords imported from '{}' (found in known breaches: {})",
+                            self.app_name_input.value,
+                            names.join(", ")
+                        );
+                        self.app_name_input.clear();
+                    }
+                    Ok(_) => {
+                        self.status_message = format!(
+                            "✓ Passwords imported from '{}'",
+                            self.app_name_input.value
+                        );
+                        self.app_name_input.clear();
+                    }
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
                     }
                 }
             }
@@ -1048,40 +1356,50 @@ impl App {
 
     /// Handles keys in settings mode
     fn handle_settings_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        let toggle = is_toggle_setting(SETTINGS_ROWS[self.selected_setting].1);
+        let input = &mut self.settings_inputs[self.selected_setting];
         match key.code {
             KeyCode::Esc => {
                 self.mode = Mode::Menu;
             }
             KeyCode::Enter => {
-                // Parse and save the new default password length
-                if let Ok(length) = self.length_input.value.parse::<usize>() {
-                    if length >= 8 && length <= 128 {
-                        self.default_password_length = length;
-                        // Save to keyring for persistence
-                        if let Err(e) = Self::save_password_length_setting(length) {
-                            self.status_message = format!("✗ Failed to save setting: {}", e);
-                        } else {
-                            self.status_message = format!("✓ Default password length set to {} characters (saved)", length);
-                        }
+                // Save every row at once so dependent settings can change together
+                match self.save_settings() {
+                    Ok(_) => {
+                        self.default_password_length = config().password.length;
+                        self.status_message = format!(
+                            "✓ Password settings saved (default length {} characters)",
+                            self.default_password_length
+                        );
                         self.mode = Mode::Menu;
-                    } else {
-                        self.status_message = "✗ Length must be between 8 and 128 characters".to_string();
                     }
-                } else {
-                    self.status_message = "✗ Please enter a valid number".to_string();
+                    Err(e) => {
+                        self.status_message = format!("✗ {}", e);
+                    }
                 }
             }
-            KeyCode::Char(c) if c.is_ascii_digit() => {
-                self.length_input.insert_char(c);
+            KeyCode::Down | KeyCode::Tab => {
+                self.selected_setting = (self.selected_setting + 1) % SETTINGS_ROWS.len();
             }
-            KeyCode::Backspace => {
-                self.length_input.delete_char();
+            KeyCode::Up | KeyCode::BackTab => {
+                self.selected_setting = (self.selected_setting + SETTINGS_ROWS.len() - 1) % SETTINGS_ROWS.len();
+            }
+            KeyCode::Char(' ') if toggle => {
+                let enabled = input.value == "true";
+                input.value = (!enabled).to_string();
+                input.cursor_position = input.value.len();
+            }
+            KeyCode::Char(c) if !toggle => {
+                input.insert_char(c);
+            }
+            KeyCode::Backspace if !toggle => {
+                input.delete_char();
             }
             KeyCode::Left => {
-                self.length_input.move_cursor_left();
+                input.move_cursor_left();
             }
             KeyCode::Right => {
-                self.length_input.move_cursor_right();
+                input.move_cursor_right();
             }
             _ => {}
         }
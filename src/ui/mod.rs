@@ -4,8 +4,10 @@ mod app;
 mod event;
 mod ui_render;
 
-pub use app::App;
-pub use event::{Event, EventHandler};
+use crate::app::clipboard::clear_clipboard;
+use crate::app::config::config;
+use app::App;
+use event::{Event, EventHandler};
 
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
@@ -26,10 +28,12 @@ pub fn run_tui() -> io::Result<()> {
 
     // Create app state
     let mut app = App::new();
-    let event_handler = EventHandler::new(250);
+    let event_handler = EventHandler::new(config().ui.tick_rate_ms);
 
     // Main loop
     let res = run_app(&mut terminal, &mut app, event_handler);
+    // Don't leave a copied password behind once the TUI is closed
+    clear_clipboard();
 
     // Restore terminal
     disable_raw_mode()?;
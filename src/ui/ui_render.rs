@@ -1,5 +1,10 @@
-use crate::ui::app::{App, Mode};
+use crate::ui::app::{is_toggle_setting, App, Mode, SETTINGS_ROWS};
+use crate::app::config::config;
+use crate::app::history::format_timestamp;
 use crate::app::keyring::{has_auto_passwords, has_custom_passwords};
+use crate::app::pin::PIN_LENGTH_RANGE;
+use crate::app::profile::active_profile;
+use crate::app::strength::{estimate_strength, Strength};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -34,6 +39,8 @@ pub fn render(f: &mut Frame, app: &App) {
         Mode::Export => render_export(f, chunks[1], app),
         Mode::Import => render_import(f, chunks[1], app),
         Mode::Settings => render_settings(f, chunks[1], app),
+        Mode::History => render_history(f, chunks[1], app),
+        Mode::Trash => render_trash(f, chunks[1], app),
     }
 
     render_footer(f, chunks[2], app);
@@ -41,7 +48,10 @@ pub fn render(f: &mut Frame, app: &App) {
 
 /// Renders the header
 fn render_header(f: &mut Frame, area: Rect) {
-    let title = Paragraph::new("🔒 AppPass - Interactive Password Manager")
+    let title = Paragraph::new(format!(
+        "🔒 AppPass - Interactive Password Manager  [profile: {}]",
+        active_profile()
+    ))
         .style(
             Style::default()
                 .fg(Color::Cyan)
@@ -56,10 +66,10 @@ fn render_header(f: &mut Frame, area: Rect) {
 fn render_footer(f: &mut Frame, area: Rect, app: &App) {
     let help_text = match app.mode {
         Mode::Menu => "↑↓: Navigate | Enter: Select | q/Esc: Quit",
-        Mode::Create => "Enter: Create | Esc: Back",
+        Mode::Create => "Tab: Switch Field | Enter: Create | Esc: Back",
         Mode::CreateCustom => "Tab: Switch Field | Enter: Create | Esc: Back",
-        Mode::List => "↑↓: Navigate | Enter: View | r: Refresh | Esc: Back",
-        Mode::View => "Enter/Esc: Back",
+        Mode::List => "Type: Filter | ↑↓: Navigate | Enter: View | F5: Refresh | Esc: Clear/Back",
+        Mode::View => "c: Copy | h: History | Enter/Esc: Back",
         Mode::UpdateAuto => "↑↓: Navigate | Enter: Select/Save | r: Refresh | Esc: Back",
         Mode::UpdateCustom => "↑↓: Navigate | Enter: Select | Tab: Switch Field | Esc: Back",
         Mode::Delete => "↑↓: Navigate | Enter: Delete | r: Refresh | Esc: Back",
@@ -67,7 +77,9 @@ fn render_footer(f: &mut Frame, area: Rect, app: &App) {
         Mode::Memorizable => "Enter: Generate | Esc: Back",
         Mode::Export => "Enter: Export | Esc: Back",
         Mode::Import => "Enter: Import | Esc: Back",
-        Mode::Settings => "Enter: Save | Esc: Cancel",
+        Mode::Settings => "↑↓/Tab: Navigate | Space: Toggle | Enter: Save | Esc: Cancel",
+        Mode::History => "↑↓: Navigate | Enter: Restore | Esc: Back",
+        Mode::Trash => "↑↓: Navigate | Enter: Restore | p: Purge | Esc: Back",
     };
 
     let footer = Paragraph::new(help_text)
@@ -77,12 +89,14 @@ fn render_footer(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(footer, area);
 }
 
-/// Renders the settings form (password length configuration)
+/// Renders the settings form (password length and policy)
 fn render_settings(f: &mut Frame, area: Rect, app: &App) {
+    const LABEL_WIDTH: usize = 36;
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),
+            Constraint::Length(SETTINGS_ROWS.len() as u16 + 2),
             Constraint::Length(8),
             Constraint::Length(3),
             Constraint::Min(0),
@@ -90,30 +104,35 @@ fn render_settings(f: &mut Frame, area: Rect, app: &App) {
         .margin(2)
         .split(area);
 
-    // Password length input
-    let length_input = Paragraph::new(app.length_input.value.as_str())
-        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
-        .block(
-            Block::default()
-                .title("Default Password Length (8-128 characters)")
-                .borders(Borders::ALL),
-        );
-    f.render_widget(length_input, chunks[0]);
+    // One row per setting, toggles shown as checkboxes
+    let items: Vec<ListItem> = SETTINGS_ROWS
+        .iter()
+        .zip(&app.settings_inputs)
+        .enumerate()
+        .map(|(i, ((label, key), input))| {
+            let value = if is_toggle_setting(key) {
+                if input.value == "true" { "[x]" } else { "[ ]" }.to_string()
+            } else {
+                input.value.clone()
+            };
+            let style = if i == app.selected_setting {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(format!("{:<width$} {}", label, value, width = LABEL_WIDTH)).style(style)
+        })
+        .collect();
+    let form = List::new(items).block(Block::default().title("Password Settings").borders(Borders::ALL));
+    f.render_widget(form, chunks[0]);
 
     // Info section
-    let info_text = format!(
-        "ℹ️  Settings - Password Length Configuration\n\
-         \n\
-         Set the default length for auto-generated passwords.\n\
-         Current: {} characters\n\
-         Range: 8-128 characters\n\
-         \n\
-         This setting is persistent and saved to keyring.\n\
-         It will be remembered across app restarts.\n\
-         \n\
-         Affects: Create New Password, Generate OTP, Generate Memorizable Password",
-        app.default_password_length
-    );
+    let info_text = "ℹ️  Character classes: comma-separated list of lower, upper, digits, symbols.\n\
+         Minimum counts only apply to classes in the list; they must fit in the length.\n\
+         Symbols are drawn from the symbol set; excluded characters are never used.\n\
+         Minimum strength (none, very-weak, weak, fair, strong, very-strong) rejects weak custom passwords.\n\
+         Default PIN digits: length of PINs created from the console or the command line without --length.\n\
+         Saved to config.toml. Affects: Create New Password, Update Auto-generated Password, Generate OTP";
     let info = Paragraph::new(info_text)
         .style(Style::default().fg(Color::Cyan))
         .block(Block::default().borders(Borders::ALL).title("Info"))
@@ -133,10 +152,15 @@ fn render_settings(f: &mut Frame, area: Rect, app: &App) {
         f.render_widget(status, chunks[2]);
     }
 
-    // Set cursor position
-    let cursor_x = chunks[0].x + (app.length_input.cursor_position as u16).min(chunks[0].width.saturating_sub(2)) + 1;
-    let cursor_y = chunks[0].y + 1;
-    f.set_cursor_position((cursor_x, cursor_y));
+    // Set cursor position on the selected text field
+    if !is_toggle_setting(SETTINGS_ROWS[app.selected_setting].1) {
+        let input = &app.settings_inputs[app.selected_setting];
+        let cursor_x = chunks[0].x
+            + (LABEL_WIDTH as u16 + 1 + input.cursor_position as u16).min(chunks[0].width.saturating_sub(2))
+            + 1;
+        let cursor_y = chunks[0].y + 1 + app.selected_setting as u16;
+        f.set_cursor_position((cursor_x, cursor_y));
+    }
 }
 
 /// Renders the main menu
@@ -156,7 +180,8 @@ fn render_menu(f: &mut Frame, area: Rect, app: &App) {
         ("Generate Memorizable Password", has_passwords),
         ("Export Passwords to CSV", has_passwords),
         ("Import Passwords from CSV", true),
-        ("Settings (Password Length)", true),
+        ("Settings (Password Length & Policy)", true),
+        ("Trash (Restore Deleted Passwords)", true),
         ("Set Auto-Lock", true),
         ("Exit", true),
     ];
@@ -221,7 +246,9 @@ fn render_create(f: &mut Frame, area: Rect, app: &App) {
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),
-            Constraint::Length(5),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(8),
             Constraint::Length(3),
             Constraint::Min(0),
         ])
@@ -229,8 +256,13 @@ fn render_create(f: &mut Frame, area: Rect, app: &App) {
         .split(area);
 
     // App name input
+    let app_name_style = if app.active_input == 0 {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
     let app_name_input = Paragraph::new(app.app_name_input.value.as_str())
-        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .style(app_name_style)
         .block(
             Block::default()
                 .title("Application Name")
@@ -238,18 +270,52 @@ fn render_create(f: &mut Frame, area: Rect, app: &App) {
         );
     f.render_widget(app_name_input, chunks[0]);
 
+    // Template input
+    let template_style = if app.active_input == 1 {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let template_input = Paragraph::new(app.template_input.value.as_str())
+        .style(template_style)
+        .block(
+            Block::default()
+                .title("Template (optional)")
+                .borders(Borders::ALL),
+        );
+    f.render_widget(template_input, chunks[1]);
+
+    // PIN digits input
+    let pin_style = if app.active_input == 2 {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let pin_input = Paragraph::new(app.length_input.value.as_str())
+        .style(pin_style)
+        .block(
+            Block::default()
+                .title("PIN Digits (optional, creates a numeric PIN)")
+                .borders(Borders::ALL),
+        );
+    f.render_widget(pin_input, chunks[2]);
+
     // Info section
     let info_text = format!(
         "ℹ️  Create New Password (Auto-generated)\n\
-         Generates a secure {}-character password automatically.\n\
-         Example: Enter 'gmail' to create a password for Gmail.",
-        app.default_password_length
+         Generates a secure {}-character password automatically, or one in the template's format.\n\
+         Template: c/C consonant, v/V vowel, l/L letter, 9 digit, ! symbol, x letter or digit,\n\
+         [A-F] set, {{n}} repeat, (...) group, \\ literal. Example: Cvcc-9999-!! or x{{5}}(-x{{5}}){{3}}\n\
+         PIN: {}-{} digits, never a sequence, repeat, common PIN or date.",
+        app.default_password_length,
+        PIN_LENGTH_RANGE.start(),
+        PIN_LENGTH_RANGE.end()
     );
     let info = Paragraph::new(info_text)
         .style(Style::default().fg(Color::Cyan))
         .block(Block::default().borders(Borders::ALL).title("Info"))
         .wrap(Wrap { trim: false });
-    f.render_widget(info, chunks[1]);
+    f.render_widget(info, chunks[3]);
 
     // Status message
     if !app.status_message.is_empty() {
@@ -261,12 +327,17 @@ fn render_create(f: &mut Frame, area: Rect, app: &App) {
         let status = Paragraph::new(app.status_message.as_str())
             .style(Style::default().fg(status_color))
             .block(Block::default().borders(Borders::ALL).title("Status"));
-        f.render_widget(status, chunks[2]);
+        f.render_widget(status, chunks[4]);
     }
 
     // Set cursor position
-    let cursor_x = chunks[0].x + (app.app_name_input.cursor_position as u16).min(chunks[0].width.saturating_sub(2)) + 1;
-    let cursor_y = chunks[0].y + 1;
+    let (chunk, input) = match app.active_input {
+        0 => (chunks[0], &app.app_name_input),
+        1 => (chunks[1], &app.template_input),
+        _ => (chunks[2], &app.length_input),
+    };
+    let cursor_x = chunk.x + (input.cursor_position as u16).min(chunk.width.saturating_sub(2)) + 1;
+    let cursor_y = chunk.y + 1;
     f.set_cursor_position((cursor_x, cursor_y));
 }
 
@@ -277,6 +348,7 @@ fn render_create_custom(f: &mut Frame, area: Rect, app: &App) {
         .constraints([
             Constraint::Length(3),
             Constraint::Length(3),
+            Constraint::Length(4),
             Constraint::Length(5),
             Constraint::Length(3),
             Constraint::Min(0),
@@ -315,6 +387,7 @@ fn render_create_custom(f: &mut Frame, area: Rect, app: &App) {
                 .borders(Borders::ALL),
         );
     f.render_widget(password_input, chunks[1]);
+    render_strength(f, chunks[2], &app.password_input.value);
 
     // Info section
     let info_text = "ℹ️  Create Custom Password\n\
@@ -324,7 +397,7 @@ fn render_create_custom(f: &mut Frame, area: Rect, app: &App) {
         .style(Style::default().fg(Color::Cyan))
         .block(Block::default().borders(Borders::ALL).title("Info"))
         .wrap(Wrap { trim: false });
-    f.render_widget(info, chunks[2]);
+    f.render_widget(info, chunks[3]);
 
     // Status message
     if !app.status_message.is_empty() {
@@ -336,7 +409,7 @@ fn render_create_custom(f: &mut Frame, area: Rect, app: &App) {
         let status = Paragraph::new(app.status_message.as_str())
             .style(Style::default().fg(status_color))
             .block(Block::default().borders(Borders::ALL).title("Status"));
-        f.render_widget(status, chunks[3]);
+        f.render_widget(status, chunks[4]);
     }
 
     // Set cursor position
@@ -351,9 +424,35 @@ fn render_create_custom(f: &mut Frame, area: Rect, app: &App) {
     }
 }
 
-/// Renders the password list
+/// Renders the estimated strength of the custom password being typed and the guessable
+/// patterns found in it
+fn render_strength(f: &mut Frame, area: Rect, password: &str) {
+    let (text, color) = if password.is_empty() {
+        ("Type a password to rate it".to_string(), Color::DarkGray)
+    } else {
+        let estimate = estimate_strength(password);
+        let patterns: Vec<String> = estimate.patterns.iter().map(|p| p.to_string()).collect();
+        let color = match estimate.strength {
+            Strength::VeryWeak | Strength::Weak => Color::Red,
+            Strength::Fair => Color::Yellow,
+            Strength::Strong | Strength::VeryStrong => Color::Green,
+        };
+        (format!("{}\n{}", estimate, patterns.join("; ")), color)
+    };
+    let title = match config().password.min_strength {
+        Some(minimum) => format!("Strength (minimum: {})", minimum),
+        None => "Strength".to_string(),
+    };
+    let strength = Paragraph::new(text)
+        .style(Style::default().fg(color))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false });
+    f.render_widget(strength, area);
+}
+
+/// Renders the password list with its filter box
 fn render_list(f: &mut Frame, area: Rect, app: &App) {
-    if app.password_list.is_empty() {
+    if app.all_passwords.is_empty() {
         let empty_msg = Paragraph::new("No passwords stored yet.\nCreate one from the main menu!")
             .style(Style::default().fg(Color::Yellow))
             .alignment(Alignment::Center)
@@ -366,6 +465,32 @@ fn render_list(f: &mut Frame, area: Rect, app: &App) {
         return;
     }
 
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let filter = Paragraph::new(app.list_filter.value.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .title("Filter (name, username, URL or tag; * and ? for wildcards)")
+                .borders(Borders::ALL),
+        );
+    f.render_widget(filter, chunks[0]);
+    let cursor_x = chunks[0].x + (app.list_filter.cursor_position as u16).min(chunks[0].width.saturating_sub(2)) + 1;
+    f.set_cursor_position((cursor_x, chunks[0].y + 1));
+
+    if app.password_list.is_empty() {
+        let no_match = Paragraph::new(format!("No entries match '{}'.", app.list_filter.value))
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Center)
+            .block(Block::default().title("Password List").borders(Borders::ALL));
+        f.render_widget(no_match, chunks[1]);
+        return;
+    }
+
+    let show_passwords = config().ui.show_passwords;
     let items: Vec<ListItem> = app
         .password_list
         .iter()
@@ -379,21 +504,29 @@ fn render_list(f: &mut Frame, area: Rect, app: &App) {
             } else {
                 Style::default().fg(Color::White)
             };
-            let masked_pwd = "*".repeat(entry.password.len().min(20));
-            ListItem::new(format!("  {} - {}", entry.app_name, masked_pwd)).style(style)
+            let shown_pwd = if show_passwords {
+                entry.password.clone()
+            } else {
+                "*".repeat(entry.password.len().min(20))
+            };
+            ListItem::new(format!("  {} - {}", entry.app_name, shown_pwd)).style(style)
         })
         .collect();
 
     let list = List::new(items)
         .block(
             Block::default()
-                .title(format!("Password List ({} entries)", app.password_list.len()))
+                .title(if app.list_filter.value.is_empty() {
+                    format!("Password List ({} entries)", app.password_list.len())
+                } else {
+                    format!("Password List ({} of {} entries)", app.password_list.len(), app.all_passwords.len())
+                })
                 .borders(Borders::ALL)
                 .style(Style::default().fg(Color::White)),
         )
         .highlight_style(Style::default().add_modifier(Modifier::BOLD));
 
-    f.render_widget(list, area);
+    f.render_widget(list, chunks[1]);
 }
 
 /// Renders the view single password
@@ -430,11 +563,155 @@ fn render_view(f: &mut Frame, area: Rect, app: &App) {
         .wrap(Wrap { trim: false });
     f.render_widget(password, chunks[1]);
 
-    let info = Paragraph::new("Press Enter or Esc to go back to the list")
-        .style(Style::default().fg(Color::Gray))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL).title("Info"));
-    f.render_widget(info, chunks[2]);
+    let details_text = entry
+        .record
+        .display_lines()
+        .iter()
+        .map(|(label, value)| format!("{}: {}", label, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let details_text = if details_text.is_empty() {
+        "No details stored.\n\nAdd them with: apppass --edit <app> --username ... --url ... --notes ...".to_string()
+    } else {
+        details_text
+    };
+    let details = Paragraph::new(details_text)
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .title("Details (c: copy, h: history, Enter or Esc to go back)")
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, chunks[2]);
+}
+
+/// Renders the password history of the viewed entry
+fn render_history(f: &mut Frame, area: Rect, app: &App) {
+    let app_name = app
+        .password_list
+        .get(app.selected_list_item)
+        .map(|e| e.app_name.as_str())
+        .unwrap_or("");
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    if app.history_list.is_empty() {
+        let empty_msg = Paragraph::new(format!("No previous passwords for '{}'.", app_name))
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Center)
+            .block(Block::default().title("Password History").borders(Borders::ALL));
+        f.render_widget(empty_msg, chunks[0]);
+    } else {
+        let items: Vec<ListItem> = app
+            .history_list
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let style = if i == app.selected_history_item {
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                ListItem::new(format!(
+                    "  {}. {} UTC - {} by {} - {}",
+                    i + 1,
+                    format_timestamp(entry.changed_at),
+                    entry.source,
+                    entry.changed_by,
+                    entry.password
+                ))
+                .style(style)
+            })
+            .collect();
+
+        let list = List::new(items).block(
+            Block::default()
+                .title(format!("Password History of '{}' (most recent first)", app_name))
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::White)),
+        );
+        f.render_widget(list, chunks[0]);
+    }
+
+    // Status message
+    if !app.status_message.is_empty() {
+        let status_color = if app.status_message.starts_with('✓') {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        let status = Paragraph::new(app.status_message.as_str())
+            .style(Style::default().fg(status_color))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+        f.render_widget(status, chunks[1]);
+    }
+}
+
+/// Renders the deleted entries in the trash
+fn render_trash(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    if app.trash_list.is_empty() {
+        let empty_msg = Paragraph::new("The trash is empty.")
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Center)
+            .block(Block::default().title("Trash").borders(Borders::ALL));
+        f.render_widget(empty_msg, chunks[0]);
+    } else {
+        let items: Vec<ListItem> = app
+            .trash_list
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let style = if i == app.selected_trash_item {
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                ListItem::new(format!(
+                    "  {} - deleted {} UTC by {}",
+                    entry.name,
+                    format_timestamp(entry.deleted_at),
+                    entry.deleted_by
+                ))
+                .style(style)
+            })
+            .collect();
+
+        let list = List::new(items).block(
+            Block::default()
+                .title("Trash (most recently deleted first)")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::White)),
+        );
+        f.render_widget(list, chunks[0]);
+    }
+
+    // Status message
+    if !app.status_message.is_empty() {
+        let status_color = if app.status_message.starts_with('✓') {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        let status = Paragraph::new(app.status_message.as_str())
+            .style(Style::default().fg(status_color))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+        f.render_widget(status, chunks[1]);
+    }
 }
 
 /// Renders the update password form
@@ -557,6 +834,7 @@ fn render_update_custom(f: &mut Frame, area: Rect, app: &App) {
             .constraints([
                 Constraint::Length(3),
                 Constraint::Length(3),
+                Constraint::Length(4),
                 Constraint::Length(5),
                 Constraint::Length(3),
             ])
@@ -594,6 +872,7 @@ fn render_update_custom(f: &mut Frame, area: Rect, app: &App) {
                     .borders(Borders::ALL),
             );
         f.render_widget(password_input, chunks[1]);
+        render_strength(f, chunks[2], &app.password_input.value);
 
         // Info section
         let info_text = "ℹ️  Update Custom Password\n\
@@ -603,7 +882,7 @@ fn render_update_custom(f: &mut Frame, area: Rect, app: &App) {
             .style(Style::default().fg(Color::Cyan))
             .block(Block::default().borders(Borders::ALL).title("Info"))
             .wrap(Wrap { trim: false });
-        f.render_widget(info, chunks[2]);
+        f.render_widget(info, chunks[3]);
 
         // Status message
         if !app.status_message.is_empty() {
@@ -615,7 +894,7 @@ fn render_update_custom(f: &mut Frame, area: Rect, app: &App) {
             let status = Paragraph::new(app.status_message.as_str())
                 .style(Style::default().fg(status_color))
                 .block(Block::default().borders(Borders::ALL).title("Status"));
-            f.render_widget(status, chunks[3]);
+            f.render_widget(status, chunks[4]);
         }
 
         // Set cursor position
@@ -812,7 +1091,7 @@ fn render_generate_otp(f: &mut Frame, area: Rect, app: &App) {
         .style(ttl_style)
         .block(
             Block::default()
-                .title("TTL in seconds (default: 300 seconds = 5 minutes)")
+                .title(format!("TTL in seconds (default: {} seconds)", config().otp.ttl))
                 .borders(Borders::ALL),
         );
     f.render_widget(ttl_input, chunks[1]);
@@ -879,9 +1158,13 @@ fn render_memorizable(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(app_name_input, chunks[0]);
 
     // Info section
-    let info_text = "ℹ️  Generate Memorizable Password\n\
-                     Creates easy-to-remember passwords (e.g., Tiger-42-Cloud).\n\
-                     Example: Enter 'blog' to generate a memorable password.";
+    let settings = config().passphrase;
+    let info_text = format!(
+        "ℹ️  Generate Memorizable Password\n\
+         Creates a passphrase of {} random words (e.g., timber-orbit-candle-...).\n\
+         Word count, separator and capitalization are set in config.toml ([passphrase]).",
+        settings.words
+    );
     let info = Paragraph::new(info_text)
         .style(Style::default().fg(Color::Cyan))
         .block(Block::default().borders(Borders::ALL).title("Info"))
@@ -53,6 +53,16 @@ macro_rules! skip_if_no_keyring {
     };
 }
 
+/// Empty configuration directory of the running test, so the developer's `config.toml` and
+/// `profiles.json` (default profile, minimum strength, breach file) do not change the results.
+/// The test harness names each test's thread after the test.
+fn test_config_dir() -> std::path::PathBuf {
+    let test = std::thread::current().name().unwrap_or("main").replace("::", "_");
+    let dir = std::env::temp_dir().join(format!("apppass_int_config_{}_{}", std::process::id(), test));
+    std::fs::create_dir_all(&dir).expect("Failed to create config directory");
+    dir
+}
+
 /// Helper function to run apppass CLI command
 fn run_apppass(args: &[&str]) -> std::process::Output {
     Command::new("cargo")
@@ -60,6 +70,22 @@ fn run_apppass(args: &[&str]) -> std::process::Output {
         .arg("--quiet")
         .arg("--")
         .args(args)
+        .env("APPPASS_CONFIG_DIR", test_config_dir())
+        .output()
+        .expect("Failed to execute apppass")
+}
+
+/// Helper function to run apppass CLI command against an encrypted vault file
+fn run_apppass_vault(vault: &std::path::Path, args: &[&str]) -> std::process::Output {
+    Command::new("cargo")
+        .arg("run")
+        .arg("--quiet")
+        .arg("--")
+        .args(args)
+        .env("APPPASS_BACKEND", "vault")
+        .env("APPPASS_VAULT", vault)
+        .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+        .env("APPPASS_CONFIG_DIR", test_config_dir())
         .output()
         .expect("Failed to execute apppass")
 }
@@ -286,9 +312,9 @@ fn test_integration_memorizable_password() {
     // Should have dashes (word separators)
     assert!(password.contains('-'), "Memorizable password should contain dashes");
     
-    // Should have 3 parts
+    // Should have 6 words
     let parts: Vec<&str> = password.split('-').collect();
-    assert_eq!(parts.len(), 3, "Memorizable password should have 3 parts");
+    assert_eq!(parts.len(), 6, "Memorizable password should have 6 words");
     
     // Cleanup
     cleanup_test_entry(&app_name);
@@ -480,3 +506,897 @@ fn test_integration_password_is_alphanumeric() {
     // Cleanup
     cleanup_test_entry(&app_name);
 }
+
+#[test]
+fn test_integration_vault_backend_roundtrip() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_vault")));
+    let app_name = unique_app_name("int_vault_app");
+
+    let output = run_apppass_vault(&vault, &["--app", &app_name]);
+    assert!(output.status.success(), "Failed to create password in vault");
+    assert!(vault.exists(), "Vault file should be created");
+
+    let contents = std::fs::read_to_string(&vault).unwrap();
+    assert!(!contents.contains(&app_name), "Vault file should not contain plaintext names");
+
+    let output = run_apppass_vault(&vault, &["--get", &app_name]);
+    assert!(output.status.success(), "Failed to get password from vault");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(extract_password(&stdout).is_some(), "Should extract password from vault output");
+
+    // A wrong master password must not open the vault
+    let output = Command::new("cargo")
+        .args(["run", "--quiet", "--", "--get", &app_name])
+        .env("APPPASS_BACKEND", "vault")
+        .env("APPPASS_VAULT", &vault)
+        .env("APPPASS_MASTER_PASSWORD", "wrong-password")
+        .env("APPPASS_CONFIG_DIR", test_config_dir())
+        .output()
+        .expect("Failed to execute apppass");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(extract_password(&stdout).is_none(), "Wrong master password should not reveal secrets");
+    assert!(is_error_output(&stdout) || is_error_output(&stderr) || !output.status.success());
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_entry_details() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_details")));
+    let app_name = unique_app_name("int_details_app");
+
+    let output = run_apppass_vault(&vault, &["--app", &app_name, "--username", "alice", "--url", "https://example.com"]);
+    assert!(output.status.success(), "Failed to create password with details");
+
+    let output = run_apppass_vault(&vault, &["--edit", &app_name, "--notes", "work account", "--field", "pin=1234"]);
+    assert!(output.status.success(), "Failed to edit details");
+    assert!(is_success_output(&String::from_utf8_lossy(&output.stdout)));
+
+    let output = run_apppass_vault(&vault, &["--get", &app_name]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(extract_password(&stdout).is_some(), "Should still show the password");
+    assert!(stdout.contains("Username: alice"));
+    assert!(stdout.contains("URL: https://example.com"));
+    assert!(stdout.contains("Notes: work account"));
+    assert!(stdout.contains("pin: 1234"));
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_profiles_isolate_entries() {
+    let dir = std::env::temp_dir().join(unique_app_name("int_profiles"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let app_name = unique_app_name("int_profile_app");
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+
+    // Unknown profiles are rejected
+    let output = run(&["--profile", "work", "--list"]);
+    assert!(!output.status.success(), "Unknown profile should fail");
+
+    let output = run(&["profile", "create", "work"]);
+    assert!(output.status.success(), "Failed to create profile");
+
+    let output = run(&["--profile", "work", "--app", &app_name]);
+    assert!(output.status.success(), "Failed to create password in profile");
+
+    // Not visible from the default profile
+    let output = run(&["--get", &app_name]);
+    assert!(extract_password(&String::from_utf8_lossy(&output.stdout)).is_none());
+
+    // Visible once 'work' is the default profile
+    let output = run(&["profile", "default", "work"]);
+    assert!(output.status.success(), "Failed to set default profile");
+    let output = run(&["--get", &app_name]);
+    assert!(extract_password(&String::from_utf8_lossy(&output.stdout)).is_some());
+
+    let output = run(&["profile", "list"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("* work (default)"));
+
+    // Deleting the profile deletes its entries
+    let output = run(&["profile", "delete", "work"]);
+    assert!(output.status.success(), "Failed to delete profile");
+    let output = run(&["profile", "create", "work"]);
+    assert!(output.status.success());
+    let output = run(&["--profile", "work", "--get", &app_name]);
+    assert!(extract_password(&String::from_utf8_lossy(&output.stdout)).is_none());
+
+    // Entries are deleted from the vault even when another backend is selected
+    let output = run(&["--profile", "work", "--app", &app_name]);
+    assert!(output.status.success(), "Failed to create password in profile");
+    let work_vault = dir.join("apppass-apppass-work.vault");
+    assert!(work_vault.exists(), "The profile should have its own vault");
+    let output = run(&["--backend", "memory", "profile", "delete", "work"]);
+    assert!(output.status.success(), "Failed to delete profile: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(!work_vault.exists(), "The vault of the deleted profile should be removed");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_concurrent_processes_keep_every_entry() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_concurrent")));
+    let prefix = unique_app_name("int_concurrent_app");
+
+    // Spawn the binary directly so the processes really run at the same time
+    let children: Vec<_> = (0..8)
+        .map(|i| {
+            Command::new(env!("CARGO_BIN_EXE_apppass"))
+                .args(["--app", &format!("{}_{}", prefix, i)])
+                .env("APPPASS_BACKEND", "vault")
+                .env("APPPASS_VAULT", &vault)
+                .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+                .env("APPPASS_CONFIG_DIR", test_config_dir())
+                .stdout(std::process::Stdio::null())
+                .spawn()
+                .expect("Failed to spawn apppass")
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success(), "Concurrent create failed");
+    }
+
+    let output = run_apppass_vault(&vault, &["--list"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for i in 0..8 {
+        let app_name = format!("{}_{}", prefix, i);
+        assert!(stdout.contains(&app_name), "'{}' missing from the list after concurrent creates", app_name);
+    }
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_doctor_reports_healthy_vault() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_doctor")));
+    let app_name = unique_app_name("int_doctor_app");
+
+    let output = run_apppass_vault(&vault, &["--app", &app_name]);
+    assert!(output.status.success(), "Failed to create password in vault");
+
+    let output = run_apppass_vault(&vault, &["doctor", "--json"]);
+    assert!(output.status.success(), "A healthy vault should pass the check");
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("doctor --json should print JSON");
+    assert_eq!(report["backend"], "vault");
+    assert_eq!(report["entries"], 1);
+    assert!(report["issues"].as_array().unwrap().is_empty());
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_concurrent_processes_keep_index_consistent() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_concurrent")));
+    let prefix = unique_app_name("int_concurrent_app");
+
+    // Two apppass processes import and delete entries at the same time. The binary is run
+    // directly so the processes don't queue on cargo's build lock.
+    let config_dir = test_config_dir();
+    let workers: Vec<_> = (0..2)
+        .map(|worker| {
+            let vault = vault.clone();
+            let config_dir = config_dir.clone();
+            let prefix = prefix.clone();
+            std::thread::spawn(move || {
+                let run = |args: &[&str]| {
+                    Command::new(env!("CARGO_BIN_EXE_apppass"))
+                        .args(args)
+                        .env("APPPASS_BACKEND", "vault")
+                        .env("APPPASS_VAULT", &vault)
+                        .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+                        .env("APPPASS_CONFIG_DIR", &config_dir)
+                        .output()
+                        .expect("Failed to execute apppass")
+                };
+                let names: Vec<String> = (0..10).map(|i| format!("{}_{}_{}", prefix, worker, i)).collect();
+                let import_file = vault.with_extension(format!("{}.csv", worker));
+                let rows: String = names.iter().map(|name| format!("{},pw-{}\n", name, name)).collect();
+                std::fs::write(&import_file, rows).unwrap();
+                let output = run(&["--import", import_file.to_str().unwrap()]);
+                assert!(output.status.success(), "Failed to import: {}", String::from_utf8_lossy(&output.stderr));
+                let _ = std::fs::remove_file(&import_file);
+                for name in names.iter().step_by(3) {
+                    let output = run(&["--delete", name]);
+                    assert!(output.status.success(), "Failed to delete {}", name);
+                }
+                names
+            })
+        })
+        .collect();
+    let names: Vec<String> = workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect();
+
+    let output = run_apppass_vault(&vault, &["doctor", "--json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("doctor --json should print JSON");
+    assert!(report["issues"].as_array().unwrap().is_empty(), "Index out of sync: {}", report["issues"]);
+    assert_eq!(report["entries"], 12);
+
+    let output = run_apppass_vault(&vault, &["--list"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for (i, name) in names.iter().enumerate() {
+        let listed = stdout.contains(&format!("Application Name: {}\n", name));
+        assert_eq!(listed, i % 10 % 3 != 0, "{} listed: {}", name, listed);
+    }
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_trash_restore() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_trash")));
+    let app_name = unique_app_name("int_trash_app");
+
+    let output = run_apppass_vault(&vault, &["--app", &app_name, "--username", "alice"]);
+    assert!(output.status.success(), "Failed to create password in vault");
+    let output = run_apppass_vault(&vault, &["--get", &app_name]);
+    let created = extract_password(&String::from_utf8_lossy(&output.stdout));
+    assert!(created.is_some(), "Should extract the created password");
+
+    let output = run_apppass_vault(&vault, &["--delete", &app_name]);
+    assert!(output.status.success(), "Failed to delete password");
+
+    let output = run_apppass_vault(&vault, &["trash", "list"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&app_name), "Deleted entry should be in the trash");
+
+    let output = run_apppass_vault(&vault, &["trash", "restore", &app_name]);
+    assert!(output.status.success(), "Failed to restore from trash");
+
+    let output = run_apppass_vault(&vault, &["--get", &app_name]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(extract_password(&stdout), created, "Restored password should match");
+    assert!(stdout.contains("Username: alice"), "Restored entry should keep its details");
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_library_api() {
+    use std::sync::Arc;
+
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_lib")));
+    let export_file = vault.with_extension("csv");
+    let store = apppass::VaultStore::open(&vault, "integration-master-password").expect("Failed to open vault");
+    apppass::set_store(Arc::new(store));
+
+    apppass::generate_save_safety_password("lib_app", Some(20), None, None).unwrap();
+    let password = apppass::get_from_keyring("lib_app").unwrap();
+    assert_eq!(password.len(), 20);
+    assert!(matches!(
+        apppass::generate_save_safety_password("lib_app", None, None, None),
+        Err(apppass::AppError::AlreadyExists(_))
+    ));
+
+    apppass::export_passwords(export_file.to_str().unwrap()).unwrap();
+    apppass::delete_password("lib_app").unwrap();
+    assert!(apppass::list_entries().unwrap().is_empty());
+    apppass::import_passwords(export_file.to_str().unwrap()).unwrap();
+    assert_eq!(apppass::get_from_keyring("lib_app").unwrap(), password);
+
+    // The binary sees what the library wrote
+    let output = run_apppass_vault(&vault, &["--get", "lib_app"]);
+    assert_eq!(extract_password(&String::from_utf8_lossy(&output.stdout)), Some(password));
+
+    let _ = std::fs::remove_file(&export_file);
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_config_sets_generator_defaults() {
+    let dir = std::env::temp_dir().join(unique_app_name("int_config"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let app_name = unique_app_name("int_config_app");
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+
+    let output = run(&["config", "set", "password.length", "12"]);
+    assert!(output.status.success(), "Failed to set password.length");
+    let output = run(&["config", "set", "password.classes", "digits"]);
+    assert!(output.status.success(), "Failed to set password.classes");
+    let output = run(&["config", "get", "password.length"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "12");
+
+    // Out-of-range values are rejected and leave the file unchanged
+    let output = run(&["config", "set", "password.length", "4"]);
+    assert!(!output.status.success(), "Out-of-range length should fail");
+    let output = run(&["config", "set", "default_profile", "missing"]);
+    assert!(!output.status.success(), "Unknown default profile should fail");
+
+    let output = run(&["--app", &app_name]);
+    assert!(output.status.success(), "Failed to create password");
+    let output = run(&["--get", &app_name]);
+    let password = extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No password");
+    assert_eq!(password.len(), 12);
+    assert!(password.chars().all(|c| c.is_ascii_digit()), "Password should use the configured classes");
+
+    // A malformed file is reported instead of ignored
+    std::fs::write(dir.join("config.toml"), "[password\n").unwrap();
+    let output = run(&["--list"]);
+    assert!(!output.status.success(), "Malformed config should fail");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_copy_does_not_print_the_password() {
+    let dir = std::env::temp_dir().join(unique_app_name("int_copy"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let app_name = unique_app_name("int_copy_app");
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+
+    // Keep the copy so the run doesn't wait for the clear
+    let output = run(&["config", "set", "clipboard.timeout", "0"]);
+    assert!(output.status.success(), "Failed to set clipboard.timeout");
+    let output = run(&["--app", &app_name]);
+    assert!(output.status.success(), "Failed to create password");
+    let output = run(&["--get", &app_name]);
+    let password = extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No password");
+
+    // Copied when there is a clipboard, an error otherwise; never printed
+    let output = run(&["--get", &app_name, "--copy"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("Application Name: {}", app_name)), "No entry in: {}", stdout);
+    assert!(
+        stdout.contains("Password copied to the clipboard") || stdout.contains("Clipboard error"),
+        "No copy result in: {}",
+        stdout
+    );
+    assert!(!stdout.contains(&password), "Password printed with --copy: {}", stdout);
+
+    let output = run(&["--copy"]);
+    assert!(!output.status.success(), "--copy without --get should fail");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_legacy_store_is_migrated() {
+    use apppass::SecretStore;
+
+    let dir = std::env::temp_dir().join(unique_app_name("int_migrate"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+
+    // Data as written by apppass 0.2
+    {
+        let store = apppass::VaultStore::open(&vault, "integration-master-password").expect("Failed to open vault");
+        store.set("apppass_index", "legacy_auto,legacy_custom").unwrap();
+        store.set("legacy_auto", "auto-password").unwrap();
+        store.set("legacy_custom", "custom-password").unwrap();
+        store.set("legacy_custom_type", "custom").unwrap();
+        store.set("password_length", "24").unwrap();
+    }
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+
+    let output = run(&["--list"]);
+    assert!(output.status.success(), "Migration failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Migrated the store"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("legacy_auto") && stdout.contains("legacy_custom"));
+
+    // The length moved to the section of the profile owning the store
+    let output = run(&["--profile", "default", "config", "get", "password.length"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "24");
+    let output = run(&["config", "get", "password.length"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "30");
+
+    // Migrated once only
+    let output = run(&["--get", "legacy_custom"]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Migrated the store"));
+    assert_eq!(extract_password(&String::from_utf8_lossy(&output.stdout)).as_deref(), Some("custom-password"));
+
+    // A store written by a newer apppass is refused
+    {
+        let store = apppass::VaultStore::open(&vault, "integration-master-password").expect("Failed to open vault");
+        store.set("apppass_schema_version", "999").unwrap();
+    }
+    let output = run(&["--list"]);
+    assert!(!output.status.success(), "Newer schema should be refused");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("schema version 999"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_search_and_get_suggestions() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_search")));
+    for (app, args) in [
+        ("github-work", vec!["--username", "octocat"]),
+        ("gitlab", vec!["--tag", "ci"]),
+        ("bank", vec!["--url", "https://online.example-bank.com"]),
+    ] {
+        let mut full = vec!["--app", app];
+        full.extend(args);
+        let output = run_apppass_vault(&vault, &full);
+        assert!(output.status.success(), "Failed to create '{}'", app);
+    }
+
+    let stdout = |args: &[&str]| String::from_utf8_lossy(&run_apppass_vault(&vault, args).stdout).to_string();
+
+    let found = stdout(&["search", "git"]);
+    assert!(found.contains("github-work") && found.contains("gitlab") && !found.contains("bank"));
+    assert!(stdout(&["search", "octo"]).contains("(username: octocat)"));
+    assert!(stdout(&["search", "ci", "--mode", "substring"]).contains("(tag: ci)"));
+    assert!(stdout(&["search", "*bank.com"]).contains("bank"));
+    assert!(stdout(&["search", "zzz"]).contains("No entries match"));
+
+    // A mistyped name suggests the closest entries
+    let output = stdout(&["--get", "gitlba"]);
+    assert!(extract_password(&output).is_none());
+    assert!(output.contains("Did you mean: gitlab"), "No suggestion in: {}", output);
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_password_policy_flags() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_policy")));
+    let app_name = unique_app_name("int_policy_app");
+
+    let output = run_apppass_vault(&vault, &[
+        "--app", &app_name, "--length", "20", "--classes", "digits", "--symbols", "#%",
+        "--min-symbols", "5", "--exclude", "0", "--no-repeats",
+    ]);
+    assert!(output.status.success(), "Failed to create password with a policy");
+    let output = run_apppass_vault(&vault, &["--get", &app_name]);
+    let password = extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No password");
+    assert_eq!(password.len(), 20);
+    assert!(password.chars().filter(|c| "#%".contains(*c)).count() >= 5, "Too few symbols: {}", password);
+    assert!(password.chars().all(|c| "#%123456789".contains(c)), "Unexpected character: {}", password);
+    assert!(password.as_bytes().windows(2).all(|w| w[0] != w[1]), "Repeated character: {}", password);
+
+    // Regenerating takes the policy flags too
+    let output = run_apppass_vault(&vault, &["--update", &app_name, "--classes", "upper", "--no-ambiguous"]);
+    assert!(output.status.success(), "Failed to regenerate password");
+    let output = run_apppass_vault(&vault, &["--get", &app_name]);
+    let password = extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No new password");
+    assert!(password.chars().all(|c| c.is_ascii_uppercase() && c != 'O' && c != 'I'), "{}", password);
+
+    // A policy that cannot be met is reported
+    let output = run_apppass_vault(&vault, &["--app", "int_policy_short", "--length", "8", "--min-digits", "9"]);
+    assert!(is_error_output(&String::from_utf8_lossy(&output.stderr)), "Impossible policy should fail");
+    let output = run_apppass_vault(&vault, &["--get", "int_policy_short"]);
+    assert!(extract_password(&String::from_utf8_lossy(&output.stdout)).is_none());
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_stored_policy_is_reused() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_entry_policy")));
+    let app_name = unique_app_name("int_entry_policy_app");
+    let get_password = || {
+        let output = run_apppass_vault(&vault, &["--get", &app_name]);
+        extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No password")
+    };
+
+    let output = run_apppass_vault(&vault, &["--app", &app_name, "--length", "16", "--classes", "lower,digits", "--exclude", "abc"]);
+    assert!(output.status.success(), "Failed to create password");
+
+    // --update without flags follows the policy stored at creation
+    let output = run_apppass_vault(&vault, &["--update", &app_name]);
+    assert!(output.status.success(), "Failed to regenerate password");
+    let password = get_password();
+    assert_eq!(password.len(), 16);
+    assert!(password.chars().all(|c| (c.is_ascii_lowercase() || c.is_ascii_digit()) && !"abc".contains(c)), "{}", password);
+
+    let output = run_apppass_vault(&vault, &["policy", "show", &app_name]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("length = 16") && stdout.contains("classes = lower,digits"), "{}", stdout);
+
+    // Editing the stored policy changes the next rotation
+    let output = run_apppass_vault(&vault, &["policy", "set", &app_name, "--length", "10", "--classes", "upper"]);
+    assert!(output.status.success(), "Failed to edit policy");
+    run_apppass_vault(&vault, &["--update", &app_name]);
+    let password = get_password();
+    assert_eq!(password.len(), 10);
+    assert!(password.chars().all(|c| c.is_ascii_uppercase()), "{}", password);
+
+    let output = run_apppass_vault(&vault, &["policy", "set", &app_name, "--length", "2", "--min-digits", "3"]);
+    assert!(!output.status.success(), "Impossible policy should be rejected");
+
+    let output = run_apppass_vault(&vault, &["policy", "reset", &app_name]);
+    assert!(output.status.success(), "Failed to reset policy");
+    let output = run_apppass_vault(&vault, &["policy", "show", &app_name]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("no stored policy"));
+
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_passphrase_options() {
+    let vault = std::env::temp_dir().join(format!("{}.vault", unique_app_name("int_passphrase")));
+    let wordlist = vault.with_extension("words");
+    std::fs::write(&wordlist, "11111 copper\n11112 lantern\n11113 meadow\n11114 harbor\n").unwrap();
+    let app_name = unique_app_name("int_passphrase_app");
+
+    let output = run_apppass_vault(&vault, &[
+        "--passphrase", &app_name, "--words", "5", "--separator", ".", "--capitalize", "upper",
+        "--add-digit", "--wordlist", wordlist.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "Failed to create passphrase");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Entropy: "), "No entropy report in: {}", stdout);
+
+    let output = run_apppass_vault(&vault, &["--get", &app_name]);
+    let password = extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No password");
+    let words: Vec<&str> = password.split('.').collect();
+    assert_eq!(words.len(), 5, "{}", password);
+    assert!(words.iter().all(|w| ["COPPER", "LANTERN", "MEADOW", "HARBOR"]
+        .iter()
+        .any(|known| w.trim_end_matches(|c: char| c.is_ascii_digit()) == *known)), "{}", password);
+    assert_eq!(password.chars().filter(|c| c.is_ascii_digit()).count(), 1, "{}", password);
+
+    let _ = std::fs::remove_file(&wordlist);
+    let _ = std::fs::remove_file(&vault);
+    let _ = std::fs::remove_file(vault.with_extension("lock"));
+}
+
+#[test]
+fn test_integration_custom_password_strength() {
+    let dir = std::env::temp_dir().join(unique_app_name("int_strength"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let app_name = unique_app_name("int_strength_app");
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+
+    let output = run(&["--app", &app_name]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Entropy: 178.6 bits (very-strong)"), "No entropy report in: {}", stdout);
+
+    // Without a minimum, weak passwords are saved with a warning
+    let output = run(&["--update-custom", &app_name, "--password", "Summer2024"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Strength: very-weak"), "No strength report in: {}", stdout);
+    assert!(stdout.contains("'Summer' is a common password"), "No pattern in: {}", stdout);
+
+    let output = run(&["config", "set", "password.min_strength", "fair"]);
+    assert!(output.status.success(), "Failed to set password.min_strength");
+    let output = run(&["--update-custom", &app_name, "--password", "qwerty1990"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("too weak"), "Weak password should be rejected: {}", stderr);
+    let output = run(&["--get", &app_name]);
+    let password = extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No password");
+    assert_eq!(password, "Summer2024");
+
+    let output = run(&["--update-custom", &app_name, "--password", "vT8#qL2!zW9m$Kp4"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Password updated successfully"), "{}", stdout);
+    assert!(stdout.contains("Strength: strong") || stdout.contains("Strength: very-strong"), "{}", stdout);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_template_generation() {
+    let dir = std::env::temp_dir().join(unique_app_name("int_template"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let app_name = unique_app_name("int_template_app");
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+    let get_password = || {
+        let output = run(&["--get", &app_name]);
+        extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No password")
+    };
+    let assert_format = |password: &str| {
+        let chars: Vec<char> = password.chars().collect();
+        assert_eq!(chars.len(), 9, "{}", password);
+        assert!(chars[0].is_ascii_uppercase() && chars[1].is_ascii_lowercase(), "{}", password);
+        assert_eq!(chars[2], '-', "{}", password);
+        assert!(chars[3..7].iter().all(|c| c.is_ascii_digit()), "{}", password);
+        assert_eq!(&password[7..], "#x", "{}", password);
+    };
+
+    let output = run(&["--app", &app_name, "--template", "Cv-9{4}#\\x"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let first = get_password();
+    assert_format(&first);
+
+    // --update reuses the stored template
+    let output = run(&["--update", &app_name]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_format(&get_password());
+    let output = run(&["policy", "show", &app_name]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("template = Cv-9{4}#\\x"));
+
+    let output = run(&["--update", &app_name, "--template", "9{4"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid template '9{4': unclosed '{' at position 2"), "{}", stderr);
+    let output = run(&["--app", &app_name, "--template", "x{8}", "--length", "8"]);
+    assert!(!output.status.success(), "--template and --length should conflict");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_pin_generation() {
+    let dir = std::env::temp_dir().join(unique_app_name("int_pin"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let app_name = unique_app_name("int_pin_app");
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+    let get_password = || {
+        let output = run(&["--get", &app_name]);
+        extract_password(&String::from_utf8_lossy(&output.stdout)).expect("No password")
+    };
+    let is_pin = |pin: &str, digits: usize| pin.len() == digits && pin.chars().all(|c| c.is_ascii_digit());
+
+    let output = run(&["config", "set", "pin.length", "8"]);
+    assert!(output.status.success(), "Failed to set pin.length");
+    let output = run(&["--app", &app_name, "--pin"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("PIN saved securely"), "{}", stdout);
+    let pin = get_password();
+    assert!(is_pin(&pin, 8), "{}", pin);
+    assert_eq!(apppass::pin_weakness(&pin), None, "{}", pin);
+
+    // Rotation keeps producing PINs of the same length
+    let output = run(&["--update", &app_name]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let rotated = get_password();
+    assert!(is_pin(&rotated, 8) && rotated != pin, "{}", rotated);
+    run(&["--update", &app_name, "--length", "4"]);
+    assert!(is_pin(&get_password(), 4));
+
+    let output = run(&["--app", &unique_app_name("int_pin_short"), "--pin", "--length", "3"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("PIN length must be between 4 and 12"), "{}", stderr);
+    let output = run(&["--app", &unique_app_name("int_pin_policy"), "--pin", "--min-digits", "2"]);
+    assert!(!output.status.success(), "--pin and policy flags should conflict");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_derive() {
+    let dir = std::env::temp_dir().join(unique_app_name("int_derive"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let site = unique_app_name("int_derive_site");
+
+    let run = |args: &[&str]| {
+        let output = Command::new("cargo")
+            .args(["run", "--quiet", "--", "derive"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .env("APPPASS_DERIVE_SECRET", "correct horse battery staple")
+            .output()
+            .expect("Failed to execute apppass");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let derive = |args: &[&str]| extract_password(&run(args)).expect("No password");
+
+    // Same parameters, same password; the algorithm is fixed
+    let password = derive(&["github", "--counter", "2"]);
+    assert_eq!(password, "Ob06ESeJXzQZ49KJnPfA");
+    assert_eq!(derive(&["GitHub", "--counter", "2"]), password);
+    assert_ne!(derive(&["github", "--counter", "3"]), password);
+
+    // Saved parameters, never the password, are reused
+    let saved = derive(&[&site, "--counter", "4", "--length", "12", "--save"]);
+    assert_eq!(saved.len(), 12);
+    assert_eq!(derive(&[&site]), saved);
+    assert!(run(&["--list"]).contains(&format!("{} (counter 4): 12 chars", site)));
+
+    run(&[&site, "--forget"]);
+    assert!(!run(&["--list"]).contains(&site));
+    assert_ne!(derive(&[&site]), saved);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_breach_check() {
+    use sha1::{Digest, Sha1};
+
+    let dir = std::env::temp_dir().join(unique_app_name("int_breach"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let (breached, safe) = (unique_app_name("int_breach_bad"), unique_app_name("int_breach_good"));
+
+    // A corpus sorted by hash, with Windows line endings like the downloaded files
+    let mut lines: Vec<String> = (0..1000)
+        .map(|i| format!("{:X}:1", Sha1::digest(format!("filler {}", i).as_bytes())))
+        .collect();
+    lines.push(format!("{:X}:42", Sha1::digest(b"Summer2024!")));
+    lines.sort();
+    let corpus = dir.join("corpus.txt");
+    std::fs::write(&corpus, lines.join("\r\n") + "\r\n").unwrap();
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+    let stdout = |output: &std::process::Output| String::from_utf8_lossy(&output.stdout).to_string();
+
+    let output = run(&["check"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No breach file"));
+
+    // Import reports breached custom passwords but still imports them
+    let csv = dir.join("import.csv");
+    std::fs::write(&csv, format!("name,password,type\n{},Summer2024!,custom\n{},q8#Lr2vX!m9Tz,custom\n", breached, safe)).unwrap();
+    run(&["config", "set", "password.breach_file", corpus.to_str().unwrap()]);
+    let output = run(&["--import", csv.to_str().unwrap()]);
+    assert!(stdout(&output).contains(&format!("{} (found 42 times)", breached)), "{}", stdout(&output));
+    assert!(!stdout(&output).contains(&safe));
+
+    let output = run(&["check", &breached, &safe]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains(&format!("{}: found 42 times in known breaches", breached)));
+    assert!(stdout(&output).contains(&format!("{}: not found", safe)));
+    let output = run(&["check", &safe]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+
+    // Updating to a breached password reports it, and min_strength rejects it
+    let output = run(&["--update-custom", &safe, "--password", "Summer2024!"]);
+    assert!(stdout(&output).contains("very-weak (21.5 bits, found 42 times in known breaches)"), "{}", stdout(&output));
+    run(&["config", "set", "password.min_strength", "weak"]);
+    let output = run(&["--update-custom", &safe, "--password", "Summer2024!"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Password is too weak"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_integration_audit() {
+    let dir = std::env::temp_dir().join(unique_app_name("int_audit"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let vault = dir.join("apppass.vault");
+    let (first, second, generated) = (
+        unique_app_name("int_audit_a"),
+        unique_app_name("int_audit_b"),
+        unique_app_name("int_audit_gen"),
+    );
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .args(args)
+            .env("APPPASS_BACKEND", "vault")
+            .env("APPPASS_VAULT", &vault)
+            .env("APPPASS_MASTER_PASSWORD", "integration-master-password")
+            .env("APPPASS_CONFIG_DIR", &dir)
+            .output()
+            .expect("Failed to execute apppass")
+    };
+    let stdout = |output: &std::process::Output| String::from_utf8_lossy(&output.stdout).to_string();
+
+    let output = run(&["audit"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert!(stdout(&output).contains("No findings"));
+
+    // Two custom entries sharing a weak password, and a generated one
+    let csv = dir.join("import.csv");
+    std::fs::write(&csv, format!("name,password,type,username\n{},Summer2024!,custom,alice\n{},Summer2024!,custom,\n", first, second)).unwrap();
+    run(&["--import", csv.to_str().unwrap()]);
+    run(&["--app", &generated, "--length", "24"]);
+
+    let output = run(&["audit", "--format", "json"]);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout(&output));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["entries"]["custom"], 2);
+    assert_eq!(report["entries"]["generated"], 1);
+    let findings = report["findings"].as_array().unwrap();
+    let entries_of = |kind: &str| -> Vec<String> {
+        findings
+            .iter()
+            .filter(|finding| finding["kind"] == kind)
+            .flat_map(|finding| finding["entries"].as_array().unwrap().iter())
+            .map(|entry| entry.as_str().unwrap().to_string())
+            .collect()
+    };
+    let mut both = vec![first.clone(), second.clone()];
+    both.sort();
+    assert_eq!(entries_of("weak"), both);
+    assert_eq!(entries_of("reused"), both);
+    let mut missing = vec![generated.clone(), second.clone()];
+    missing.sort();
+    assert_eq!(entries_of("missing-metadata"), missing);
+    assert!(entries_of("old").is_empty());
+
+    let output = run(&["audit", "--format", "markdown", "--fail-on", "expired-otp,old"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert!(stdout(&output).starts_with("# apppass security audit"));
+    assert!(stdout(&output).contains("## Reused passwords (1)"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}